@@ -1,59 +1,212 @@
 use reqwest;
 use serde_json::{self, Value};
-use ethers_core::abi::Event;
+use ethers_core::abi::{Abi, Event};
 use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 
+/// storage slot holding the implementation address of an EIP-1967 proxy
+pub const EIP1967_IMPLEMENTATION_SLOT: &str =
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+
+/// Where to find the implementation address of a proxy contract
+pub enum ImplementationSource {
+    /// read the EIP-1967 implementation slot of the proxy through a json rpc endpoint
+    StorageSlot { rpc_url: String },
+    /// use an explicit implementation address
+    Address(String),
+}
+
 pub struct EtherscanClient {
     base_url: String,
     api_key: String,
+    max_retries: u32,
+    retry_delay: Duration,
+    chain_id: u64,
+    cache: Option<AbiCache>,
+}
+
+/// On-disk cache of fetched ABIs, keyed by chain id and contract address
+struct AbiCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl AbiCache {
+    fn path(&self, chain_id: u64, contract_address: &str) -> PathBuf {
+        self.dir.join(chain_id.to_string()).join(format!("{}.json", contract_address.to_lowercase()))
+    }
+
+    /// cached ABI, if present and younger than the ttl
+    fn read(&self, path: &Path) -> Option<Abi> {
+        let age = fs::metadata(path).ok()?.modified().ok()?.elapsed().ok()?;
+        if age >= self.ttl {
+            return None;
+        }
+        serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+    }
+
+    fn write(&self, path: &Path, abi_value: &Value) -> Result<(), Box<dyn Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(abi_value)?)?;
+        Ok(())
+    }
 }
 
 impl EtherscanClient {
     pub fn new(api_key: String) -> Self {
+        Self::new_with_base_url("https://api.etherscan.io/api".to_string(), api_key)
+    }
+
+    pub fn new_with_base_url(base_url: String, api_key: String) -> Self {
         EtherscanClient {
-            base_url: "https://api.etherscan.io/api".to_string(),
+            base_url,
             api_key,
+            max_retries: 3,
+            retry_delay: Duration::from_secs(1),
+            chain_id: 1,
+            cache: None,
         }
     }
 
-    pub async fn get_abi(&self, contract_address: &str) -> Result<Vec<Event>, Box<dyn Error>> {
+    /// Cache successfully fetched ABIs in `cache_dir`, reusing them for up to `ttl`
+    pub fn with_cache_dir(mut self, cache_dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        self.cache = Some(AbiCache { dir: cache_dir.into(), ttl });
+        self
+    }
+
+    /// Retry up to `max_retries` times, waiting `retry_delay` between attempts, when the
+    /// explorer's rate limit is reached
+    pub fn with_retries(mut self, max_retries: u32, retry_delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    pub fn for_chain(chain_id: u64, api_key: String) -> Result<Self, Box<dyn Error>> {
+        let base_url = match chain_id {
+            1 => "https://api.etherscan.io/api",
+            5 => "https://api-goerli.etherscan.io/api",
+            10 => "https://api-optimistic.etherscan.io/api",
+            56 => "https://api.bscscan.com/api",
+            100 => "https://api.gnosisscan.io/api",
+            137 => "https://api.polygonscan.com/api",
+            250 => "https://api.ftmscan.com/api",
+            8453 => "https://api.basescan.org/api",
+            42161 => "https://api.arbiscan.io/api",
+            11155111 => "https://api-sepolia.etherscan.io/api",
+            _ => return Err(format!("No known explorer for chain id {}", chain_id).into()),
+        };
+        let mut client = Self::new_with_base_url(base_url.to_string(), api_key);
+        client.chain_id = chain_id;
+        Ok(client)
+    }
+
+    pub async fn get_full_abi(&self, contract_address: &str) -> Result<Abi, Box<dyn Error>> {
+        let cache_path = self.cache.as_ref().map(|cache| cache.path(self.chain_id, contract_address));
+        if let (Some(cache), Some(path)) = (&self.cache, &cache_path) {
+            if let Some(abi) = cache.read(path) {
+                return Ok(abi);
+            }
+        }
+
         let url = format!(
             "{}?module=contract&action=getabi&address={}&format=raw&apikey={}",
             self.base_url, contract_address, self.api_key
         );
 
-        let resp = reqwest::get(&url).await?.text().await?;
-        let abi_value: Value = serde_json::from_str(&resp)?;
+        let mut attempt = 0;
+        loop {
+            let resp = reqwest::get(&url).await?.text().await?;
+            let abi_value: Value = serde_json::from_str(&resp)?;
 
-        // Add error handling for the specific API response
-        if let Value::Object(obj) = &abi_value {
-            if let Some(Value::String(status)) = obj.get("status") {
-                if status == "0" {
-                    return Err("Invalid contract address".into());
+            // Add error handling for the specific API response
+            if let Value::Object(obj) = &abi_value {
+                if let Some(Value::String(status)) = obj.get("status") {
+                    if status == "0" {
+                        let result = obj.get("result").and_then(Value::as_str).unwrap_or_default();
+                        if !is_rate_limit_message(result) {
+                            return Err(format!("Invalid contract address: {}", result).into());
+                        }
+                        if attempt >= self.max_retries {
+                            return Err(format!("Rate limit reached after {} retries: {}", attempt, result).into());
+                        }
+                        attempt += 1;
+                        tokio::time::sleep(self.retry_delay).await;
+                        continue;
+                    }
                 }
             }
+
+            let abi = serde_json::from_value::<Abi>(abi_value.clone())?;
+            // only successfully parsed ABIs are cached, a failed write just skips the cache
+            if let (Some(cache), Some(path)) = (&self.cache, &cache_path) {
+                let _ = cache.write(path, &abi_value);
+            }
+            return Ok(abi);
         }
+    }
 
+    pub async fn get_abi(&self, contract_address: &str) -> Result<Vec<Event>, Box<dyn Error>> {
+        let abi = self.get_full_abi(contract_address).await?;
+        Ok(abi.events().cloned().collect())
+    }
 
-        let mut events = Vec::new();
-        if let Value::Array(abis) = abi_value {
-            for abi in abis {
-                if let Value::Object(obj) = abi {
-                    if let Some(Value::String(type_str)) = obj.get("type") {
-                        if type_str == "event" {
-                            if let Ok(event) = serde_json::from_value::<Event>(Value::Object(obj.clone())) {
-                                events.push(event);
-                            }
-                        }
-                    }
-                }
+    /// Events of a proxy contract merged with the events of its implementation
+    pub async fn get_abi_resolving_proxy(
+        &self,
+        proxy_address: &str,
+        implementation: &ImplementationSource,
+    ) -> Result<Vec<Event>, Box<dyn Error>> {
+        let implementation_address = match implementation {
+            ImplementationSource::StorageSlot { rpc_url } => {
+                read_implementation_address(rpc_url, proxy_address).await?
+            }
+            ImplementationSource::Address(address) => address.clone(),
+        };
+
+        let mut events = self.get_abi(proxy_address).await?;
+        for event in self.get_abi(&implementation_address).await? {
+            let is_duplicate = events
+                .iter()
+                .any(|known| known.signature() == event.signature() && known.anonymous == event.anonymous);
+            if !is_duplicate {
+                events.push(event);
             }
         }
         Ok(events)
     }
 }
 
+/// Read the implementation address stored in the EIP-1967 slot of a proxy
+pub async fn read_implementation_address(rpc_url: &str, proxy_address: &str) -> Result<String, Box<dyn Error>> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_getStorageAt",
+        "params": [proxy_address, EIP1967_IMPLEMENTATION_SLOT, "latest"],
+    });
+    let response: Value = reqwest::Client::new().post(rpc_url).json(&request).send().await?.json().await?;
+    let slot = response
+        .get("result")
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("Could not read implementation slot: {}", response))?;
+
+    let slot = slot.trim_start_matches("0x");
+    if slot.len() < 40 || slot.chars().all(|c| c == '0') {
+        return Err(format!("{} is not an EIP-1967 proxy", proxy_address).into());
+    }
+    Ok(format!("0x{}", &slot[slot.len() - 40..]))
+}
+
+fn is_rate_limit_message(result: &str) -> bool {
+    result.to_lowercase().contains("rate limit")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -61,6 +214,203 @@ mod tests {
     // use mockito::{mock, server_url};
 
 
+    #[test]
+    fn for_chain_known_chain_ids() {
+        let client = EtherscanClient::for_chain(137, "key".to_string()).unwrap();
+        assert_eq!(client.base_url, "https://api.polygonscan.com/api");
+        let client = EtherscanClient::for_chain(42161, "key".to_string()).unwrap();
+        assert_eq!(client.base_url, "https://api.arbiscan.io/api");
+    }
+
+    #[test]
+    fn for_chain_unknown_chain_id() {
+        assert!(EtherscanClient::for_chain(123456789, "key".to_string()).is_err());
+    }
+
+    const MIXED_ABI: &str = r#"[
+        {"type":"function","name":"transfer","inputs":[{"name":"to","type":"address"},{"name":"amount","type":"uint256"}],"outputs":[{"name":"","type":"bool"}],"stateMutability":"nonpayable"},
+        {"type":"event","name":"Transfer","inputs":[{"name":"from","type":"address","indexed":true},{"name":"to","type":"address","indexed":true},{"name":"amount","type":"uint256","indexed":false}],"anonymous":false},
+        {"type":"error","name":"InsufficientBalance","inputs":[{"name":"available","type":"uint256"}]}
+    ]"#;
+
+    #[tokio::test]
+    async fn get_full_abi_includes_functions_and_errors() {
+        let server = httpmock::MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.path("/api").query_param("action", "getabi");
+                then.status(200).body(MIXED_ABI);
+            })
+            .await;
+        let client = EtherscanClient::new_with_base_url(server.url("/api"), "key".to_string());
+
+        let abi = client.get_full_abi("0x0").await.unwrap();
+        assert_eq!(abi.functions().count(), 1);
+        assert_eq!(abi.events().count(), 1);
+        assert_eq!(abi.errors().count(), 1);
+
+        let events = client.get_abi("0x0").await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name, "Transfer");
+    }
+
+    #[tokio::test]
+    async fn get_full_abi_retries_rate_limit() {
+        let server = httpmock::MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.path("/api");
+                then.status(200)
+                    .body(r#"{"status":"0","message":"NOTOK","result":"Max rate limit reached"}"#);
+            })
+            .await;
+        let client = EtherscanClient::new_with_base_url(server.url("/api"), "key".to_string())
+            .with_retries(2, Duration::ZERO);
+
+        let error = client.get_full_abi("0x0").await.unwrap_err();
+        assert!(error.to_string().contains("Rate limit reached after 2 retries"));
+        assert_eq!(mock.hits_async().await, 3);
+    }
+
+    #[tokio::test]
+    async fn get_full_abi_invalid_address_is_not_retried() {
+        let server = httpmock::MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.path("/api");
+                then.status(200)
+                    .body(r#"{"status":"0","message":"NOTOK","result":"Invalid Address format"}"#);
+            })
+            .await;
+        let client = EtherscanClient::new_with_base_url(server.url("/api"), "key".to_string())
+            .with_retries(2, Duration::ZERO);
+
+        let error = client.get_full_abi("0x0").await.unwrap_err();
+        assert_eq!(error.to_string(), "Invalid contract address: Invalid Address format");
+        assert_eq!(mock.hits_async().await, 1);
+    }
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("abi_cache_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[tokio::test]
+    async fn get_full_abi_reads_cache() {
+        let server = httpmock::MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.path("/api");
+                then.status(200).body(MIXED_ABI);
+            })
+            .await;
+        let dir = cache_dir("hit");
+        let client = EtherscanClient::new_with_base_url(server.url("/api"), "key".to_string())
+            .with_cache_dir(&dir, Duration::from_secs(3600));
+
+        client.get_full_abi("0xABC").await.unwrap();
+        let abi = client.get_full_abi("0xabc").await.unwrap();
+        assert_eq!(abi.events().count(), 1);
+        assert_eq!(mock.hits_async().await, 1);
+        assert!(dir.join("1").join("0xabc.json").exists());
+    }
+
+    #[tokio::test]
+    async fn get_full_abi_refetches_expired_cache() {
+        let server = httpmock::MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.path("/api");
+                then.status(200).body(MIXED_ABI);
+            })
+            .await;
+        let client = EtherscanClient::new_with_base_url(server.url("/api"), "key".to_string())
+            .with_cache_dir(cache_dir("expired"), Duration::ZERO);
+
+        client.get_full_abi("0xabc").await.unwrap();
+        client.get_full_abi("0xabc").await.unwrap();
+        assert_eq!(mock.hits_async().await, 2);
+    }
+
+    #[tokio::test]
+    async fn get_full_abi_does_not_cache_errors() {
+        let server = httpmock::MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.path("/api");
+                then.status(200)
+                    .body(r#"{"status":"0","message":"NOTOK","result":"Invalid Address format"}"#);
+            })
+            .await;
+        let dir = cache_dir("error");
+        let client = EtherscanClient::new_with_base_url(server.url("/api"), "key".to_string())
+            .with_cache_dir(&dir, Duration::from_secs(3600));
+
+        assert!(client.get_full_abi("0xabc").await.is_err());
+        assert!(client.get_full_abi("0xabc").await.is_err());
+        assert_eq!(mock.hits_async().await, 2);
+        assert!(!dir.join("1").join("0xabc.json").exists());
+    }
+
+    const PROXY_ABI: &str = r#"[
+        {"type":"event","name":"Upgraded","inputs":[{"name":"implementation","type":"address","indexed":true}],"anonymous":false}
+    ]"#;
+
+    #[tokio::test]
+    async fn get_abi_resolving_proxy_reads_implementation_slot() {
+        let server = httpmock::MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.path("/rpc").body_contains(EIP1967_IMPLEMENTATION_SLOT);
+                then.status(200).body(
+                    r#"{"jsonrpc":"2.0","id":1,"result":"0x0000000000000000000000001111111111111111111111111111111111111111"}"#,
+                );
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.path("/api").query_param("address", "0xproxy");
+                then.status(200).body(PROXY_ABI);
+            })
+            .await;
+        let implementation = server
+            .mock_async(|when, then| {
+                when.path("/api").query_param("address", "0x1111111111111111111111111111111111111111");
+                then.status(200).body(MIXED_ABI);
+            })
+            .await;
+        let client = EtherscanClient::new_with_base_url(server.url("/api"), "key".to_string());
+
+        let source = ImplementationSource::StorageSlot { rpc_url: server.url("/rpc") };
+        let events = client.get_abi_resolving_proxy("0xproxy", &source).await.unwrap();
+        let names: Vec<_> = events.iter().map(|event| event.name.as_str()).collect();
+        assert_eq!(names, vec!["Upgraded", "Transfer"]);
+        implementation.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn get_abi_resolving_proxy_with_explicit_implementation() {
+        let server = httpmock::MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.path("/api").query_param("address", "0xproxy");
+                then.status(200).body(MIXED_ABI);
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.path("/api").query_param("address", "0ximplementation");
+                then.status(200).body(MIXED_ABI);
+            })
+            .await;
+        let client = EtherscanClient::new_with_base_url(server.url("/api"), "key".to_string());
+
+        let source = ImplementationSource::Address("0ximplementation".to_string());
+        let events = client.get_abi_resolving_proxy("0xproxy", &source).await.unwrap();
+        assert_eq!(events.len(), 1);
+    }
+
     #[tokio::test]
     async fn fetch_abi_with_api_key() {
         env::set_var("apikey", "sample_api_key");
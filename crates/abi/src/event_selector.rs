@@ -1,21 +1,54 @@
-use ethers_core::abi::Event;
-use std::io::{self, Write};
+use ethers_core::{abi::Event, types::H256};
+use std::io::{self, BufRead, Write};
 
 pub struct EventSelector;
 
 impl EventSelector {
-    pub fn select_event(events: &[Event]) -> Result<&Event, String> {
+    pub fn select_event<R: BufRead, W: Write>(
+        events: &[Event],
+        mut input: R,
+        mut output: W,
+    ) -> Result<&Event, String> {
         for (i, event) in events.iter().enumerate() {
-            println!("{}: {}", i + 1, event.name);
+            let signature = Self::event_signature(event);
+            if event.anonymous {
+                writeln!(output, "{}: {} (anonymous, no topic0)", i + 1, signature)
+            } else {
+                writeln!(output, "{}: {} {:?}", i + 1, signature, event.signature())
+            }
+            .map_err(|e| e.to_string())?;
         }
 
-        print!("Select an event: ");
-        io::stdout().flush().unwrap();
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
-        let choice: usize = input.trim().parse().map_err(|_| "Invalid input".to_string())?;
+        write!(output, "Select an event: ").map_err(|e| e.to_string())?;
+        output.flush().map_err(|e| e.to_string())?;
+        let mut line = String::new();
+        input.read_line(&mut line).map_err(|e| e.to_string())?;
+        let choice: usize = line.trim().parse().map_err(|_| "Invalid input".to_string())?;
 
-        events.get(choice - 1).ok_or_else(|| "Event not found".to_string())
+        let event = choice
+            .checked_sub(1)
+            .and_then(|index| events.get(index))
+            .ok_or_else(|| "Event not found".to_string())?;
+        if event.anonymous {
+            return Err(format!("Event {} is anonymous and has no topic0", event.name));
+        }
+        Ok(event)
+    }
+
+    /// event whose topic0 is `topic0`, anonymous events have no topic0 and never match
+    pub fn select_event_by_topic0(events: &[Event], topic0: H256) -> Option<&Event> {
+        events.iter().find(|event| !event.anonymous && event.signature() == topic0)
+    }
+
+    /// canonical signature of an event, e.g. Transfer(address,address,uint256)
+    pub fn event_signature(event: &Event) -> String {
+        let name = if event.name.is_empty() { "<unnamed>" } else { event.name.as_str() };
+        let inputs: Vec<String> = event.inputs.iter().map(|input| input.kind.to_string()).collect();
+        format!("{}({})", name, inputs.join(","))
+    }
+
+    pub fn select_event_stdin(events: &[Event]) -> Result<&Event, String> {
+        Self::select_event(events, io::stdin().lock(), io::stdout())
     }
 }
 
@@ -23,6 +56,7 @@ impl EventSelector {
 mod event_selector_tests {
     use super::*;
     use ethers_core::abi::{ParamType, EventParam, Event};
+    use std::io::Cursor;
 
     fn mock_event(name: &str) -> Event {
         Event {
@@ -36,16 +70,88 @@ mod event_selector_tests {
         }
     }
 
+    fn mock_anonymous_event(name: &str) -> Event {
+        Event { anonymous: true, ..mock_event(name) }
+    }
+
     #[test]
     fn select_first_event() {
         let events = vec![mock_event("Event1"), mock_event("Event2")];
-        // Simulate user input for selecting the first event
-        // This part needs a way to mock standard input, or a change in the
-        // EventSelector to allow injecting input sources for testing.
-        let selected_event = EventSelector::select_event(&events);
+        let mut output = Vec::new();
+        let selected_event = EventSelector::select_event(&events, Cursor::new("1\n"), &mut output);
         assert!(selected_event.is_ok());
         assert_eq!(selected_event.unwrap().name, "Event1");
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("1: Event1"));
+        assert!(output.contains("2: Event2"));
+    }
+
+    #[test]
+    fn select_out_of_range_event() {
+        let events = vec![mock_event("Event1"), mock_event("Event2")];
+        for input in ["0\n", "3\n"] {
+            let selected_event = EventSelector::select_event(&events, Cursor::new(input), io::sink());
+            assert_eq!(selected_event.unwrap_err(), "Event not found");
+        }
+    }
+
+    #[test]
+    fn select_non_numeric_input() {
+        let events = vec![mock_event("Event1"), mock_event("Event2")];
+        let selected_event = EventSelector::select_event(&events, Cursor::new("first\n"), io::sink());
+        assert_eq!(selected_event.unwrap_err(), "Invalid input");
     }
 
-    // Additional tests could include invalid selections, no selection, etc.
+    #[test]
+    fn select_anonymous_event() {
+        let events = vec![mock_event("Event1"), mock_anonymous_event("Event2")];
+        let mut output = Vec::new();
+        let selected_event = EventSelector::select_event(&events, Cursor::new("2\n"), &mut output);
+        assert_eq!(selected_event.unwrap_err(), "Event Event2 is anonymous and has no topic0");
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("2: Event2(address) (anonymous, no topic0)"));
+    }
+
+    #[test]
+    fn select_event_by_matching_topic0() {
+        let events = vec![mock_event("Event1"), mock_event("Event2")];
+        let topic0 = events[1].signature();
+        let selected_event = EventSelector::select_event_by_topic0(&events, topic0);
+        assert_eq!(selected_event.unwrap().name, "Event2");
+    }
+
+    #[test]
+    fn select_event_by_unknown_topic0() {
+        let events = vec![mock_event("Event1"), mock_anonymous_event("Event2")];
+        let anonymous_topic0 = events[1].signature();
+        assert!(EventSelector::select_event_by_topic0(&events, H256::zero()).is_none());
+        assert!(EventSelector::select_event_by_topic0(&events, anonymous_topic0).is_none());
+    }
+
+    #[test]
+    fn list_overloaded_events_by_signature() {
+        let transfer = |inputs: Vec<ParamType>| Event {
+            name: "Transfer".to_string(),
+            inputs: inputs
+                .into_iter()
+                .map(|kind| EventParam { name: String::new(), kind, indexed: false })
+                .collect(),
+            anonymous: false,
+        };
+        let events = vec![
+            transfer(vec![ParamType::Address, ParamType::Address, ParamType::Uint(256)]),
+            transfer(vec![ParamType::Address, ParamType::Uint(256)]),
+        ];
+        let mut output = Vec::new();
+        let selected_event = EventSelector::select_event(&events, Cursor::new("2\n"), &mut output);
+        assert_eq!(selected_event.unwrap().inputs.len(), 2);
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            "1: Transfer(address,address,uint256) 0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        ));
+        assert!(output.contains("2: Transfer(address,uint256) 0x"));
+    }
 }
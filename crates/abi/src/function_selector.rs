@@ -0,0 +1,81 @@
+use ethers_core::abi::Function;
+use std::io::{self, BufRead, Write};
+
+pub struct FunctionSelector;
+
+impl FunctionSelector {
+    pub fn select_function<R: BufRead, W: Write>(
+        functions: &[Function],
+        mut input: R,
+        mut output: W,
+    ) -> Result<&Function, String> {
+        for (i, function) in functions.iter().enumerate() {
+            writeln!(output, "{}: {}", i + 1, function.signature()).map_err(|e| e.to_string())?;
+        }
+
+        write!(output, "Select a function: ").map_err(|e| e.to_string())?;
+        output.flush().map_err(|e| e.to_string())?;
+        let mut line = String::new();
+        input.read_line(&mut line).map_err(|e| e.to_string())?;
+        let choice: usize = line.trim().parse().map_err(|_| "Invalid input".to_string())?;
+
+        choice
+            .checked_sub(1)
+            .and_then(|index| functions.get(index))
+            .ok_or_else(|| "Function not found".to_string())
+    }
+
+    pub fn select_function_stdin(functions: &[Function]) -> Result<&Function, String> {
+        Self::select_function(functions, io::stdin().lock(), io::stdout())
+    }
+
+    pub fn selector_hex(function: &Function) -> String {
+        let selector: String =
+            function.short_signature().iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!("0x{}", selector)
+    }
+}
+
+#[cfg(test)]
+mod function_selector_tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn mock_function(name: &str, input_types: &[&str]) -> Function {
+        let inputs: Vec<_> = input_types
+            .iter()
+            .map(|kind| serde_json::json!({"name": "", "type": kind}))
+            .collect();
+        let function = serde_json::json!({
+            "type": "function",
+            "name": name,
+            "inputs": inputs,
+            "outputs": [],
+            "stateMutability": "nonpayable",
+        });
+        serde_json::from_value(function).unwrap()
+    }
+
+    #[test]
+    fn select_second_function() {
+        let functions = vec![
+            mock_function("totalSupply", &[]),
+            mock_function("transfer", &["address", "uint256"]),
+        ];
+        let selected = FunctionSelector::select_function(&functions, Cursor::new("2\n"), io::sink());
+        assert_eq!(selected.unwrap().name, "transfer");
+    }
+
+    #[test]
+    fn select_out_of_range_function() {
+        let functions = vec![mock_function("totalSupply", &[])];
+        let selected = FunctionSelector::select_function(&functions, Cursor::new("2\n"), io::sink());
+        assert_eq!(selected.unwrap_err(), "Function not found");
+    }
+
+    #[test]
+    fn erc20_transfer_selector() {
+        let transfer = mock_function("transfer", &["address", "uint256"]);
+        assert_eq!(FunctionSelector::selector_hex(&transfer), "0xa9059cbb");
+    }
+}
@@ -1,9 +1,12 @@
 pub mod etherscan_client;
 pub mod event_selector;
+pub mod function_selector;
 
 use std::env;
-use etherscan_client::EtherscanClient;
+use std::time::Duration;
+use etherscan_client::{EtherscanClient, ImplementationSource};
 use event_selector::EventSelector;
+use function_selector::FunctionSelector;
 
 #[tokio::main]
 async fn main() {
@@ -19,16 +22,50 @@ async fn run() -> Result<(), String> {
         return Err("Please provide a contract address".into());
     }
     let contract_address = &args[1];
+    let mode = args.get(2).map(|mode| mode.as_str()).unwrap_or("events");
 
     let api_key = env::var("apikey").map_err(|_| "API key not set in environment".to_string())?;
 
-    let client = EtherscanClient::new(api_key);
-    let events = client.get_abi(contract_address).await.map_err(|e| e.to_string())?;
+    let client = match env::var("chainid") {
+        Ok(chain_id) => {
+            let chain_id = chain_id.parse().map_err(|_| "Invalid chain id".to_string())?;
+            EtherscanClient::for_chain(chain_id, api_key).map_err(|e| e.to_string())?
+        }
+        Err(_) => EtherscanClient::new(api_key),
+    };
+    let client = match env::var("cache_dir") {
+        Ok(cache_dir) => client.with_cache_dir(cache_dir, Duration::from_secs(24 * 60 * 60)),
+        Err(_) => client,
+    };
+    // proxies are resolved through an explicit implementation address or an rpc endpoint
+    let implementation = match (env::var("implementation"), env::var("rpc_url")) {
+        (Ok(address), _) => Some(ImplementationSource::Address(address)),
+        (Err(_), Ok(rpc_url)) => Some(ImplementationSource::StorageSlot { rpc_url }),
+        _ => None,
+    };
 
-
-    let event = EventSelector::select_event(&events)?;
-    let topic_0 = event.signature();
-    println!("Selected Event: {}, Topic 0: {:?}", event.name, topic_0);
+    match mode {
+        "events" => {
+            let events = match &implementation {
+                Some(implementation) => client
+                    .get_abi_resolving_proxy(contract_address, implementation)
+                    .await
+                    .map_err(|e| e.to_string())?,
+                None => client.get_abi(contract_address).await.map_err(|e| e.to_string())?,
+            };
+            let event = EventSelector::select_event_stdin(&events)?;
+            let topic_0 = event.signature();
+            println!("Selected Event: {}, Topic 0: {:?}", event.name, topic_0);
+        }
+        "functions" => {
+            let abi = client.get_full_abi(contract_address).await.map_err(|e| e.to_string())?;
+            let functions: Vec<_> = abi.functions().cloned().collect();
+            let function = FunctionSelector::select_function_stdin(&functions)?;
+            let selector = FunctionSelector::selector_hex(function);
+            println!("Selected Function: {}, Selector: {}", function.name, selector);
+        }
+        _ => return Err(format!("Unknown mode {}, expected events or functions", mode)),
+    }
 
     Ok(())
 }
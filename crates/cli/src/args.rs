@@ -26,6 +26,10 @@ pub struct Args {
     #[arg(short, long, allow_negative_numbers = true, help_heading = "Content Options", num_args(1..))]
     pub blocks: Option<Vec<String>>,
 
+    /// Block hashes, collected as the blocks they resolve to
+    #[arg(long, value_name = "HASH", help_heading = "Content Options", num_args(1..))]
+    pub block_hashes: Option<Vec<String>>,
+
     /// Transaction hashes, see syntax below
     #[arg(
         short,
@@ -74,6 +78,11 @@ pub struct Args {
     #[arg(long, help_heading = "Content Options")]
     pub hex: bool,
 
+    /// Encoding of binary columns: binary, hex, or prefix_hex
+    /// [default: binary for parquet and arrow, prefix_hex otherwise]
+    #[arg(long, value_name = "FORMAT", help_heading = "Content Options", verbatim_doc_comment)]
+    pub binary_column_format: Option<String>,
+
     /// Columns(s) to sort by, `none` for unordered
     #[arg(short, long, num_args(0..), help_heading="Content Options")]
     pub sort: Option<Vec<String>>,
@@ -82,10 +91,34 @@ pub struct Args {
     #[arg(long, help_heading = "Content Options")]
     pub exclude_failed: bool,
 
-    /// RPC url [default: ETH_RPC_URL env var]
+    /// Keep traces of all, success, or reverted transactions [default: all]
+    #[arg(long, value_name = "STATUS", help_heading = "Content Options")]
+    pub trace_status: Option<String>,
+
+    /// RPC url, http(s), ws(s), or ipc path [default: ETH_RPC_URL env var]
     #[arg(short, long, help_heading = "Source Options")]
     pub rpc: Option<String>,
 
+    /// Fallback RPC url(s), tried in order when the primary fails
+    #[arg(long, value_name = "URL", num_args(1..), help_heading = "Source Options")]
+    pub fallback_rpc: Option<Vec<String>>,
+
+    /// Http header(s) sent with every rpc request, e.g. "Authorization: Bearer <token>"
+    #[arg(long, value_name = "HEADER", num_args(1..), help_heading = "Source Options")]
+    pub rpc_header: Option<Vec<String>>,
+
+    /// Expected chain id, errors if the rpc reports a different one
+    #[arg(long, help_heading = "Source Options")]
+    pub chain_id: Option<u64>,
+
+    /// Use --chain-id without querying the rpc for its chain id
+    #[arg(long, requires = "chain_id", help_heading = "Source Options")]
+    pub skip_chain_id_check: bool,
+
+    /// Warn before collecting if the rpc node does not serve historical state
+    #[arg(long, help_heading = "Source Options")]
+    pub check_archive: bool,
+
     /// Network name [default: name of eth_getChainId]
     #[arg(long, help_heading = "Source Options")]
     pub network_name: Option<String>,
@@ -94,6 +127,14 @@ pub struct Args {
     #[arg(short('l'), long, value_name = "limit", help_heading = "Acquisition Options")]
     pub requests_per_second: Option<u32>,
 
+    /// Ratelimit on trace and debug requests per second, instead of --requests-per-second
+    #[arg(long, value_name = "limit", help_heading = "Acquisition Options")]
+    pub trace_requests_per_second: Option<u32>,
+
+    /// Ratelimit on log requests per second, instead of --requests-per-second
+    #[arg(long, value_name = "limit", help_heading = "Acquisition Options")]
+    pub log_requests_per_second: Option<u32>,
+
     /// Max retries for provider errors
     #[arg(long, default_value_t = 5, value_name = "R", help_heading = "Acquisition Options")]
     pub max_retries: u32,
@@ -102,6 +143,23 @@ pub struct Args {
     #[arg(long, default_value_t = 500, value_name = "B", help_heading = "Acquisition Options")]
     pub initial_backoff: u64,
 
+    /// Seconds before a read or log request is retried, 0 to wait indefinitely
+    /// [default: 30 for reads, 60 for logs]
+    #[arg(long, value_name = "S", help_heading = "Acquisition Options", verbatim_doc_comment)]
+    pub request_timeout: Option<u64>,
+
+    /// Seconds before a trace or debug request is retried, 0 to wait indefinitely [default: 300]
+    #[arg(long, value_name = "S", help_heading = "Acquisition Options")]
+    pub trace_request_timeout: Option<u64>,
+
+    /// Pause requests after this many consecutive provider failures
+    #[arg(long, value_name = "N", help_heading = "Acquisition Options")]
+    pub circuit_breaker_failures: Option<u32>,
+
+    /// Seconds requests stay paused before probing the provider again
+    #[arg(long, default_value_t = 30, value_name = "S", help_heading = "Acquisition Options")]
+    pub circuit_breaker_cooldown: u64,
+
     /// Global number of concurrent requests
     #[arg(long, value_name = "M", help_heading = "Acquisition Options")]
     pub max_concurrent_requests: Option<u64>,
@@ -167,6 +225,10 @@ pub struct Args {
     #[arg(long, help_heading = "Output Options")]
     pub json: bool,
 
+    /// Save as arrow ipc (feather) instead of parquet
+    #[arg(long, help_heading = "Output Options")]
+    pub arrow: bool,
+
     /// Number of rows per row group in parquet file
     #[arg(long, value_name = "GROUP_SIZE", help_heading = "Output Options")]
     pub row_group_size: Option<usize>,
@@ -179,6 +241,11 @@ pub struct Args {
     #[arg(long, help_heading = "Output Options")]
     pub no_stats: bool,
 
+    /// Write parquet row groups every N collected rows to bound memory,
+    /// files are sorted by a streaming sort once collected
+    #[arg(long, value_name = "N_ROWS", help_heading = "Output Options")]
+    pub flush_rows: Option<u64>,
+
     /// Compression algorithm and level
     #[arg(long, help_heading="Output Options", value_name="NAME [#]", num_args(1..=2), default_value = "lz4")]
     pub compression: Vec<String>,
@@ -192,6 +259,14 @@ pub struct Args {
     #[arg(long, help_heading = "Output Options")]
     pub no_report: bool,
 
+    /// Write a .sha256 checksum of each output file's data next to it
+    #[arg(long, help_heading = "Output Options")]
+    pub checksums: bool,
+
+    /// Rows re-fetched per file by `cryo verify`, 0 to only compare checksums
+    #[arg(long, default_value_t = 10, value_name = "N", help_heading = "Output Options")]
+    pub verify_samples: usize,
+
     /// Address(es)
     #[arg(long, help_heading = "Dataset-specific Options", num_args(1..))]
     pub address: Option<Vec<String>>,
@@ -216,6 +291,30 @@ pub struct Args {
     #[arg(long, help_heading = "Dataset-specific Options", num_args(1..))]
     pub inputs: Option<Vec<String>>,
 
+    /// Find transactions matching --from-address/--to-address with trace_filter instead of
+    /// scanning whole blocks
+    #[arg(long, help_heading = "Dataset-specific Options")]
+    pub trace_filter: bool,
+
+    /// Fetch block headers first and skip blocks whose logs bloom cannot contain the
+    /// --topic0/--contract logs, for rare events with a large --inner-request-size (costs a
+    /// request per header)
+    #[arg(long, help_heading = "Dataset-specific Options")]
+    pub bloom_filter: bool,
+
+    /// Keep only traces touching these address(es), for trace-based datasets
+    #[arg(long, help_heading = "Dataset-specific Options", value_name="address", num_args(1..))]
+    pub include_addresses: Option<Vec<String>>,
+
+    /// Drop traces touching these address(es), for trace-based datasets
+    #[arg(long, help_heading = "Dataset-specific Options", value_name="address", num_args(1..))]
+    pub exclude_addresses: Option<Vec<String>>,
+
+    /// Function signature for eth_calls, return types are used to decode outputs
+    /// ex: "balanceOf(address) returns (uint256)"
+    #[arg(long, value_name = "SIG", help_heading = "Dataset-specific Options")]
+    pub function_signature: Option<String>,
+
     /// Slot(s)
     #[arg(long, help_heading = "Dataset-specific Options", num_args(1..))]
     pub slot: Option<Vec<String>>,
@@ -224,7 +323,7 @@ pub struct Args {
     #[arg(long, help_heading = "Dataset-specific Options", num_args(1..))]
     pub contract: Option<Vec<String>>,
 
-    /// Topic0(s)
+    /// Topic0(s) as hashes or event signatures
     #[arg(long, visible_alias = "event", help_heading = "Dataset-specific Options", num_args(1..))]
     pub topic0: Option<Vec<String>>,
 
@@ -244,7 +343,7 @@ pub struct Args {
     #[arg(long, value_name = "SIG", help_heading = "Dataset-specific Options", num_args(1..))]
     pub event_signature: Option<String>,
 
-    /// Event signature for fetching log from etherscan
+    /// Event abi json file for log decoding, as PATH or PATH:EVENT
     #[arg(long, value_name = "ABI", help_heading = "Dataset-specific Options", num_args(1..))]
     pub event_abi: Option<String>,
 
@@ -315,6 +414,9 @@ fn get_after_str() -> String {
       <white><bold>cryo help</bold></white>                      display help message
       <white><bold>cryo help syntax</bold></white>               display block + tx specification syntax
       <white><bold>cryo help datasets</bold></white>             display list of all datasets
+      <white><bold>cryo help schemas [DATASET(S)]</bold></white> print columns of datasets as json
+      <white><bold>cryo verify <<FILE(S)></bold></white>          compare files to their checksums and sampled rows to
+                                     the chain
       <white><bold>cryo help</bold></white>"#
     );
     let post_subcommands = " <DATASET(S)>         display info about a dataset";
@@ -16,6 +16,7 @@ use eyre::Result;
 async fn main() -> Result<()> {
     let args = Args::parse();
     match run::run(args).await {
+        Ok(Some(freeze_summary)) if !freeze_summary.cancelled.is_empty() => std::process::exit(130),
         Ok(Some(freeze_summary)) if freeze_summary.errored.is_empty() => Ok(()),
         Ok(Some(_freeze_summary)) => std::process::exit(1),
         Ok(None) => Ok(()),
@@ -4,16 +4,24 @@ use std::collections::HashMap;
 
 use cryo_freeze::{BlockChunk, ChunkData, Datatype, Fetcher, ParseError, Subchunk, Table};
 
+use super::parse_utils::parse_binary_arg;
 use crate::args::Args;
 
 pub(crate) async fn parse_blocks<P: JsonRpcClient>(
     args: &Args,
     fetcher: Arc<Fetcher<P>>,
 ) -> Result<(Option<Vec<Option<String>>>, Option<Vec<BlockChunk>>), ParseError> {
-    let (files, explicit_numbers): (Vec<&String>, Vec<&String>) = match &args.blocks {
-        Some(blocks) => blocks.iter().partition(|tx| std::path::Path::new(tx).exists()),
-        None => return Ok((None, None)),
-    };
+    let (files, explicit_numbers): (Vec<&String>, Vec<&String>) =
+        match (&args.blocks, &args.block_hashes) {
+            (Some(_), Some(_)) => {
+                return Err(ParseError::ParseError(
+                    "cannot use both --blocks and --block-hashes".to_string(),
+                ))
+            }
+            (None, Some(block_hashes)) => return parse_block_hashes(block_hashes, &fetcher).await,
+            (Some(blocks), None) => blocks.iter().partition(|tx| std::path::Path::new(tx).exists()),
+            (None, None) => return Ok((None, None)),
+        };
 
     let (file_labels, file_chunks) = if !files.is_empty() {
         let mut file_labels = Vec::new();
@@ -68,6 +76,57 @@ pub(crate) async fn parse_blocks<P: JsonRpcClient>(
     Ok((labels, Some(block_chunks)))
 }
 
+/// resolve block hashes to block numbers, one chunk per input list
+///
+/// hashes of blocks that are no longer in the canonical chain are rejected, because blocks are
+/// collected by number
+async fn parse_block_hashes<P: JsonRpcClient>(
+    inputs: &[String],
+    fetcher: &Fetcher<P>,
+) -> Result<(Option<Vec<Option<String>>>, Option<Vec<BlockChunk>>), ParseError> {
+    let mut labels = Vec::new();
+    let mut block_chunks = Vec::new();
+    for (list, hashes) in parse_binary_arg(inputs, "block_hash")? {
+        let mut numbers = Vec::new();
+        for hash in hashes {
+            if hash.len() != 32 {
+                return Err(ParseError::ParseError("block hashes must be 32 bytes".to_string()))
+            }
+            let hash = H256::from_slice(&hash);
+            numbers.push(resolve_block_hash(hash, fetcher).await?);
+        }
+        labels.push(list.to_label());
+        block_chunks.push(BlockChunk::Numbers(numbers));
+    }
+    Ok((Some(labels), Some(block_chunks)))
+}
+
+async fn resolve_block_hash<P: JsonRpcClient>(
+    hash: H256,
+    fetcher: &Fetcher<P>,
+) -> Result<u64, ParseError> {
+    let not_found = || ParseError::ParseError(format!("block hash {:?} not found", hash));
+    let number = fetcher
+        .get_block_by_hash(hash)
+        .await
+        .map_err(|e| ParseError::ParseError(format!("could not get block {:?}: {}", hash, e)))?
+        .and_then(|block| block.number)
+        .ok_or_else(not_found)?
+        .as_u64();
+    let canonical = fetcher
+        .get_block(number)
+        .await
+        .map_err(|e| ParseError::ParseError(format!("could not get block {}: {}", number, e)))?
+        .and_then(|block| block.hash);
+    if canonical != Some(hash) {
+        return Err(ParseError::ParseError(format!(
+            "block hash {:?} is not in the canonical chain at block {}",
+            hash, number
+        )))
+    }
+    Ok(number)
+}
+
 fn read_integer_column(path: &str, column: &str) -> Result<Vec<u64>, ParseError> {
     let file = std::fs::File::open(path)
         .map_err(|_e| ParseError::ParseError("could not open file path".to_string()))?;
@@ -121,6 +180,7 @@ async fn postprocess_block_chunks<P: JsonRpcClient>(
     // split block range into chunks
     let block_chunks = match args.n_chunks {
         Some(n_chunks) => block_chunks.subchunk_by_count(&n_chunks),
+        None if args.align => block_chunks.subchunk_by_aligned_size(&args.chunk_size),
         None => block_chunks.subchunk_by_size(&args.chunk_size),
     };
 
@@ -311,7 +371,7 @@ where
     };
 
     let end_block =
-        if second_ref != "latest" && !second_ref.is_empty() && !first_ref.starts_with('-') {
+        if !is_block_tag(second_ref) && !second_ref.is_empty() && !first_ref.starts_with('-') {
             end_block - 1
         } else {
             end_block
@@ -328,16 +388,36 @@ async fn parse_block_number<P: JsonRpcClient>(
     fetcher: &Fetcher<P>,
 ) -> Result<u64, ParseError> {
     match (block_ref, range_position) {
-        ("latest", _) => fetcher.get_block_number().await.map(|n| n.as_u64()).map_err(|_e| {
-            ParseError::ParseError("Error retrieving latest block number".to_string())
-        }),
-        ("", RangePosition::First) => Ok(0),
-        ("", RangePosition::Last) => {
-            fetcher.get_block_number().await.map(|n| n.as_u64()).map_err(|_e| {
-                ParseError::ParseError("Error retrieving last block number".to_string())
+        ("latest", _) | ("", RangePosition::Last) => parse_latest_block(fetcher).await,
+        _ if block_ref.starts_with("latest-") => {
+            let offset = parse_block_count(&block_ref["latest-".len()..])?;
+            Ok(parse_latest_block(fetcher).await?.saturating_sub(offset))
+        }
+        (tag, _) if is_block_tag(tag) => {
+            let tag = tag
+                .parse::<BlockNumber>()
+                .map_err(|_e| ParseError::ParseError(format!("Error parsing block tag {}", tag)))?;
+            fetcher.resolve_block_tag(tag).await.map_err(|_e| {
+                ParseError::ParseError(format!("Error retrieving {} block number", tag))
             })
         }
+        ("", RangePosition::First) => Ok(0),
         ("", RangePosition::None) => Err(ParseError::ParseError("invalid input".to_string())),
+        _ => parse_block_count(block_ref),
+    }
+}
+
+/// chain head, resolved once per run so that every relative block ref uses the same head
+async fn parse_latest_block<P: JsonRpcClient>(fetcher: &Fetcher<P>) -> Result<u64, ParseError> {
+    fetcher
+        .head_block_number()
+        .await
+        .map_err(|_e| ParseError::ParseError("Error retrieving latest block number".to_string()))
+}
+
+/// parse a number of blocks that may use a { B M K } suffix
+fn parse_block_count(block_ref: &str) -> Result<u64, ParseError> {
+    match block_ref {
         _ if block_ref.ends_with('B') | block_ref.ends_with('b') => {
             let s = &block_ref[..block_ref.len() - 1];
             s.parse::<f64>()
@@ -363,35 +443,79 @@ async fn parse_block_number<P: JsonRpcClient>(
     }
 }
 
+/// whether block ref is a tag that resolves to the chain's current state
+fn is_block_tag(block_ref: &str) -> bool {
+    matches!(block_ref, "latest" | "finalized" | "safe" | "pending") ||
+        block_ref.starts_with("latest-")
+}
+
 async fn apply_reorg_buffer<P: JsonRpcClient>(
     block_chunks: Vec<BlockChunk>,
     reorg_filter: u64,
     fetcher: &Fetcher<P>,
 ) -> Result<Vec<BlockChunk>, ParseError> {
-    match reorg_filter {
-        0 => Ok(block_chunks),
-        reorg_filter => {
-            let latest_block = match fetcher.get_block_number().await {
-                Ok(result) => result.as_u64(),
-                Err(_e) => {
-                    return Err(ParseError::ParseError("reorg buffer parse error".to_string()))
-                }
-            };
-            let max_allowed = latest_block - reorg_filter;
-            Ok(block_chunks
-                .into_iter()
-                .filter_map(|x| match x.max_value() {
-                    Some(max_block) if max_block <= max_allowed => Some(x),
-                    _ => None,
-                })
-                .collect())
-        }
+    let (block_chunks, skipped) = fetcher
+        .reorg_safe_chunks(block_chunks, reorg_filter)
+        .await
+        .map_err(|_e| ParseError::ParseError("reorg buffer parse error".to_string()))?;
+    if !skipped.is_empty() {
+        let ranges: Vec<String> = skipped
+            .iter()
+            .filter_map(|chunk| match (chunk.min_value(), chunk.max_value()) {
+                (Some(min), Some(max)) if min == max => Some(min.to_string()),
+                (Some(min), Some(max)) => Some(format!("{}-{}", min, max)),
+                _ => None,
+            })
+            .collect();
+        eprintln!(
+            "warning: skipping blocks within {} blocks of chain head: {}",
+            reorg_filter,
+            ranges.join(", ")
+        );
     }
+    Ok(block_chunks)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cryo_freeze::RetryConfig;
+
+    #[tokio::test]
+    async fn resolves_block_hashes_to_numbers() {
+        let (provider, mock) = Provider::mocked();
+        let fetcher = Fetcher::new(provider, None, None, RetryConfig::default());
+        let hash = H256::repeat_byte(5);
+        let block = Block::<TxHash> {
+            hash: Some(hash),
+            number: Some(17_000_000.into()),
+            ..Default::default()
+        };
+        // mock responses are popped from the back
+        mock.push::<Block<TxHash>, _>(block.clone()).unwrap();
+        mock.push::<Block<TxHash>, _>(block).unwrap();
+        let (labels, chunks) =
+            parse_block_hashes(&[format!("{:?}", hash)], &fetcher).await.unwrap();
+        assert_eq!(labels, Some(vec![None]));
+        assert!(matches!(
+            chunks.as_deref(),
+            Some([BlockChunk::Numbers(numbers)]) if numbers == &[17_000_000]
+        ));
+
+        // a reorged block resolves to a number whose canonical block has another hash
+        let reorged = Block::<TxHash> {
+            hash: Some(H256::repeat_byte(6)),
+            number: Some(17_000_000.into()),
+            ..Default::default()
+        };
+        mock.push::<Block<TxHash>, _>(Block::<TxHash> { hash: Some(hash), ..reorged.clone() })
+            .unwrap();
+        mock.push::<Block<TxHash>, _>(reorged).unwrap();
+        let error = parse_block_hashes(&[format!("{:?}", H256::repeat_byte(6))], &fetcher)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("not in the canonical chain"));
+    }
 
     enum BlockTokenTest<'a> {
         WithoutMock((&'a str, BlockChunk)),   // Token | Expected
@@ -400,7 +524,7 @@ mod tests {
 
     async fn block_token_test_helper(tests: Vec<(BlockTokenTest<'_>, bool)>) {
         let (provider, mock) = Provider::mocked();
-        let fetcher = Fetcher { provider, semaphore: None, rate_limiter: None };
+        let fetcher = Fetcher::new(provider, None, None, RetryConfig::default());
         for (test, res) in tests {
             match test {
                 BlockTokenTest::WithMock((token, expected, latest)) => {
@@ -446,7 +570,7 @@ mod tests {
 
     async fn block_input_test_helper(tests: Vec<(BlockInputTest<'_>, bool)>) {
         let (provider, mock) = Provider::mocked();
-        let fetcher = Fetcher { provider, semaphore: None, rate_limiter: None };
+        let fetcher = Fetcher::new(provider, None, None, RetryConfig::default());
         for (test, res) in tests {
             match test {
                 BlockInputTest::WithMock((inputs, expected, latest)) => {
@@ -507,7 +631,7 @@ mod tests {
 
     async fn block_number_test_helper(tests: Vec<(BlockNumberTest<'_>, bool)>) {
         let (provider, mock) = Provider::mocked();
-        let fetcher = Fetcher { provider, semaphore: None, rate_limiter: None };
+        let fetcher = Fetcher::new(provider, None, None, RetryConfig::default());
         for (test, res) in tests {
             match test {
                 BlockNumberTest::WithMock((block_ref, range_position, expected, latest)) => {
@@ -550,6 +674,8 @@ mod tests {
             (BlockTokenTest::WithoutMock((r"10:+100", BlockChunk::Range(10, 109))), true), /* Relative positive */
             (BlockTokenTest::WithMock((r"1:latest", BlockChunk::Range(1, 12), 12)), true), /* Explicit latest */
             (BlockTokenTest::WithMock((r"1:", BlockChunk::Range(1, 12), 12)), true), /* Implicit latest */
+            (BlockTokenTest::WithMock((r"latest-2:latest", BlockChunk::Range(10, 12), 12)), true), /* Relative to latest */
+            (BlockTokenTest::WithMock((r"latest-1k:latest-1", BlockChunk::Range(0, 11), 12)), true), /* Clamped to 0 */
             // Number type
             (BlockTokenTest::WithoutMock((r"1", BlockChunk::Numbers(vec![1]))), true), /* Single block */
         ];
@@ -608,6 +734,7 @@ mod tests {
             (BlockNumberTest::WithMock((r"latest", RangePosition::None, 12, 12)), true), /* Lastest block */
             (BlockNumberTest::WithoutMock((r"", RangePosition::First, 0)), true), // First block
             (BlockNumberTest::WithMock((r"", RangePosition::Last, 12, 12)), true), // Last block
+            (BlockNumberTest::WithMock((r"latest-5", RangePosition::None, 7, 12)), true), // Offset
             (BlockNumberTest::WithoutMock((r"1B", RangePosition::None, 1000000000)), true), // B
             (BlockNumberTest::WithoutMock((r"1M", RangePosition::None, 1000000)), true), // M
             (BlockNumberTest::WithoutMock((r"1K", RangePosition::None, 1000)), true), // K
@@ -40,6 +40,8 @@ pub(crate) fn parse_file_output(args: &Args, source: &Source) -> Result<FileOutp
         suffix: label.clone(),
         parquet_compression,
         row_group_size,
+        flush_rows: args.flush_rows,
+        checksums: args.checksums,
     };
 
     Ok(output)
@@ -89,13 +91,12 @@ pub(crate) fn parse_network_name(args: &Args, chain_id: u64) -> String {
 }
 
 pub(crate) fn parse_output_format(args: &Args) -> Result<FileFormat, ParseError> {
-    match (args.csv, args.json) {
-        (true, true) => {
-            Err(ParseError::ParseError("choose one of parquet, csv, or json".to_string()))
-        }
-        (true, _) => Ok(FileFormat::Csv),
-        (_, true) => Ok(FileFormat::Json),
-        (false, false) => Ok(FileFormat::Parquet),
+    match (args.csv, args.json, args.arrow) {
+        (true, false, false) => Ok(FileFormat::Csv),
+        (false, true, false) => Ok(FileFormat::Json),
+        (false, false, true) => Ok(FileFormat::ArrowIpc),
+        (false, false, false) => Ok(FileFormat::Parquet),
+        _ => Err(ParseError::ParseError("choose one of parquet, csv, json, or arrow".to_string())),
     }
 }
 
@@ -10,3 +10,4 @@ mod source;
 
 pub use args::*;
 use schemas::*;
+pub(crate) use source::parse_source;
@@ -21,6 +21,7 @@ pub(crate) fn hex_strings_to_binary(hex_strings: &[String]) -> Result<Vec<Vec<u8
 pub(crate) enum BinaryInputList {
     Explicit,
     ParquetColumn(String, String),
+    TextFile(String),
 }
 
 use std::path::Path;
@@ -30,11 +31,13 @@ impl BinaryInputList {
     pub(crate) fn to_label(&self) -> Option<String> {
         match self {
             BinaryInputList::Explicit => None,
-            BinaryInputList::ParquetColumn(path, _) => Path::new(&path)
-                .file_stem()
-                .and_then(|stem| stem.to_str())
-                .map(|stem_str| stem_str.split("__").last().unwrap_or(stem_str))
-                .map(|s| s.to_string()),
+            BinaryInputList::ParquetColumn(path, _) | BinaryInputList::TextFile(path) => {
+                Path::new(&path)
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .map(|stem_str| stem_str.split("__").last().unwrap_or(stem_str))
+                    .map(|s| s.to_string())
+            }
         }
     }
 }
@@ -42,8 +45,8 @@ impl BinaryInputList {
 type ParsedBinaryArg = HashMap<BinaryInputList, Vec<Vec<u8>>>;
 
 /// parse binary argument list
-/// each argument can be a hex string or a parquet column reference
-/// each parquet column is loaded into its own list, hex strings loaded into another
+/// each argument can be a hex string, a parquet column reference, or a text file of hex strings
+/// each file is loaded into its own list, hex strings loaded into another
 pub(crate) fn parse_binary_arg(
     inputs: &[String],
     default_column: &str,
@@ -51,16 +54,24 @@ pub(crate) fn parse_binary_arg(
     let mut parsed = HashMap::new();
 
     // separate into files vs explicit
-    let (files, hex_strings): (Vec<&String>, Vec<&String>) =
-        inputs.iter().partition(|tx| std::path::Path::new(tx).exists());
+    let (files, hex_strings): (Vec<&String>, Vec<&String>) = inputs.iter().partition(|input| {
+        parse_file_column_reference(input, default_column)
+            .map(|reference| Path::new(&reference.path).exists())
+            .unwrap_or(false)
+    });
 
     // files columns
     for path in files {
         let reference = parse_file_column_reference(path, default_column)?;
-        let values = cryo_freeze::read_binary_column(&reference.path, &reference.column)
-            .map_err(|_e| ParseError::ParseError("could not read input".to_string()))?;
-        let key = BinaryInputList::ParquetColumn(reference.path, reference.column);
-        parsed.insert(key, values);
+        if reference.path.ends_with(".parquet") {
+            let values = cryo_freeze::read_binary_column(&reference.path, &reference.column)
+                .map_err(|_e| ParseError::ParseError("could not read input".to_string()))?;
+            let key = BinaryInputList::ParquetColumn(reference.path, reference.column);
+            parsed.insert(key, values);
+        } else {
+            let values = read_hex_text_file(&reference.path)?;
+            parsed.insert(BinaryInputList::TextFile(reference.path), values);
+        }
     }
 
     // explicit binary strings
@@ -73,6 +84,20 @@ pub(crate) fn parse_binary_arg(
     Ok(parsed)
 }
 
+/// read hex strings separated by whitespace or commas, skipping lines starting with #
+fn read_hex_text_file(path: &str) -> Result<Vec<Vec<u8>>, ParseError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|_| ParseError::ParseError(format!("could not read input file {}", path)))?;
+    let hex_strings: Vec<String> = contents
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_string())
+        .collect();
+    hex_strings_to_binary(&hex_strings)
+}
+
 struct FileColumnReference {
     path: String,
     column: String,
@@ -97,3 +122,25 @@ fn parse_file_column_reference(
 
     Ok(parsed)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_text_file_of_hashes() {
+        let path = std::env::temp_dir().join("cryo_tx_hashes.txt");
+        let tx1 = format!("0x{}", "11".repeat(32));
+        let tx2 = "22".repeat(32);
+        std::fs::write(&path, format!("# transactions\n{}\n\n{},\n", tx1, tx2)).unwrap();
+        let input = path.to_string_lossy().to_string();
+        let explicit = "33".repeat(32);
+
+        let parsed = parse_binary_arg(&[input.clone(), explicit], "transaction_hash").unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let key = BinaryInputList::TextFile(input);
+        assert_eq!(parsed[&key], vec![vec![0x11; 32], vec![0x22; 32]]);
+        assert_eq!(parsed[&BinaryInputList::Explicit], vec![vec![0x33; 32]]);
+        assert_eq!(key.to_label(), Some("cryo_tx_hashes".to_string()));
+    }
+}
@@ -4,8 +4,9 @@ use super::{
 };
 use crate::args::Args;
 use cryo_freeze::{
-    AddressChunk, CallDataChunk, Datatype, Dim, Fetcher, ParseError, Partition, PartitionLabels,
-    SlotChunk, Table, TimeDimension, TopicChunk, TransactionChunk,
+    event_signature_topic0, parse_function_signature, AddressChunk, CallDataChunk, Datatype, Dim,
+    Fetcher, ParseError, Partition, PartitionLabels, SlotChunk, Table, TimeDimension, TopicChunk,
+    TransactionChunk,
 };
 use ethers::prelude::*;
 use rand::{seq::SliceRandom, thread_rng};
@@ -25,7 +26,8 @@ pub(crate) async fn parse_partitions<P: JsonRpcClient>(
     let (block_number_labels, block_numbers) = blocks::parse_blocks(args, fetcher.clone()).await?;
     let (transaction_hash_labels, transactions) =
         parse_transaction_chunks(&args.txs, "transaction_hash")?;
-    let call_datas = parse_call_datas(&args.call_data, &args.function, &args.inputs)?;
+    let function = parse_function_selectors(&args.function, &args.function_signature)?;
+    let call_datas = parse_call_datas(&args.call_data, &function, &args.inputs)?;
     let call_data_labels = None;
     let (address_labels, addresses) = parse_address_chunks(&args.address, "address")?;
     let (contract_labels, contracts) = parse_address_chunks(&args.contract, "contract_address")?;
@@ -33,7 +35,12 @@ pub(crate) async fn parse_partitions<P: JsonRpcClient>(
         parse_address_chunks(&args.from_address, "from_address")?;
     let (to_address_labels, to_addresses) = parse_address_chunks(&args.to_address, "to_address")?;
     let (slot_labels, slots) = parse_slot_chunks(&args.slot, "slot")?;
-    let (topic0_labels, topic0s) = parse_topic(&args.topic0, "topic0")?;
+    let event_topic0 = schemas
+        .values()
+        .find_map(|table| table.log_decoder.as_ref())
+        .map(|decoder| decoder.event.signature());
+    let topic0 = parse_topic0_signatures(&args.topic0, event_topic0)?;
+    let (topic0_labels, topic0s) = parse_topic(&topic0, "topic0")?;
     let (topic1_labels, topic1s) = parse_topic(&args.topic1, "topic1")?;
     let (topic2_labels, topic2s) = parse_topic(&args.topic2, "topic2")?;
     let (topic3_labels, topic3s) = parse_topic(&args.topic3, "topic3")?;
@@ -128,6 +135,24 @@ fn parse_time_dimension(partition: &Partition) -> TimeDimension {
     }
 }
 
+/// function selectors given directly or derived from a function signature
+fn parse_function_selectors(
+    function: &Option<Vec<String>>,
+    function_signature: &Option<String>,
+) -> Result<Option<Vec<String>>, ParseError> {
+    match (function, function_signature) {
+        (Some(_), Some(_)) => {
+            let message = "cannot specify both function and function_signature";
+            Err(ParseError::ParseError(message.to_string()))
+        }
+        (None, Some(signature)) => {
+            let selector = parse_function_signature(signature)?.short_signature();
+            Ok(Some(vec![format!("0x{}", hex::encode(selector))]))
+        }
+        (function, None) => Ok(function.clone()),
+    }
+}
+
 fn parse_call_datas(
     call_datas: &Option<Vec<String>>,
     function: &Option<Vec<String>>,
@@ -210,6 +235,27 @@ pub(crate) fn parse_slot_chunks(
     }
 }
 
+/// replace human readable event signatures with their topic0
+/// if no topic0 is given, topic0 is taken from the event used for decoding
+fn parse_topic0_signatures(
+    topic0: &Option<Vec<String>>,
+    event_topic0: Option<H256>,
+) -> Result<Option<Vec<String>>, ParseError> {
+    let topic0 = match (topic0, event_topic0) {
+        (Some(topic0), _) => topic0.clone(),
+        (None, Some(event_topic0)) => vec![format!("{:?}", event_topic0)],
+        (None, None) => return Ok(None),
+    };
+    topic0
+        .into_iter()
+        .map(|topic| match topic.contains('(') {
+            true => event_signature_topic0(&topic).map(|topic| format!("{:?}", topic)),
+            false => Ok(topic),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
 fn parse_topic(
     input: &Option<Vec<String>>,
     default_column: &str,
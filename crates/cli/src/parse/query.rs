@@ -1,8 +1,11 @@
-use super::{parse_schemas, partitions};
+use super::{parse_schemas, parse_utils, partitions};
 use crate::args::Args;
-use cryo_freeze::{Dim, Fetcher, ParseError, Query, QueryLabels, Schemas};
+use cryo_freeze::{
+    parse_function_signature, ChunkData, Datatype, Dim, Fetcher, ParseError, Partition, Query,
+    QueryLabels, Schemas, TraceAddressFilter, TraceStatusFilter,
+};
 use ethers::prelude::*;
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 pub(crate) async fn parse_query<P: JsonRpcClient>(
     args: &Args,
@@ -17,8 +20,24 @@ pub(crate) async fn parse_query<P: JsonRpcClient>(
 
     let (partitions, partitioned_by, time_dimension) =
         partitions::parse_partitions(args, fetcher, &schemas).await?;
+    let partitions = if schemas.contains_key(&Datatype::PendingTransactions) {
+        label_snapshot(partitions, &partitioned_by, &schemas)?
+    } else {
+        partitions
+    };
     let datatypes = cryo_freeze::cluster_datatypes(datatypes);
     let labels = QueryLabels { align: args.align, reorg_buffer: args.reorg_buffer };
+    let function = args.function_signature.as_deref().map(parse_function_signature).transpose()?;
+    let trace_addresses = TraceAddressFilter {
+        include: args.include_addresses.as_deref().map(parse_address_set).transpose()?,
+        exclude: args
+            .exclude_addresses
+            .as_deref()
+            .map(parse_address_set)
+            .transpose()?
+            .unwrap_or_default(),
+    };
+    let trace_status = parse_trace_status(args)?;
     Ok(Query {
         datatypes,
         schemas,
@@ -27,10 +46,70 @@ pub(crate) async fn parse_query<P: JsonRpcClient>(
         partitioned_by,
         exclude_failed: args.exclude_failed,
         js_tracer: args.js_tracer.clone(),
+        use_trace_filter: args.trace_filter,
+        use_bloom_filter: args.bloom_filter,
+        function,
+        trace_addresses,
+        trace_status,
         labels,
     })
 }
 
+fn parse_trace_status(args: &Args) -> Result<TraceStatusFilter, ParseError> {
+    match args.trace_status.as_ref().map(|raw| raw.to_lowercase()).as_deref() {
+        None | Some("all") => Ok(TraceStatusFilter::All),
+        Some("success") => Ok(TraceStatusFilter::Success),
+        Some("reverted") => Ok(TraceStatusFilter::Reverted),
+        Some(_) => Err(ParseError::ParseError(
+            "trace status must be one of all, success, or reverted".to_string(),
+        )),
+    }
+}
+
+/// pending transactions are not keyed by block, so their output is labeled by collection time
+fn label_snapshot(
+    partitions: Vec<Partition>,
+    partitioned_by: &[Dim],
+    schemas: &Schemas,
+) -> Result<Vec<Partition>, ParseError> {
+    if schemas.len() > 1 {
+        return Err(ParseError::ParseError(
+            "pending_transactions cannot be collected with other datasets".to_string(),
+        ))
+    }
+    let partition = match partitions.as_slice() {
+        [partition] if partition.block_numbers.as_ref().map_or(0, |chunks| chunks.size()) <= 1 => {
+            partition.clone()
+        }
+        _ => {
+            return Err(ParseError::ParseError(
+                "pending_transactions collects a single snapshot, do not pass --blocks ranges"
+                    .to_string(),
+            ))
+        }
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|_| ParseError::ParseError("system clock is before unix epoch".to_string()))?;
+    let mut label = vec![None; partitioned_by.len()];
+    if let Some(first) = label.first_mut() {
+        *first = Some(format!("snapshot_{}", now.as_secs()));
+    }
+    Ok(vec![Partition { label: Some(label), ..partition }])
+}
+
+/// parse addresses given as hex strings or parquet column references
+fn parse_address_set(inputs: &[String]) -> Result<HashSet<H160>, ParseError> {
+    parse_utils::parse_binary_arg(inputs, "address")?
+        .into_values()
+        .flatten()
+        .map(|address| match address.len() {
+            20 => Ok(H160::from_slice(&address)),
+            _ => Err(ParseError::ParseError("addresses must be 20 bytes".to_string())),
+        })
+        .collect()
+}
+
 fn find_arg_aliases(args: &Args, schemas: &Schemas) -> Vec<(Dim, Dim)> {
     // does not currently handle optional args, just required args
     let mut swaps = Vec::new();
@@ -60,7 +139,7 @@ trait DimIsNone {
 impl DimIsNone for Args {
     fn dim_is_some(&self, dim: &Dim) -> bool {
         match dim {
-            Dim::BlockNumber => self.blocks.is_some(),
+            Dim::BlockNumber => self.blocks.is_some() || self.block_hashes.is_some(),
             Dim::TransactionHash => self.txs.is_some(),
             Dim::Address => self.address.is_some(),
             Dim::FromAddress => self.from_address.is_some(),
@@ -77,7 +156,7 @@ impl DimIsNone for Args {
 
     fn dim_is_none(&self, dim: &Dim) -> bool {
         match dim {
-            Dim::BlockNumber => self.blocks.is_none(),
+            Dim::BlockNumber => self.blocks.is_none() && self.block_hashes.is_none(),
             Dim::TransactionHash => self.txs.is_none(),
             Dim::Address => self.address.is_none(),
             Dim::FromAddress => self.from_address.is_none(),
@@ -120,3 +199,34 @@ fn apply_arg_aliases(args: &Args, arg_aliases: Vec<(Dim, Dim)>) -> Result<Args,
     }
     Ok(args)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cryo_freeze::{BlockChunk, ColumnEncoding};
+    use std::collections::HashMap;
+
+    fn pending_schemas() -> Schemas {
+        let schema = Datatype::PendingTransactions
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &None, None, None)
+            .unwrap();
+        HashMap::from([(Datatype::PendingTransactions, schema)])
+    }
+
+    fn block_partition(chunk: BlockChunk) -> Partition {
+        Partition { block_numbers: Some(vec![chunk]), ..Default::default() }
+    }
+
+    #[test]
+    fn label_snapshot_rejects_block_ranges() {
+        let schemas = pending_schemas();
+        let partitioned_by = [Dim::BlockNumber];
+        let latest = block_partition(BlockChunk::Numbers(vec![100]));
+        let labeled = label_snapshot(vec![latest], &partitioned_by, &schemas).unwrap();
+        let label = labeled[0].label.clone().unwrap();
+        assert!(label[0].as_ref().unwrap().starts_with("snapshot_"));
+
+        let range = block_partition(BlockChunk::Range(1, 100));
+        assert!(label_snapshot(vec![range], &partitioned_by, &schemas).is_err());
+    }
+}
@@ -9,7 +9,20 @@ use crate::args::Args;
 use cryo_freeze::U256Type;
 use std::str::FromStr;
 
-fn parse_datatypes(raw_inputs: &Vec<String>) -> Result<Vec<Datatype>, ParseError> {
+/// parse an event abi given as PATH or PATH:EVENT, where PATH is a json abi file
+fn parse_event_abi(event_abi: &str) -> Result<LogDecoder, ParseError> {
+    let (path, event_name) = match event_abi.rsplit_once(':') {
+        Some((path, event_name)) if !std::path::Path::new(event_abi).exists() => {
+            (path, Some(event_name))
+        }
+        _ => (event_abi, None),
+    };
+    let json = std::fs::read_to_string(path)
+        .map_err(|_| ParseError::ParseError(format!("could not read event abi file {}", path)))?;
+    LogDecoder::from_abi_json(&json, event_name).map_err(ParseError::ParseError)
+}
+
+pub(crate) fn parse_datatypes(raw_inputs: &Vec<String>) -> Result<Vec<Datatype>, ParseError> {
     let mut datatypes = Vec::new();
 
     'outer: for raw_input in raw_inputs {
@@ -34,17 +47,16 @@ pub(crate) fn parse_schemas(
     let sort = parse_sort_columns(&args.sort, &datatypes)?;
     let u256_types = parse_u256_types(args)?;
     let output_format = file_output::parse_output_format(args)?;
-    let binary_column_format = match args.hex | (output_format != FileFormat::Parquet) {
-        true => ColumnEncoding::Hex,
-        false => ColumnEncoding::Binary,
-    };
+    let binary_encoded = matches!(output_format, FileFormat::Parquet | FileFormat::ArrowIpc);
+    let binary_column_format = parse_binary_column_format(args, binary_encoded)?;
 
-    let log_decoder = match args.event_signature {
-        Some(ref sig) => match LogDecoder::new(sig.clone()) {
+    let log_decoder = match (&args.event_signature, &args.event_abi) {
+        (Some(sig), _) => match LogDecoder::new(sig.clone()) {
             Ok(res) => Some(res),
             Err(_) => return Err(ParseError::ParseError("invalid event signature".to_string())),
         },
-        None => None,
+        (None, Some(event_abi)) => Some(parse_event_abi(event_abi)?),
+        (None, None) => None,
     };
 
     // create schemas
@@ -64,7 +76,7 @@ pub(crate) fn parse_schemas(
                 .map(|schema| (*datatype, schema))
                 .map_err(|e| {
                     ParseError::ParseError(format!(
-                        "Failed to get schema for datatype: {:?}, {:?}",
+                        "Failed to get schema for datatype: {:?}, {}",
                         datatype, e
                     ))
                 })
@@ -81,9 +93,59 @@ pub(crate) fn parse_schemas(
         ensure_excluded_columns(exclude_columns, schemas)?
     };
 
+    // make sure custom sort columns are collected
+    if let (Ok(schemas), Some(sort)) = (&schemas, &args.sort) {
+        ensure_sort_columns(sort, schemas)?
+    };
+
     Ok((datatypes, schemas?))
 }
 
+fn ensure_sort_columns(
+    sort: &[String],
+    schemas: &HashMap<Datatype, Table>,
+) -> Result<(), ParseError> {
+    for schema in schemas.values() {
+        let missing: Vec<&String> = sort
+            .iter()
+            .filter(|column| column.as_str() != "none" && !schema.has_column(column))
+            .collect();
+        if !missing.is_empty() {
+            return Err(ParseError::ParseError(format!(
+                "sort columns are not collected for {}: {:?}",
+                schema.datatype.name(),
+                missing
+            )))
+        }
+    }
+    Ok(())
+}
+
+fn parse_binary_column_format(
+    args: &Args,
+    binary_encoded: bool,
+) -> Result<ColumnEncoding, ParseError> {
+    let format = match args.binary_column_format.as_ref().map(|raw| raw.to_lowercase()) {
+        None if args.hex || !binary_encoded => ColumnEncoding::Hex,
+        None => ColumnEncoding::Binary,
+        Some(raw) => match raw.as_str() {
+            "binary" => ColumnEncoding::Binary,
+            "hex" => ColumnEncoding::UnprefixedHex,
+            "prefix_hex" => ColumnEncoding::Hex,
+            _ => return Err(ParseError::ParseError("bad binary column format".to_string())),
+        },
+    };
+    if args.hex && !format.is_hex() {
+        let message = "--hex conflicts with --binary-column-format binary";
+        return Err(ParseError::ParseError(message.to_string()))
+    }
+    if format == ColumnEncoding::Binary && !binary_encoded {
+        let message = "binary columns can only be written to parquet or arrow";
+        return Err(ParseError::ParseError(message.to_string()))
+    }
+    Ok(format)
+}
+
 fn parse_u256_types(args: &Args) -> Result<Vec<U256Type>, ParseError> {
     if let Some(raw_u256_types) = args.u256_types.clone() {
         let mut u256_types: Vec<U256Type> = Vec::new();
@@ -107,6 +169,10 @@ fn parse_u256_types(args: &Args) -> Result<Vec<U256Type>, ParseError> {
             };
             u256_types.push(u256_type);
         }
+        if u256_types.contains(&U256Type::Decimal128) && (args.csv || args.json) {
+            let message = "decimal128 u256 columns can only be written to parquet or arrow";
+            return Err(ParseError::ParseError(message.to_string()))
+        }
         Ok(u256_types)
     } else {
         Ok(vec![U256Type::Binary, U256Type::String, U256Type::F64])
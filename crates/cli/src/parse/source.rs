@@ -1,32 +1,65 @@
 use std::env;
 
 use ethers::prelude::*;
-use governor::{Quota, RateLimiter};
 use polars::prelude::*;
-use std::num::NonZeroU32;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::time::Duration;
 
-use cryo_freeze::{Fetcher, ParseError, Source, SourceLabels};
+use cryo_freeze::{
+    transport::is_ipc_path, FallbackClient, Fetcher, MethodCategory, ParseError, RetryConfig,
+    Source, SourceLabels, Transport,
+};
 
 use crate::args::Args;
 
 pub(crate) async fn parse_source(args: &Args) -> Result<Source, ParseError> {
+    if args.skip_chain_id_check && args.chain_id.is_none() {
+        return Err(ParseError::ParseError("--skip-chain-id-check requires --chain-id".to_string()))
+    }
+
     // parse network info
     let rpc_url = parse_rpc_url(args);
-    let provider =
-        Provider::<RetryClient<Http>>::new_client(&rpc_url, args.max_retries, args.initial_backoff)
-            .map_err(|_e| ParseError::ParseError("could not connect to provider".to_string()))?;
-    let chain_id = provider.get_chainid().await.map_err(ParseError::ProviderError)?.as_u64();
-
-    let rate_limiter = match args.requests_per_second {
-        Some(rate_limit) => match (NonZeroU32::new(1), NonZeroU32::new(rate_limit)) {
-            (Some(one), Some(value)) => {
-                let quota = Quota::per_second(value).allow_burst(one);
-                Some(RateLimiter::direct(quota))
+    let mut rpc_urls = vec![rpc_url.clone()];
+    if let Some(fallback_rpcs) = &args.fallback_rpc {
+        rpc_urls.extend(fallback_rpcs.iter().map(|url| normalize_rpc_url(url.clone())));
+    }
+
+    let headers = parse_rpc_headers(args)?;
+
+    // connect to each endpoint, checking that all endpoints serve the same chain
+    let mut clients = Vec::new();
+    let mut chain_id = if args.skip_chain_id_check { args.chain_id } else { None };
+    for url in rpc_urls.into_iter() {
+        let client = Transport::connect_with_headers(&url, headers.clone())
+            .await
+            .map_err(|e| ParseError::ParseError(format!("could not connect to {}: {}", url, e)))?;
+        if !args.skip_chain_id_check {
+            let url_chain_id = JsonRpcClient::request::<_, U256>(&client, "eth_chainId", ())
+                .await
+                .map_err(ParseError::ProviderError)?
+                .as_u64();
+            match (chain_id, args.chain_id) {
+                (_, Some(expected)) if expected != url_chain_id => {
+                    return Err(ParseError::ParseError(format!(
+                        "rpc {} has chain_id {} but --chain-id is {}",
+                        url, url_chain_id, expected
+                    )))
+                }
+                (Some(chain_id), _) if chain_id != url_chain_id => {
+                    return Err(ParseError::ParseError(format!(
+                        "rpc {} has chain_id {} but primary rpc has chain_id {}",
+                        url, url_chain_id, chain_id
+                    )))
+                }
+                _ => chain_id = Some(url_chain_id),
             }
-            _ => None,
-        },
-        None => None,
-    };
+        }
+        clients.push((url, client));
+    }
+    let chain_id = chain_id.ok_or(ParseError::ParseError("no rpc url given".to_string()))?;
+    let client = FallbackClient::new(clients)
+        .map_err(|_| ParseError::ParseError("could not build rpc client".to_string()))?;
+    let provider = Provider::new(client);
 
     // process concurrency info
     let max_concurrent_requests = args.max_concurrent_requests.unwrap_or(100);
@@ -36,10 +69,44 @@ pub(crate) async fn parse_source(args: &Args) -> Result<Source, ParseError> {
         None => Some(4),
     };
 
-    let semaphore = tokio::sync::Semaphore::new(max_concurrent_requests as usize);
-    let semaphore = Some(semaphore);
+    let retry = RetryConfig {
+        max_retries: args.max_retries,
+        base_delay: Duration::from_millis(args.initial_backoff),
+        ..Default::default()
+    };
 
-    let fetcher = Fetcher { provider, semaphore, rate_limiter };
+    let mut fetcher =
+        Fetcher::new(provider, Some(max_concurrent_requests), args.requests_per_second, retry);
+    if let Some(limit) = args.trace_requests_per_second {
+        fetcher = fetcher.with_method_rate_limit(MethodCategory::Trace, limit);
+    }
+    if let Some(limit) = args.log_requests_per_second {
+        fetcher = fetcher.with_method_rate_limit(MethodCategory::Log, limit);
+    }
+    if let Some(timeout) = args.request_timeout {
+        let timeout = Duration::from_secs(timeout);
+        fetcher = fetcher
+            .with_request_timeout(MethodCategory::Read, timeout)
+            .with_request_timeout(MethodCategory::Log, timeout);
+    }
+    if let Some(timeout) = args.trace_request_timeout {
+        fetcher = fetcher.with_request_timeout(MethodCategory::Trace, Duration::from_secs(timeout));
+    }
+    if let Some(failures) = args.circuit_breaker_failures {
+        let cooldown = Duration::from_secs(args.circuit_breaker_cooldown);
+        fetcher = fetcher.with_circuit_breaker(failures, cooldown);
+    }
+    if args.check_archive {
+        let is_archive_node = fetcher.is_archive_node().await.map_err(|e| {
+            ParseError::ParseError(format!("could not check for historical state: {}", e))
+        })?;
+        if !is_archive_node {
+            eprintln!(
+                "warning: rpc node is not an archive node, state at old blocks is unavailable"
+            );
+            eprintln!();
+        }
+    }
     let output = Source {
         fetcher: Arc::new(fetcher),
         chain_id,
@@ -57,8 +124,24 @@ pub(crate) async fn parse_source(args: &Args) -> Result<Source, ParseError> {
     Ok(output)
 }
 
+fn parse_rpc_headers(args: &Args) -> Result<HeaderMap, ParseError> {
+    let mut headers = HeaderMap::new();
+    for header in args.rpc_header.iter().flatten() {
+        let (name, value) = header.split_once(':').ok_or_else(|| {
+            ParseError::ParseError(format!("rpc header must be NAME: VALUE, got {}", header))
+        })?;
+        let name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|_| ParseError::ParseError(format!("invalid rpc header name {}", name)))?;
+        let value = HeaderValue::from_str(value.trim()).map_err(|_| {
+            ParseError::ParseError(format!("invalid rpc header value for {}", name))
+        })?;
+        headers.append(name, value);
+    }
+    Ok(headers)
+}
+
 fn parse_rpc_url(args: &Args) -> String {
-    let mut url = match &args.rpc {
+    let url = match &args.rpc {
         Some(url) => url.clone(),
         _ => match env::var("ETH_RPC_URL") {
             Ok(url) => url,
@@ -68,8 +151,27 @@ fn parse_rpc_url(args: &Args) -> String {
             }
         },
     };
-    if !url.starts_with("http") {
-        url = "http://".to_string() + url.as_str();
-    };
-    url
+    normalize_rpc_url(url)
+}
+
+fn normalize_rpc_url(url: String) -> String {
+    if !url.starts_with("http") && !url.starts_with("ws") && !is_ipc_path(&url) {
+        "http://".to_string() + url.as_str()
+    } else {
+        url
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn skip_chain_id_check_requires_chain_id() {
+        let args = Args { skip_chain_id_check: true, ..Default::default() };
+        match parse_source(&args).await {
+            Err(ParseError::ParseError(message)) => assert!(message.contains("--chain-id")),
+            _ => panic!("expected a parse error"),
+        }
+    }
 }
@@ -2,8 +2,8 @@ use crate::{args, parse, remember};
 use clap_cryo::Parser;
 use color_print::cstr;
 use colored::Colorize;
-use cryo_freeze::{err, CollectError, ExecutionEnv, FreezeSummary};
-use std::{sync::Arc, time::SystemTime};
+use cryo_freeze::{err, CollectError, Datatype, ExecutionEnv, FreezeSummary};
+use std::{str::FromStr, sync::Arc, time::SystemTime};
 
 /// run cli
 pub async fn run(args: args::Args) -> Result<Option<FreezeSummary>, CollectError> {
@@ -11,6 +11,9 @@ pub async fn run(args: args::Args) -> Result<Option<FreezeSummary>, CollectError
     if args.datatype.first() == Some(&"help".to_string()) {
         return handle_help_subcommands(args).await
     }
+    if args.datatype.first() == Some(&"verify".to_string()) {
+        return handle_verify_subcommand(args).await
+    }
 
     let cryo_dir: std::path::PathBuf = args.output_dir.clone().into();
     let cryo_dir = cryo_dir.join(".cryo");
@@ -51,9 +54,92 @@ pub async fn run(args: args::Args) -> Result<Option<FreezeSummary>, CollectError
     let source = Arc::new(source);
     let env = ExecutionEnv { t_start_parse, ..env };
     let env = env.set_start_time();
+    if !env.dry {
+        handle_interrupts(env.clone());
+    }
     cryo_freeze::freeze(&query, &source, &sink, &env).await
 }
 
+/// on the first ctrl-c, let in-flight chunks finish without starting new ones
+/// on the second ctrl-c, exit immediately
+fn handle_interrupts(env: ExecutionEnv) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return
+        }
+        eprintln!();
+        eprintln!("stopping after in-flight chunks finish, press ctrl-c again to exit now");
+        env.cancel();
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(130);
+        }
+    });
+}
+
+/// compare output files to their recorded checksums, then re-fetch sampled rows and report
+/// values that differ from the chain
+async fn handle_verify_subcommand(args: args::Args) -> Result<Option<FreezeSummary>, CollectError> {
+    let paths = &args.datatype[1..];
+    if paths.is_empty() {
+        return Err(err("specify output file(s) to verify"))
+    }
+    let mut n_mismatches = 0;
+    for path in paths.iter() {
+        let path = std::path::Path::new(path);
+        match cryo_freeze::verify_checksum(path)? {
+            Some(true) => println!("{}: checksum ok", path.display()),
+            Some(false) => {
+                println!("{}: checksum mismatch", path.display());
+                n_mismatches += 1;
+            }
+            None if args.verify_samples == 0 => {
+                return Err(err(&format!("{} has no checksum file", path.display())))
+            }
+            None => {}
+        }
+    }
+    if args.verify_samples == 0 {
+        if n_mismatches > 0 {
+            return Err(err(&format!("found {} mismatches", n_mismatches)))
+        }
+        return Ok(None)
+    }
+
+    let source = parse::parse_source(&args).await?;
+    for path in paths.iter() {
+        let path = std::path::Path::new(path);
+        // output files are named {network}__{datatype}__{chunk}.parquet
+        let datatype = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.split("__").nth(1))
+            .ok_or_else(|| err("could not determine datatype from file name"))?;
+        let datatype = Datatype::from_str(datatype)?;
+        let summary =
+            cryo_freeze::verify_file(path, datatype, &source.fetcher, args.verify_samples).await?;
+        println!(
+            "{}: checked {} rows, {} mismatches",
+            path.display(),
+            summary.n_checked,
+            summary.mismatches.len()
+        );
+        for mismatch in summary.mismatches.iter() {
+            println!(
+                "- {} {}: file = {}, chain = {}",
+                mismatch.key,
+                mismatch.column,
+                mismatch.file_value.as_deref().unwrap_or("null"),
+                mismatch.chain_value.as_deref().unwrap_or("null"),
+            );
+        }
+        n_mismatches += summary.mismatches.len();
+    }
+    if n_mismatches > 0 {
+        return Err(err(&format!("found {} mismatches", n_mismatches)))
+    }
+    Ok(None)
+}
+
 async fn handle_help_subcommands(args: args::Args) -> Result<Option<FreezeSummary>, CollectError> {
     if args.datatype.len() == 1 {
         args::Args::parse_from(vec!["cryo", "-h"]);
@@ -66,6 +152,8 @@ async fn handle_help_subcommands(args: args::Args) -> Result<Option<FreezeSummar
 - can use multiple parquet files     <white><bold>--blocks ./path/to/files/*.parquet[:COLUMN_NAME]</bold></white>
 - numbers can contain { _ . K M B }  <white><bold>5_000 5K 15M 15.5M</bold></white>
 - omitting range end means latest    <white><bold>15.5M:</bold></white> == <white><bold>15.5M:latest</bold></white>
+- can use block tags                 <white><bold>15.5M:finalized 15.5M:safe</bold></white>
+- can count back from latest         <white><bold>latest-1000:latest</bold></white>
 - omitting range start means 0       <white><bold>:700</bold></white> == <white><bold>0:700</bold></white>
 - minus on start means minus end     <white><bold>-1000:7000</bold></white> == <white><bold>6000:7000</bold></white>
 - plus sign on end means plus start  <white><bold>15M:+1000</bold></white> == <white><bold>15M:15.001K</bold></white>
@@ -76,11 +164,23 @@ async fn handle_help_subcommands(args: args::Args) -> Result<Option<FreezeSummar
 - can use transaction hashes         <white><bold>--txs TX_HASH1 TX_HASH2 TX_HASH3</bold></white>
 - can use a parquet file             <white><bold>--txs ./path/to/file.parquet[:COLUMN_NAME]</bold></white>
                                      (default column name is <white><bold>transaction_hash</bold></white>)
-- can use multiple parquet files     <white><bold>--txs ./path/to/ethereum__logs*.parquet</bold></white>"#
+- can use multiple parquet files     <white><bold>--txs ./path/to/ethereum__logs*.parquet</bold></white>
+- can use a text file of hashes      <white><bold>--txs ./path/to/hashes.txt</bold></white>
+                                     (separated by newlines, spaces, or commas)"#
         );
         println!("{}", content);
     } else if args.datatype.len() == 2 && args.datatype.contains(&"datasets".to_string()) {
         cryo_freeze::print_all_datasets();
+    } else if args.datatype[1] == "schemas" {
+        let datatypes = if args.datatype.len() == 2 {
+            Datatype::all()
+        } else {
+            parse::schemas::parse_datatypes(&args.datatype[2..].to_vec())?
+        };
+        let schemas: Vec<_> = datatypes.iter().map(|datatype| datatype.describe()).collect();
+        let json = serde_json::to_string_pretty(&schemas)
+            .map_err(|_| err("could not serialize schemas"))?;
+        println!("{}", json);
     } else {
         let args = args::Args { datatype: args.datatype[1..].to_vec(), ..args };
         let (datatypes, schemas) = super::parse::schemas::parse_schemas(&args)?;
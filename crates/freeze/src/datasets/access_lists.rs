@@ -0,0 +1,217 @@
+use crate::*;
+use ethers::{prelude::*, types::transaction::eip2930::AccessListWithGasUsed};
+use polars::prelude::*;
+
+/// columns for access lists
+#[cryo_to_df::to_df(Datatype::AccessLists)]
+#[derive(Default)]
+pub struct AccessLists {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    transaction_index: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    /// address accessed by the transaction
+    address: Vec<Option<Vec<u8>>>,
+    /// storage slots of the address accessed by the transaction
+    storage_keys: Vec<Vec<Vec<u8>>>,
+    /// gas used by the transaction when the access list is applied
+    gas_used: Vec<Option<u64>>,
+    /// error returned by the node if the access list could not be created
+    error: Vec<Option<String>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for AccessLists {
+    fn default_sort() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "transaction_index"])
+    }
+
+    fn estimate_requests(params: &Params) -> usize {
+        // the block or transaction, then an access list per transaction
+        match params.transaction_hash {
+            Some(_) => 2,
+            None => 1 + ESTIMATED_TRANSACTIONS_PER_BLOCK,
+        }
+    }
+}
+
+/// access list of a transaction or the error returned by the node
+type AccessListResult = Result<AccessListWithGasUsed, String>;
+
+/// access list of each transaction, along with its block number, index, and hash
+type TxAccessLists = Vec<(u32, u32, H256, AccessListResult)>;
+
+#[async_trait::async_trait]
+impl CollectByBlock for AccessLists {
+    type Response = TxAccessLists;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let block_number = request.block_number()?;
+        let block = source
+            .fetcher
+            .get_block_with_txs(block_number)
+            .await?
+            .ok_or(CollectError::BlockNotFound { number: block_number })?;
+        futures::future::try_join_all(
+            block.transactions.iter().map(|tx| tx_access_list(tx, source.clone())),
+        )
+        .await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        process_access_lists(response, columns, &query.schemas)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for AccessLists {
+    type Response = TxAccessLists;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let tx_hash = request.ethers_transaction_hash()?;
+        let tx = source
+            .fetcher
+            .get_transaction(tx_hash)
+            .await?
+            .ok_or(CollectError::TransactionNotFound { tx_hash })?;
+        Ok(vec![tx_access_list(&tx, source).await?])
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        process_access_lists(response, columns, &query.schemas)
+    }
+}
+
+/// build access list of transaction against the state before its block
+///
+/// transactions earlier in the same block are not applied first, so access lists of
+/// transactions that depend on them can differ from what was accessed on chain. errors returned
+/// by the node, such as reverts, are kept so that one transaction does not fail its whole block
+async fn tx_access_list(
+    tx: &Transaction,
+    source: Arc<Source>,
+) -> R<(u32, u32, H256, AccessListResult)> {
+    let block_number = tx.block_number.ok_or(CollectError::MissingBlockNumber)?.as_u64();
+    let transaction_index =
+        tx.transaction_index.ok_or(err("transaction index not specified"))?.as_u32();
+    let request = TransactionRequest {
+        from: Some(tx.from),
+        to: tx.to.map(NameOrAddress::Address),
+        gas: Some(tx.gas),
+        gas_price: tx.gas_price,
+        value: Some(tx.value),
+        data: Some(tx.input.clone()),
+        ..Default::default()
+    };
+    let parent = BlockNumber::Number(block_number.saturating_sub(1).into());
+    let access_list =
+        node_error_as_result(source.fetcher.create_access_list(request, parent).await)?;
+    Ok((block_number as u32, transaction_index, tx.hash, access_list))
+}
+
+/// turn errors returned by the node into an error message, other errors are still raised
+fn node_error_as_result(result: R<AccessListWithGasUsed>) -> R<AccessListResult> {
+    match result {
+        Ok(access_list) => Ok(Ok(access_list)),
+        Err(CollectError::ProviderError(e)) => match RpcError::as_error_response(&e) {
+            Some(response) => Ok(Err(response.message.clone())),
+            None => Err(CollectError::ProviderError(e)),
+        },
+        Err(e) => Err(e),
+    }
+}
+
+/// process access lists into columns, one row per accessed address
+///
+/// a transaction whose access list could not be created gets a single row with its error
+fn process_access_lists(
+    response: TxAccessLists,
+    columns: &mut AccessLists,
+    schemas: &Schemas,
+) -> R<()> {
+    let schema = schemas.get_schema(&Datatype::AccessLists)?;
+    for (block_number, transaction_index, tx_hash, access_list) in response.into_iter() {
+        let rows = match access_list {
+            Ok(access_list) => {
+                let gas_used = access_list.gas_used.as_u64();
+                access_list
+                    .access_list
+                    .0
+                    .into_iter()
+                    .map(|item| {
+                        let keys =
+                            item.storage_keys.iter().map(|key| key.as_bytes().to_vec()).collect();
+                        (Some(item.address.as_bytes().to_vec()), keys, Some(gas_used), None)
+                    })
+                    .collect()
+            }
+            Err(error) => vec![(None, vec![], None, Some(error))],
+        };
+        for (address, storage_keys, gas_used, error) in rows {
+            columns.n_rows += 1;
+            store!(schema, columns, block_number, block_number);
+            store!(schema, columns, transaction_index, transaction_index);
+            store!(schema, columns, transaction_hash, tx_hash.as_bytes().to_vec());
+            store!(schema, columns, address, address);
+            store!(schema, columns, storage_keys, storage_keys);
+            store!(schema, columns, gas_used, gas_used);
+            store!(schema, columns, error, error);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::transaction::eip2930::{AccessList, AccessListItem};
+    use std::collections::HashMap;
+
+    #[test]
+    fn keeps_node_errors_per_transaction() {
+        let response =
+            JsonRpcError { code: 3, message: "execution reverted".to_string(), data: None };
+        let reverted =
+            ProviderError::JsonRpcClientError(Box::new(MockError::JsonRpcError(response)));
+        let result = node_error_as_result(Err(CollectError::ProviderError(reverted)));
+        assert_eq!(result.unwrap().unwrap_err(), "execution reverted");
+
+        let transport = ProviderError::CustomError("connection refused".to_string());
+        assert!(node_error_as_result(Err(CollectError::ProviderError(transport))).is_err());
+    }
+
+    #[test]
+    fn stores_failed_access_lists_as_errors() {
+        let schema = Datatype::AccessLists
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &None, None, None)
+            .unwrap();
+        let schemas = HashMap::from([(Datatype::AccessLists, schema)]);
+        let item = AccessListItem {
+            address: H160::repeat_byte(1),
+            storage_keys: vec![H256::repeat_byte(2)],
+        };
+        let access_list =
+            AccessListWithGasUsed { access_list: AccessList(vec![item]), gas_used: 21000.into() };
+        let response = vec![
+            (1, 0, H256::repeat_byte(3), Ok(access_list)),
+            (1, 1, H256::repeat_byte(4), Err("execution reverted".to_string())),
+        ];
+        let mut columns = AccessLists::default();
+        process_access_lists(response, &mut columns, &schemas).unwrap();
+
+        assert_eq!(columns.n_rows, 2);
+        assert_eq!(columns.address, vec![Some(vec![1; 20]), None]);
+        assert_eq!(columns.storage_keys, vec![vec![vec![2; 32]], vec![]]);
+        assert_eq!(columns.gas_used, vec![Some(21000), None]);
+        assert_eq!(columns.error, vec![None, Some("execution reverted".to_string())]);
+    }
+
+    #[test]
+    fn estimates_a_request_per_transaction() {
+        let block = Params { block_number: Some(1), ..Default::default() };
+        assert_eq!(AccessLists::estimate_requests(&block), 1 + ESTIMATED_TRANSACTIONS_PER_BLOCK);
+        let tx = Params { transaction_hash: Some(vec![0; 32]), ..Default::default() };
+        assert_eq!(AccessLists::estimate_requests(&tx), 2);
+    }
+}
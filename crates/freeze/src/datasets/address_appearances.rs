@@ -10,7 +10,9 @@ pub struct AddressAppearances {
     n_rows: usize,
     block_number: Vec<u32>,
     transaction_hash: Vec<Vec<u8>>,
+    /// address that appears in the block
     address: Vec<Vec<u8>>,
+    /// how the address appears, such as tx_from, tx_to or a log topic
     relationship: Vec<String>,
     chain_id: Vec<u64>,
 }
@@ -20,6 +22,18 @@ impl Dataset for AddressAppearances {
     fn default_sort() -> Option<Vec<&'static str>> {
         Some(vec!["block_number", "transaction_hash", "address", "relationship"])
     }
+
+    fn estimate_requests(params: &Params) -> usize {
+        // block, logs, and traces, or transaction, block, receipt, and traces
+        match params.transaction_hash {
+            Some(_) => 4,
+            None => 3,
+        }
+    }
+
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::Trace]
+    }
 }
 
 type BlockLogsTraces = (Block<TxHash>, Vec<Log>, Vec<Trace>);
@@ -0,0 +1,160 @@
+use super::calls::{store_call_trace, CallTrace};
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// columns for address traces, call traces from or to the given addresses
+#[cryo_to_df::to_df(Datatype::AddressTraces)]
+#[derive(Default)]
+pub struct AddressTraces {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    transaction_hash: Vec<Option<Vec<u8>>>,
+    transaction_index: Vec<Option<u32>>,
+    /// position of the trace in the call tree, as indices joined by underscores
+    trace_address: Vec<String>,
+    /// type of the call, such as call, delegatecall or staticcall
+    call_type: Vec<String>,
+    /// address that made the call
+    from_address: Vec<Vec<u8>>,
+    /// address that was called
+    to_address: Vec<Option<Vec<u8>>>,
+    /// value sent with the call, in wei
+    value: Vec<U256>,
+    /// gas given to the call
+    gas: Vec<Option<u64>>,
+    /// gas used by the call
+    gas_used: Vec<Option<u64>>,
+    /// call data of the call
+    input: Vec<Option<Vec<u8>>>,
+    /// data returned by the call
+    output: Vec<Option<Vec<u8>>>,
+    /// error message if the trace reverted
+    error: Vec<Option<String>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for AddressTraces {
+    fn default_sort() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "transaction_index", "trace_address"])
+    }
+
+    fn optional_parameters() -> Vec<Dim> {
+        vec![Dim::FromAddress, Dim::ToAddress]
+    }
+
+    fn use_block_ranges() -> bool {
+        true
+    }
+
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::Trace]
+    }
+}
+
+/// number of traces requested per `trace_filter` call
+const TRACE_FILTER_PAGE_SIZE: usize = 10_000;
+
+#[async_trait::async_trait]
+impl CollectByBlock for AddressTraces {
+    type Response = Vec<Trace>;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let (start, end) = request.block_range()?;
+        let mut filter = TraceFilter::default().from_block(start).to_block(end);
+        if request.from_address.is_none() && request.to_address.is_none() {
+            return Err(err("address_traces requires from_address or to_address"))
+        }
+        if let Some(from_address) = &request.from_address {
+            filter = filter.from_address(vec![H160::from_slice(from_address)]);
+        }
+        if let Some(to_address) = &request.to_address {
+            filter = filter.to_address(vec![H160::from_slice(to_address)]);
+        }
+        source.fetcher.trace_filter_paged(filter, TRACE_FILTER_PAGE_SIZE).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        // matching traces are not contiguous per transaction, so only the failed traces
+        // themselves can be excluded
+        let traces = if query.exclude_failed {
+            response.into_iter().filter(|trace| trace.error.is_none()).collect()
+        } else {
+            response
+        };
+        let traces = query.trace_addresses.filter(traces);
+        process_address_traces(&traces, columns, &query.schemas)
+    }
+}
+
+impl CollectByTransaction for AddressTraces {
+    type Response = ();
+}
+
+/// process traces into columns, skipping block and uncle rewards
+pub(crate) fn process_address_traces(
+    traces: &[Trace],
+    columns: &mut AddressTraces,
+    schemas: &Schemas,
+) -> R<()> {
+    let schema = schemas.get(&Datatype::AddressTraces).ok_or(err("schema not provided"))?;
+    for (trace, call) in traces.iter().filter_map(|trace| Some((trace, CallTrace::of(trace)?))) {
+        store_call_trace!(schema, columns, trace, call);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn trace(action: Action, action_type: ActionType, error: Option<String>) -> Trace {
+        Trace {
+            action,
+            result: None,
+            trace_address: vec![],
+            subtraces: 0,
+            transaction_position: Some(2),
+            transaction_hash: Some(H256::repeat_byte(1)),
+            block_number: 7,
+            block_hash: H256::zero(),
+            action_type,
+            error,
+        }
+    }
+
+    #[test]
+    fn emits_one_row_per_call_skipping_rewards() {
+        let schema = Datatype::AddressTraces
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &None, None, None)
+            .unwrap();
+        let schemas = HashMap::from([(Datatype::AddressTraces, schema)]);
+        let call = ethers::types::Call {
+            from: H160::repeat_byte(3),
+            to: H160::repeat_byte(4),
+            value: 5.into(),
+            call_type: CallType::Call,
+            ..Default::default()
+        };
+        let reward =
+            Reward { author: H160::zero(), value: 2.into(), reward_type: RewardType::Block };
+        let traces = vec![
+            trace(Action::Call(call), ActionType::Call, None),
+            trace(Action::Reward(reward), ActionType::Reward, None),
+            trace(Action::Create(Create::default()), ActionType::Create, Some("reverted".into())),
+        ];
+        let mut columns = AddressTraces::default();
+        process_address_traces(&traces, &mut columns, &schemas).unwrap();
+
+        assert_eq!(columns.n_rows, 2);
+        assert_eq!(columns.block_number, vec![7, 7]);
+        assert_eq!(columns.transaction_index, vec![Some(2), Some(2)]);
+        assert_eq!(columns.call_type, vec!["call", "create"]);
+        assert_eq!(columns.from_address, vec![vec![3; 20], vec![0; 20]]);
+        assert_eq!(columns.to_address, vec![Some(vec![4; 20]), None]);
+        assert_eq!(columns.value, vec![U256::from(5), U256::zero()]);
+        assert_eq!(columns.error, vec![None, Some("reverted".to_string())]);
+    }
+}
@@ -10,14 +10,21 @@ pub struct BalanceDiffs {
     pub(crate) block_number: Vec<Option<u32>>,
     pub(crate) transaction_index: Vec<Option<u32>>,
     pub(crate) transaction_hash: Vec<Option<Vec<u8>>>,
+    /// address whose balance changed
     pub(crate) address: Vec<Vec<u8>>,
+    /// balance before the transaction, in wei
     pub(crate) from_value: Vec<U256>,
+    /// balance after the transaction, in wei
     pub(crate) to_value: Vec<U256>,
     pub(crate) chain_id: Vec<u64>,
 }
 
 #[async_trait::async_trait]
-impl Dataset for BalanceDiffs {}
+impl Dataset for BalanceDiffs {
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::TraceReplay, Capability::DebugTrace]
+    }
+}
 
 type BlockTxsTraces = (Option<u32>, Vec<Option<Vec<u8>>>, Vec<ethers::types::BlockTrace>);
 
@@ -29,7 +36,7 @@ impl CollectByBlock for BalanceDiffs {
         let schema =
             query.schemas.get(&Datatype::BalanceDiffs).ok_or(err("schema not provided"))?;
         let include_txs = schema.has_column("transaction_hash");
-        source.fetcher.trace_block_state_diffs(request.block_number()? as u32, include_txs).await
+        source.fetcher.block_state_diffs(request.block_number()? as u32, include_txs).await
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
@@ -42,7 +49,7 @@ impl CollectByTransaction for BalanceDiffs {
     type Response = BlockTxsTraces;
 
     async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
-        source.fetcher.trace_transaction_state_diffs(request.transaction_hash()?).await
+        source.fetcher.transaction_state_diffs(request.transaction_hash()?).await
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
@@ -76,11 +83,9 @@ pub(crate) fn process_balance_diff(
     columns: &mut BalanceDiffs,
     schema: &Table,
 ) {
-    let (from, to) = match diff {
-        Diff::Same => return,
-        Diff::Born(value) => (U256::zero(), *value),
-        Diff::Died(value) => (*value, U256::zero()),
-        Diff::Changed(ChangedType { from, to }) => (*from, *to),
+    let (from, to) = match diff_from_to(diff) {
+        (None, None) => return,
+        (from, to) => from_to_or_default(from, to),
     };
     columns.n_rows += 1;
     store!(schema, columns, block_number, *block_number);
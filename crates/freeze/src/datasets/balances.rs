@@ -8,7 +8,9 @@ use polars::prelude::*;
 pub struct Balances {
     n_rows: usize,
     block_number: Vec<u32>,
+    /// address whose balance is queried
     address: Vec<Vec<u8>>,
+    /// balance of the address at the end of the block, in wei
     balance: Vec<U256>,
     chain_id: Vec<u64>,
 }
@@ -8,19 +8,41 @@ use polars::prelude::*;
 pub struct Blocks {
     n_rows: u64,
     block_hash: Vec<Option<Vec<u8>>>,
+    /// hash of the parent block
     parent_hash: Vec<Vec<u8>>,
+    /// address that received the block reward
     author: Vec<Option<Vec<u8>>>,
+    /// root of the state trie after the block
     state_root: Vec<Vec<u8>>,
+    /// root of the transactions trie of the block
     transactions_root: Vec<Vec<u8>>,
+    /// root of the receipts trie of the block
     receipts_root: Vec<Vec<u8>>,
     block_number: Vec<Option<u32>>,
+    /// gas used by all transactions of the block
     gas_used: Vec<u64>,
+    /// maximum gas that the block's transactions can use
+    gas_limit: Vec<u64>,
+    /// arbitrary data added by the block's producer
     extra_data: Vec<Vec<u8>>,
+    /// bloom filter of the logs of the block
     logs_bloom: Vec<Option<Vec<u8>>>,
+    /// unix timestamp of the block
     timestamp: Vec<u32>,
+    /// proof of work difficulty of the block, 0 after the merge
+    difficulty: Vec<U256>,
+    /// sum of the difficulty of the chain up to and including the block
     total_difficulty: Vec<Option<U256>>,
+    /// size of the block in bytes
     size: Vec<Option<u32>>,
+    /// base fee per unit of gas, null before london
     base_fee_per_gas: Vec<Option<u64>>,
+    /// gas used divided by gas limit
+    gas_used_ratio: Vec<Option<f64>>,
+    /// base fee per unit of gas of the next block
+    next_base_fee: Vec<Option<u64>>,
+    /// number of transactions in the block
+    transaction_count: Vec<u64>,
     chain_id: Vec<u64>,
 }
 
@@ -38,6 +60,14 @@ impl Dataset for Blocks {
             "chain_id",
         ])
     }
+
+    fn estimate_requests(params: &Params) -> usize {
+        // the transaction, then its block
+        match params.transaction_hash {
+            Some(_) => 2,
+            None => 1,
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -95,11 +125,80 @@ pub(crate) fn process_block<TX>(block: Block<TX>, columns: &mut Blocks, schema:
     store!(schema, columns, receipts_root, block.receipts_root.0.to_vec());
     store!(schema, columns, block_number, block.number.map(|x| x.as_u32()));
     store!(schema, columns, gas_used, block.gas_used.as_u64());
+    store!(schema, columns, gas_limit, block.gas_limit.as_u64());
     store!(schema, columns, extra_data, block.extra_data.to_vec());
     store!(schema, columns, logs_bloom, block.logs_bloom.map(|x| x.0.to_vec()));
     store!(schema, columns, timestamp, block.timestamp.as_u32());
+    store!(schema, columns, difficulty, block.difficulty);
     store!(schema, columns, total_difficulty, block.total_difficulty);
     store!(schema, columns, base_fee_per_gas, block.base_fee_per_gas.map(|x| x.as_u64()));
+    store!(schema, columns, gas_used_ratio, gas_used_ratio(block.gas_used, block.gas_limit));
+    store!(
+        schema,
+        columns,
+        next_base_fee,
+        block
+            .base_fee_per_gas
+            .and_then(|base_fee| next_base_fee(base_fee, block.gas_used, block.gas_limit))
+            .map(|x| x.as_u64())
+    );
     store!(schema, columns, size, block.size.map(|x| x.as_u32()));
+    store!(schema, columns, transaction_count, block.transactions.len() as u64);
     Ok(())
 }
+
+/// bound on the base fee change between blocks, as 1 / BASE_FEE_MAX_CHANGE_DENOMINATOR (eip-1559)
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// ratio of the gas limit to the gas target (eip-1559)
+const ELASTICITY_MULTIPLIER: u64 = 2;
+
+fn gas_used_ratio(gas_used: U256, gas_limit: U256) -> Option<f64> {
+    if gas_limit.is_zero() {
+        None
+    } else {
+        Some(gas_used.as_u64() as f64 / gas_limit.as_u64() as f64)
+    }
+}
+
+/// base fee of the child of a block, following the eip-1559 update rule
+pub(crate) fn next_base_fee(base_fee: U256, gas_used: U256, gas_limit: U256) -> Option<U256> {
+    let gas_target = gas_limit / ELASTICITY_MULTIPLIER;
+    if gas_target.is_zero() {
+        return None
+    }
+    let next = if gas_used == gas_target {
+        base_fee
+    } else if gas_used > gas_target {
+        let delta =
+            base_fee * (gas_used - gas_target) / gas_target / BASE_FEE_MAX_CHANGE_DENOMINATOR;
+        base_fee + delta.max(U256::one())
+    } else {
+        let delta =
+            base_fee * (gas_target - gas_used) / gas_target / BASE_FEE_MAX_CHANGE_DENOMINATOR;
+        base_fee.saturating_sub(delta)
+    };
+    Some(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn next(base_fee: u64, gas_used: u64, gas_limit: u64) -> Option<u64> {
+        next_base_fee(base_fee.into(), gas_used.into(), gas_limit.into()).map(|x| x.as_u64())
+    }
+
+    #[test]
+    fn computes_next_base_fee() {
+        // block 12965000 (london) -> 12965001
+        assert_eq!(next(1_000_000_000, 30_025_257, 30_029_122), Some(1_124_967_822));
+        // empty blocks lower the base fee by 1/8
+        assert_eq!(next(1_000_000_000, 0, 30_000_000), Some(875_000_000));
+        // blocks at the gas target keep the base fee
+        assert_eq!(next(1_000_000_000, 15_000_000, 30_000_000), Some(1_000_000_000));
+        // increases are at least 1 wei
+        assert_eq!(next(7, 15_000_001, 30_000_000), Some(8));
+        assert_eq!(next(7, 0, 0), None);
+    }
+}
@@ -0,0 +1,406 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// columns for calls, one row per call, create, or suicide trace
+#[cryo_to_df::to_df(Datatype::Calls)]
+#[derive(Default)]
+pub struct Calls {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    transaction_hash: Vec<Option<Vec<u8>>>,
+    transaction_index: Vec<Option<u32>>,
+    /// position of the trace in the call tree, as indices joined by underscores
+    trace_address: Vec<String>,
+    /// type of the call, such as call, delegatecall or staticcall
+    call_type: Vec<String>,
+    /// address that made the call
+    from_address: Vec<Vec<u8>>,
+    /// address that was called
+    to_address: Vec<Option<Vec<u8>>>,
+    /// value sent with the call, in wei
+    value: Vec<U256>,
+    /// gas given to the call
+    gas: Vec<Option<u64>>,
+    /// gas used by the call
+    gas_used: Vec<Option<u64>>,
+    /// call data of the call
+    input: Vec<Option<Vec<u8>>>,
+    /// data returned by the call
+    output: Vec<Option<Vec<u8>>>,
+    /// error message if the trace reverted
+    error: Vec<Option<String>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for Calls {
+    fn optional_parameters() -> Vec<Dim> {
+        vec![Dim::FromAddress, Dim::ToAddress]
+    }
+
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::Trace, Capability::DebugTrace]
+    }
+
+    fn estimate_requests(_params: &Params) -> usize {
+        // traces, plus the block or transaction when tracing with geth's callTracer
+        2
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for Calls {
+    type Response = Vec<Trace>;
+
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
+        let block_number = request.block_number()?;
+        let traces = if source.fetcher.capabilities().await?.trace {
+            source.fetcher.trace_block(block_number.into()).await?
+        } else {
+            // nodes without parity style traces are traced with geth's callTracer
+            let frames = source
+                .fetcher
+                .debug_trace_block(block_number, "callTracer")
+                .await?
+                .into_iter()
+                .map(|trace| match trace {
+                    GethTrace::Known(GethTraceFrame::CallTracer(frame)) => Ok(frame),
+                    _ => Err(CollectError::InvalidTraceResult),
+                })
+                .collect::<R<Vec<_>>>()?;
+            let block = source
+                .fetcher
+                .get_block(block_number)
+                .await?
+                .ok_or(CollectError::BlockNotFound { number: block_number })?;
+            let transactions = block.transactions.into_iter().enumerate().map(|(i, tx)| (tx, i));
+            call_frames_to_traces(block_number, transactions.collect(), frames)?
+        };
+        Ok(filter_traces_by_status_and_addresses(traces, &request, &query.trace_status))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let traces =
+            if query.exclude_failed { traces::filter_failed_traces(response) } else { response };
+        let traces = query.trace_addresses.filter(traces);
+        process_calls(&traces, columns, &query.schemas)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for Calls {
+    type Response = Vec<Trace>;
+
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
+        let tx_hash = request.ethers_transaction_hash()?;
+        let traces = if source.fetcher.capabilities().await?.trace {
+            source.fetcher.trace_transaction(tx_hash).await?
+        } else {
+            let tx = source
+                .fetcher
+                .get_transaction(tx_hash)
+                .await?
+                .ok_or(CollectError::TransactionNotFound { tx_hash })?;
+            let (block_number, tx_index) = match (tx.block_number, tx.transaction_index) {
+                (Some(block_number), Some(tx_index)) => {
+                    (block_number.as_u64(), tx_index.as_usize())
+                }
+                _ => return Err(err("transaction is not included in a block")),
+            };
+            let (_, _, frames) = source
+                .fetcher
+                .geth_debug_trace_transaction_calls(tx_hash.as_bytes().to_vec(), false)
+                .await?;
+            call_frames_to_traces(block_number, vec![(tx_hash, tx_index)], frames)?
+        };
+        Ok(filter_traces_by_status_and_addresses(traces, &request, &query.trace_status))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let traces =
+            if query.exclude_failed { traces::filter_failed_traces(response) } else { response };
+        let traces = query.trace_addresses.filter(traces);
+        process_calls(&traces, columns, &query.schemas)
+    }
+}
+
+/// store a call trace as a row of a dataset with the columns of calls
+macro_rules! store_call_trace {
+    ($schema:expr, $columns:expr, $trace:expr, $call:expr) => {
+        $columns.n_rows += 1;
+        store!($schema, $columns, block_number, $trace.block_number as u32);
+        store!(
+            $schema,
+            $columns,
+            transaction_hash,
+            $trace.transaction_hash.map(|x| x.as_bytes().to_vec())
+        );
+        store!($schema, $columns, transaction_index, $trace.transaction_position.map(|x| x as u32));
+        store!($schema, $columns, trace_address, $call.trace_address);
+        store!($schema, $columns, call_type, $call.call_type);
+        store!($schema, $columns, from_address, $call.from.as_bytes().to_vec());
+        store!($schema, $columns, to_address, $call.to.map(|x| x.as_bytes().to_vec()));
+        store!($schema, $columns, value, $call.value);
+        store!($schema, $columns, gas, $call.gas);
+        store!($schema, $columns, gas_used, $call.gas_used);
+        store!($schema, $columns, input, $call.input);
+        store!($schema, $columns, output, $call.output);
+        store!($schema, $columns, error, $trace.error.clone());
+    };
+}
+pub(crate) use store_call_trace;
+
+/// process traces into columns, skipping block and uncle rewards
+pub(crate) fn process_calls(traces: &[Trace], columns: &mut Calls, schemas: &Schemas) -> R<()> {
+    let schema = schemas.get(&Datatype::Calls).ok_or(err("schema not provided"))?;
+    for (trace, call) in traces.iter().filter_map(|trace| Some((trace, CallTrace::of(trace)?))) {
+        store_call_trace!(schema, columns, trace, call);
+    }
+    Ok(())
+}
+
+/// convert geth callTracer frames into parity style traces, one frame per transaction
+pub(crate) fn call_frames_to_traces(
+    block_number: u64,
+    transactions: Vec<(H256, usize)>,
+    frames: Vec<CallFrame>,
+) -> R<Vec<Trace>> {
+    if transactions.len() != frames.len() {
+        return Err(err("number of call traces does not match number of transactions"))
+    }
+    let mut traces = Vec::new();
+    for ((tx_hash, tx_index), frame) in transactions.into_iter().zip(frames) {
+        push_call_frame(frame, vec![], block_number, tx_hash, tx_index, &mut traces)?;
+    }
+    Ok(traces)
+}
+
+fn push_call_frame(
+    frame: CallFrame,
+    trace_address: Vec<usize>,
+    block_number: u64,
+    tx_hash: H256,
+    tx_index: usize,
+    traces: &mut Vec<Trace>,
+) -> R<()> {
+    let to = match frame.to {
+        Some(NameOrAddress::Address(address)) => Some(address),
+        Some(NameOrAddress::Name(_)) => return Err(err("block name string not allowed")),
+        None => None,
+    };
+    let value = frame.value.unwrap_or_default();
+    let output = frame.output.unwrap_or_default();
+    let (action, action_type, result) = match frame.typ.as_str() {
+        "CREATE" | "CREATE2" => (
+            Action::Create(Create { from: frame.from, value, gas: frame.gas, init: frame.input }),
+            ActionType::Create,
+            Some(Res::Create(CreateResult {
+                gas_used: frame.gas_used,
+                code: output,
+                address: to.unwrap_or_default(),
+            })),
+        ),
+        "SELFDESTRUCT" => (
+            Action::Suicide(Suicide {
+                address: frame.from,
+                refund_address: to.unwrap_or_default(),
+                balance: value,
+            }),
+            ActionType::Suicide,
+            None,
+        ),
+        typ => {
+            let call_type = match typ {
+                "DELEGATECALL" => CallType::DelegateCall,
+                "STATICCALL" => CallType::StaticCall,
+                "CALLCODE" => CallType::CallCode,
+                _ => CallType::Call,
+            };
+            let call = ethers::types::Call {
+                from: frame.from,
+                to: to.unwrap_or_default(),
+                value,
+                gas: frame.gas,
+                input: frame.input,
+                call_type,
+            };
+            let result = CallResult { gas_used: frame.gas_used, output };
+            (Action::Call(call), ActionType::Call, Some(Res::Call(result)))
+        }
+    };
+    let subcalls = frame.calls.unwrap_or_default();
+    traces.push(Trace {
+        action,
+        // parity traces have no result for failed calls
+        result: if frame.error.is_some() { None } else { result },
+        trace_address: trace_address.clone(),
+        subtraces: subcalls.len(),
+        transaction_position: Some(tx_index),
+        transaction_hash: Some(tx_hash),
+        block_number,
+        block_hash: H256::zero(),
+        action_type,
+        error: frame.error,
+    });
+    for (i, subcall) in subcalls.into_iter().enumerate() {
+        let mut sub_trace_address = trace_address.clone();
+        sub_trace_address.push(i);
+        push_call_frame(subcall, sub_trace_address, block_number, tx_hash, tx_index, traces)?;
+    }
+    Ok(())
+}
+
+/// fields of a call, create, or suicide trace
+pub(crate) struct CallTrace {
+    pub(crate) trace_address: String,
+    pub(crate) call_type: String,
+    pub(crate) from: H160,
+    pub(crate) to: Option<H160>,
+    pub(crate) value: U256,
+    pub(crate) gas: Option<u64>,
+    pub(crate) gas_used: Option<u64>,
+    pub(crate) input: Option<Vec<u8>>,
+    pub(crate) output: Option<Vec<u8>>,
+}
+
+impl CallTrace {
+    /// flatten a trace, returns None for block and uncle rewards
+    pub(crate) fn of(trace: &Trace) -> Option<CallTrace> {
+        let (call_type, from, to, value, gas, input) = match &trace.action {
+            Action::Call(action) => (
+                traces::action_call_type_to_string(&action.call_type),
+                action.from,
+                Some(action.to),
+                action.value,
+                Some(action.gas.as_u64()),
+                Some(action.input.to_vec()),
+            ),
+            Action::Create(action) => {
+                let to = match &trace.result {
+                    Some(Res::Create(result)) => Some(result.address),
+                    _ => None,
+                };
+                let input = Some(action.init.to_vec());
+                (
+                    "create".to_string(),
+                    action.from,
+                    to,
+                    action.value,
+                    Some(action.gas.as_u64()),
+                    input,
+                )
+            }
+            Action::Suicide(action) => (
+                "suicide".to_string(),
+                action.address,
+                Some(action.refund_address),
+                action.balance,
+                None,
+                None,
+            ),
+            Action::Reward(_) => return None,
+        };
+        let (gas_used, output) = match &trace.result {
+            Some(Res::Call(result)) => {
+                (Some(result.gas_used.as_u64()), Some(result.output.to_vec()))
+            }
+            Some(Res::Create(result)) => {
+                (Some(result.gas_used.as_u64()), Some(result.code.to_vec()))
+            }
+            Some(Res::None) | None => (None, None),
+        };
+        let trace_address =
+            trace.trace_address.iter().map(|n| n.to_string()).collect::<Vec<String>>().join("_");
+        Some(CallTrace { trace_address, call_type, from, to, value, gas, gas_used, input, output })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn trace(action: Action, action_type: ActionType, trace_address: Vec<usize>) -> Trace {
+        Trace {
+            action,
+            result: None,
+            trace_address,
+            subtraces: 0,
+            transaction_position: Some(0),
+            transaction_hash: Some(H256::zero()),
+            block_number: 1,
+            block_hash: H256::zero(),
+            action_type,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn emits_one_row_per_call_create_and_suicide() {
+        let schema = Datatype::Calls
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &None, None, None)
+            .unwrap();
+        let schemas = HashMap::from([(Datatype::Calls, schema)]);
+        let call = ethers::types::Call {
+            call_type: CallType::DelegateCall,
+            gas: 21_000.into(),
+            ..Default::default()
+        };
+        let traces = vec![
+            trace(Action::Call(call), ActionType::Call, vec![]),
+            trace(Action::Create(Create::default()), ActionType::Create, vec![0]),
+            trace(Action::Suicide(Suicide::default()), ActionType::Suicide, vec![1]),
+            trace(
+                Action::Reward(Reward {
+                    author: H160::zero(),
+                    value: 2.into(),
+                    reward_type: RewardType::Block,
+                }),
+                ActionType::Reward,
+                vec![],
+            ),
+        ];
+        let mut columns = Calls::default();
+        process_calls(&traces, &mut columns, &schemas).unwrap();
+
+        assert_eq!(columns.n_rows, 3);
+        assert_eq!(columns.call_type, vec!["delegate_call", "create", "suicide"]);
+        assert_eq!(columns.trace_address, vec!["", "0", "1"]);
+        assert_eq!(columns.gas, vec![Some(21_000), Some(0), None]);
+    }
+
+    #[test]
+    fn converts_call_frames_to_traces() {
+        let frame = |typ: &str, calls: Option<Vec<CallFrame>>| CallFrame {
+            typ: typ.to_string(),
+            to: Some(NameOrAddress::Address(H160::repeat_byte(2))),
+            gas: 100.into(),
+            gas_used: 10.into(),
+            calls,
+            ..Default::default()
+        };
+        let failed =
+            CallFrame { error: Some("execution reverted".to_string()), ..frame("CREATE2", None) };
+        let root = frame("CALL", Some(vec![frame("STATICCALL", None), failed]));
+        let tx_hash = H256::repeat_byte(9);
+        let traces = call_frames_to_traces(5, vec![(tx_hash, 3)], vec![root]).unwrap();
+
+        let addresses: Vec<_> = traces.iter().map(|trace| trace.trace_address.clone()).collect();
+        assert_eq!(addresses, vec![vec![], vec![0], vec![1]]);
+        assert_eq!(traces[0].subtraces, 2);
+        assert!(traces.iter().all(|trace| trace.transaction_position == Some(3)));
+        assert!(matches!(traces[2].action, Action::Create(_)));
+        assert!(traces[2].result.is_none());
+
+        let schema = Datatype::Calls
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &None, None, None)
+            .unwrap();
+        let schemas = HashMap::from([(Datatype::Calls, schema)]);
+        let mut columns = Calls::default();
+        process_calls(&traces, &mut columns, &schemas).unwrap();
+        assert_eq!(columns.call_type, vec!["call", "static_call", "create"]);
+        assert_eq!(columns.gas_used, vec![Some(10), Some(10), None]);
+        assert_eq!(columns.error, vec![None, None, Some("execution reverted".to_string())]);
+    }
+}
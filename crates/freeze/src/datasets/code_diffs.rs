@@ -10,14 +10,21 @@ pub struct CodeDiffs {
     pub(crate) block_number: Vec<Option<u32>>,
     pub(crate) transaction_index: Vec<Option<u32>>,
     pub(crate) transaction_hash: Vec<Option<Vec<u8>>>,
+    /// address whose code changed
     pub(crate) address: Vec<Vec<u8>>,
+    /// code before the transaction
     pub(crate) from_value: Vec<Vec<u8>>,
+    /// code after the transaction
     pub(crate) to_value: Vec<Vec<u8>>,
     pub(crate) chain_id: Vec<u64>,
 }
 
 #[async_trait::async_trait]
-impl Dataset for CodeDiffs {}
+impl Dataset for CodeDiffs {
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::TraceReplay, Capability::DebugTrace]
+    }
+}
 
 type BlockTxTraces = (Option<u32>, Vec<Option<Vec<u8>>>, Vec<ethers::types::BlockTrace>);
 
@@ -28,7 +35,7 @@ impl CollectByBlock for CodeDiffs {
     async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
         let schema = query.schemas.get(&Datatype::CodeDiffs).ok_or(err("schema not provided"))?;
         let include_txs = schema.has_column("transaction_hash");
-        source.fetcher.trace_block_state_diffs(request.block_number()? as u32, include_txs).await
+        source.fetcher.block_state_diffs(request.block_number()? as u32, include_txs).await
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
@@ -41,7 +48,7 @@ impl CollectByTransaction for CodeDiffs {
     type Response = BlockTxTraces;
 
     async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
-        source.fetcher.trace_transaction_state_diffs(request.transaction_hash()?).await
+        source.fetcher.transaction_state_diffs(request.transaction_hash()?).await
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
@@ -76,22 +83,16 @@ pub(crate) fn process_code_diff(
     schema: &Table,
 ) {
     // this code will skip self-destructs and EOAs
-    let (from, to) = match diff {
-        Diff::Same => return,
-        Diff::Born(value) => {
-            if value.is_empty() {
-                return
-            };
-            (Vec::new(), value.to_vec())
-        }
-        Diff::Died(value) => (value.to_vec(), Vec::new()),
-        Diff::Changed(ChangedType { from, to }) => (from.to_vec(), to.to_vec()),
+    let (from, to) = match diff_from_to(diff) {
+        (None, None) => return,
+        (None, Some(to)) if to.is_empty() => return,
+        (from, to) => from_to_or_default(from, to),
     };
     columns.n_rows += 1;
     store!(schema, columns, block_number, *block_number);
     store!(schema, columns, transaction_index, Some(transaction_index as u32));
     store!(schema, columns, transaction_hash, transaction_hash.clone());
     store!(schema, columns, address, addr.as_bytes().to_vec());
-    store!(schema, columns, from_value, from);
-    store!(schema, columns, to_value, to);
+    store!(schema, columns, from_value, from.to_vec());
+    store!(schema, columns, to_value, to.to_vec());
 }
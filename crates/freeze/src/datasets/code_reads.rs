@@ -11,13 +11,19 @@ pub struct CodeReads {
     pub(crate) block_number: Vec<Option<u32>>,
     pub(crate) transaction_index: Vec<Option<u32>>,
     pub(crate) transaction_hash: Vec<Option<Vec<u8>>>,
+    /// address whose code is read
     pub(crate) contract_address: Vec<Vec<u8>>,
+    /// code read by the transaction
     pub(crate) code: Vec<Vec<u8>>,
     pub(crate) chain_id: Vec<u64>,
 }
 
 #[async_trait::async_trait]
-impl Dataset for CodeReads {}
+impl Dataset for CodeReads {
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::DebugTrace]
+    }
+}
 
 type BlockTxsTraces = (Option<u32>, Vec<Option<Vec<u8>>>, Vec<BTreeMap<H160, AccountState>>);
 
@@ -8,7 +8,9 @@ use polars::prelude::*;
 pub struct Codes {
     n_rows: usize,
     block_number: Vec<u32>,
+    /// address whose code is queried
     address: Vec<Vec<u8>>,
+    /// code of the address at the end of the block
     code: Vec<Vec<u8>>,
     chain_id: Vec<u64>,
 }
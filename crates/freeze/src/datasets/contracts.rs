@@ -10,23 +10,40 @@ use polars::prelude::*;
 pub struct Contracts {
     n_rows: u64,
     block_number: Vec<u32>,
+    /// index of the contract creation within its block
     create_index: Vec<u32>,
+    transaction_index: Vec<Option<u32>>,
     transaction_hash: Vec<Option<Vec<u8>>>,
+    /// address of the created contract
     contract_address: Vec<Vec<u8>>,
+    /// address that sent the transaction that created the contract
     deployer: Vec<Vec<u8>>,
+    /// address that created the contract, the deployer unless created by a contract
     factory: Vec<Vec<u8>>,
+    /// code run to create the contract
     init_code: Vec<Vec<u8>>,
+    /// code of the created contract
     code: Vec<Vec<u8>>,
+    /// keccak hash of the init code
     init_code_hash: Vec<Vec<u8>>,
+    /// keccak hash of the contract's code
     code_hash: Vec<Vec<u8>>,
     chain_id: Vec<u64>,
 }
 
 #[async_trait::async_trait]
 impl Dataset for Contracts {
+    fn aliases() -> Vec<&'static str> {
+        vec!["contract_creations"]
+    }
+
     fn default_sort() -> Option<Vec<&'static str>> {
         Some(vec!["block_number", "create_index"])
     }
+
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::Trace]
+    }
 }
 
 #[async_trait::async_trait]
@@ -38,8 +55,10 @@ impl CollectByBlock for Contracts {
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let traces = query.trace_status.filter(response);
         let traces =
-            if query.exclude_failed { traces::filter_failed_traces(response) } else { response };
+            if query.exclude_failed { traces::filter_failed_traces(traces) } else { traces };
+        let traces = query.trace_addresses.filter(traces);
         process_contracts(&traces, columns, &query.schemas)
     }
 }
@@ -53,8 +72,10 @@ impl CollectByTransaction for Contracts {
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let traces = query.trace_status.filter(response);
         let traces =
-            if query.exclude_failed { traces::filter_failed_traces(response) } else { response };
+            if query.exclude_failed { traces::filter_failed_traces(traces) } else { traces };
+        let traces = query.trace_addresses.filter(traces);
         process_contracts(&traces, columns, &query.schemas)
     }
 }
@@ -84,6 +105,8 @@ pub(crate) fn process_contracts(
             store!(schema, columns, block_number, trace.block_number as u32);
             store!(schema, columns, create_index, create_index);
             create_index += 1;
+            let tx_index = trace.transaction_position.map(|x| x as u32);
+            store!(schema, columns, transaction_index, tx_index);
             let tx = trace.transaction_hash;
             store!(schema, columns, transaction_hash, tx.map(|x| x.as_bytes().to_vec()));
             store!(schema, columns, contract_address, result.address.as_bytes().into());
@@ -91,8 +114,8 @@ pub(crate) fn process_contracts(
             store!(schema, columns, factory, create.from.as_bytes().into());
             store!(schema, columns, init_code, create.init.to_vec());
             store!(schema, columns, code, result.code.to_vec());
-            store!(schema, columns, code_hash, keccak256(create.init.clone()).into());
-            store!(schema, columns, init_code_hash, keccak256(result.code.clone()).into());
+            store!(schema, columns, init_code_hash, keccak256(create.init.clone()).into());
+            store!(schema, columns, code_hash, keccak256(result.code.clone()).into());
         }
     }
     Ok(())
@@ -8,8 +8,11 @@ use polars::prelude::*;
 pub struct Erc20Balances {
     n_rows: u64,
     block_number: Vec<u32>,
+    /// address of the erc20 token
     erc20: Vec<Vec<u8>>,
+    /// address whose token balance is queried
     address: Vec<Vec<u8>>,
+    /// token balance of the address, null if the call failed
     balance: Vec<Option<U256>>,
     chain_id: Vec<u64>,
 }
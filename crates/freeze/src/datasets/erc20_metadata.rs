@@ -1,4 +1,5 @@
 use crate::*;
+use ethers::prelude::*;
 use polars::prelude::*;
 
 /// columns for transactions
@@ -7,10 +8,16 @@ use polars::prelude::*;
 pub struct Erc20Metadata {
     n_rows: u64,
     block_number: Vec<u32>,
+    /// address of the erc20 token
     erc20: Vec<Vec<u8>>,
+    /// name of the token, null if the call failed
     name: Vec<Option<String>>,
+    /// symbol of the token, null if the call failed
     symbol: Vec<Option<String>>,
+    /// number of decimals of the token, null if the call failed
     decimals: Vec<Option<u32>>,
+    /// total supply of the token, null if the call failed
+    total_supply: Vec<Option<U256>>,
     chain_id: Vec<u64>,
 }
 
@@ -31,6 +38,10 @@ impl Dataset for Erc20Metadata {
     fn arg_aliases() -> Option<std::collections::HashMap<Dim, Dim>> {
         Some([(Dim::Contract, Dim::Address)].into_iter().collect())
     }
+
+    fn estimate_requests(_params: &Params) -> usize {
+        4
+    }
 }
 
 pub(crate) fn remove_control_characters(s: &str) -> String {
@@ -38,9 +49,25 @@ pub(crate) fn remove_control_characters(s: &str) -> String {
     re.replace_all(s, "").to_string()
 }
 
+/// decode the output of a `name()` or `symbol()` call
+///
+/// outputs are decoded as an abi-encoded `string`, falling back to a right-padded `bytes32`
+/// as returned by older tokens such as MKR
+pub(crate) fn decode_string_output(output: &[u8]) -> Option<String> {
+    let decoded = match ethers::abi::decode(&[ethers::abi::ParamType::String], output) {
+        Ok(tokens) => tokens.into_iter().next().and_then(|token| token.into_string()),
+        Err(_) if output.len() == 32 => {
+            let end = output.iter().rposition(|byte| *byte != 0).map_or(0, |i| i + 1);
+            String::from_utf8(output[..end].to_vec()).ok()
+        }
+        Err(_) => None,
+    };
+    decoded.map(|s| remove_control_characters(&s))
+}
+
 #[async_trait::async_trait]
 impl CollectByBlock for Erc20Metadata {
-    type Response = (u32, Vec<u8>, Option<String>, Option<String>, Option<u32>);
+    type Response = (u32, Vec<u8>, Option<String>, Option<String>, Option<u32>, Option<U256>);
 
     async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
         let block_number = request.ethers_block_number()?;
@@ -49,18 +76,14 @@ impl CollectByBlock for Erc20Metadata {
         // name
         let call_data = FUNCTION_ERC20_NAME.clone();
         let name = match source.fetcher.call2(address, call_data, block_number).await {
-            Ok(output) => {
-                String::from_utf8(output.to_vec()).ok().map(|s| remove_control_characters(&s))
-            }
+            Ok(output) => decode_string_output(&output),
             Err(_) => None,
         };
 
         // symbol
         let call_data = FUNCTION_ERC20_SYMBOL.clone();
         let symbol = match source.fetcher.call2(address, call_data, block_number).await {
-            Ok(output) => {
-                String::from_utf8(output.to_vec()).ok().map(|s| remove_control_characters(&s))
-            }
+            Ok(output) => decode_string_output(&output),
             Err(_) => None,
         };
 
@@ -71,18 +94,33 @@ impl CollectByBlock for Erc20Metadata {
             Err(_) => None,
         };
 
-        Ok((request.block_number()? as u32, request.address()?, name, symbol, decimals))
+        // total supply
+        let call_data = FUNCTION_ERC20_TOTAL_SUPPLY.clone();
+        let total_supply = match source.fetcher.call2(address, call_data, block_number).await {
+            Ok(output) if output.len() == 32 => Some(U256::from_big_endian(&output)),
+            _ => None,
+        };
+
+        Ok((
+            request.block_number()? as u32,
+            request.address()?,
+            name,
+            symbol,
+            decimals,
+            total_supply,
+        ))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
         let schema = query.schemas.get_schema(&Datatype::Erc20Metadata)?;
-        let (block, address, name, symbol, decimals) = response;
+        let (block, address, name, symbol, decimals, total_supply) = response;
         columns.n_rows += 1;
         store!(schema, columns, block_number, block);
         store!(schema, columns, erc20, address);
         store!(schema, columns, name, name);
         store!(schema, columns, symbol, symbol);
         store!(schema, columns, decimals, decimals);
+        store!(schema, columns, total_supply, total_supply);
         Ok(())
     }
 }
@@ -90,3 +128,26 @@ impl CollectByBlock for Erc20Metadata {
 impl CollectByTransaction for Erc20Metadata {
     type Response = ();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_abi_string_output() {
+        let output = ethers::abi::encode(&[ethers::abi::Token::String("WETH".into())]);
+        assert_eq!(decode_string_output(&output), Some("WETH".to_string()));
+    }
+
+    #[test]
+    fn decode_bytes32_output() {
+        let mut output = b"MKR".to_vec();
+        output.resize(32, 0);
+        assert_eq!(decode_string_output(&output), Some("MKR".to_string()));
+    }
+
+    #[test]
+    fn decode_invalid_output() {
+        assert_eq!(decode_string_output(&[0xff; 7]), None);
+    }
+}
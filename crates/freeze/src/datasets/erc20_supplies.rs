@@ -8,7 +8,9 @@ use polars::prelude::*;
 pub struct Erc20Supplies {
     n_rows: u64,
     block_number: Vec<u32>,
+    /// address of the erc20 token
     erc20: Vec<Vec<u8>>,
+    /// total supply of the token, null if the call failed
     total_supply: Vec<Option<U256>>,
     chain_id: Vec<u64>,
 }
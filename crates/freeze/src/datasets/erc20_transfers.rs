@@ -11,9 +11,13 @@ pub struct Erc20Transfers {
     transaction_index: Vec<u32>,
     log_index: Vec<u32>,
     transaction_hash: Vec<Vec<u8>>,
+    /// address of the erc20 token
     erc20: Vec<Vec<u8>>,
+    /// address that sent the tokens
     from_address: Vec<Vec<u8>>,
+    /// address that received the tokens
     to_address: Vec<Vec<u8>>,
+    /// amount of tokens transferred
     value: Vec<U256>,
     chain_id: Vec<u64>,
 }
@@ -46,9 +50,9 @@ impl CollectByBlock for Erc20Transfers {
             topics[2] = Some(ValueOrArray::Value(Some(H256::from_slice(&v[..]))));
         }
         let filter = Filter { topics, ..request.ethers_log_filter()? };
-        let logs = source.fetcher.get_logs(&filter).await?;
+        let logs = source.fetcher.get_logs_chunked(&filter, source.inner_request_size).await?;
 
-        Ok(logs.into_iter().filter(|x| x.topics.len() == 3 && x.data.len() == 32).collect())
+        Ok(logs.into_iter().filter(is_erc20_transfer).collect())
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
@@ -72,6 +76,9 @@ impl CollectByTransaction for Erc20Transfers {
     }
 }
 
+/// erc20 and erc721 Transfer events share topic0, erc20 transfers index from and to and put
+/// value in data (3 topics, 32 bytes of data) while erc721 transfers also index the token id
+/// (4 topics, no data), see erc721_transfers
 fn is_erc20_transfer(log: &Log) -> bool {
     log.topics.len() == 3 && log.data.len() == 32 && log.topics[0] == *EVENT_ERC20_TRANSFER
 }
@@ -95,3 +102,23 @@ fn process_erc20_transfers(logs: Vec<Log>, columns: &mut Erc20Transfers, schema:
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer_log(n_topics: usize, data_len: usize) -> Log {
+        let mut topics = vec![*EVENT_ERC20_TRANSFER];
+        topics.extend((1..n_topics).map(|i| H256::from_low_u64_be(i as u64)));
+        Log { topics, data: vec![1; data_len].into(), ..Default::default() }
+    }
+
+    #[test]
+    fn distinguishes_erc20_from_erc721_transfers() {
+        assert!(is_erc20_transfer(&transfer_log(3, 32)));
+        // erc721 indexes token id instead of putting value in data
+        assert!(!is_erc20_transfer(&transfer_log(4, 0)));
+        // non-standard tokens that index nothing are not decoded
+        assert!(!is_erc20_transfer(&transfer_log(1, 96)));
+    }
+}
@@ -1,5 +1,6 @@
-use super::erc20_metadata::remove_control_characters;
+use super::erc20_metadata::decode_string_output;
 use crate::*;
+use ethers::prelude::*;
 use polars::prelude::*;
 
 /// columns for transactions
@@ -8,8 +9,11 @@ use polars::prelude::*;
 pub struct Erc721Metadata {
     n_rows: u64,
     block_number: Vec<u32>,
+    /// address of the erc721 token
     erc721: Vec<Vec<u8>>,
+    /// name of the token, null if the call failed
     name: Vec<Option<String>>,
+    /// symbol of the token, null if the call failed
     symbol: Vec<Option<String>>,
     chain_id: Vec<u64>,
 }
@@ -26,6 +30,10 @@ impl Dataset for Erc721Metadata {
     fn arg_aliases() -> Option<std::collections::HashMap<Dim, Dim>> {
         Some([(Dim::Contract, Dim::Address)].into_iter().collect())
     }
+
+    fn estimate_requests(_params: &Params) -> usize {
+        2
+    }
 }
 
 #[async_trait::async_trait]
@@ -35,16 +43,29 @@ impl CollectByBlock for Erc721Metadata {
     async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
         let block_number = request.ethers_block_number()?;
         let address = request.ethers_address()?;
+        let calls =
+            [(address, FUNCTION_ERC20_NAME.clone()), (address, FUNCTION_ERC20_SYMBOL.clone())];
 
-        // name
-        let call_data = FUNCTION_ERC20_NAME.clone();
-        let output = source.fetcher.call2(address, call_data, block_number).await?;
-        let name = String::from_utf8(output.to_vec()).ok().map(|s| remove_control_characters(&s));
-
-        // symbol
-        let call_data = FUNCTION_ERC20_SYMBOL.clone();
-        let output = source.fetcher.call2(address, call_data, block_number).await?;
-        let symbol = String::from_utf8(output.to_vec()).ok().map(|s| remove_control_characters(&s));
+        // batch name and symbol into one request when Multicall3 is available, multicall can
+        // still fail at blocks before Multicall3 was deployed
+        let batched = if source.fetcher.has_multicall3().await? {
+            source.fetcher.multicall3(&calls, block_number).await.ok()
+        } else {
+            None
+        };
+        let outputs = match batched {
+            Some(outputs) => outputs,
+            None => {
+                let mut outputs = Vec::new();
+                for (address, call_data) in calls {
+                    outputs
+                        .push(Some(source.fetcher.call2(address, call_data, block_number).await?));
+                }
+                outputs
+            }
+        };
+        let decode = |output: &Option<Bytes>| output.as_ref().and_then(|x| decode_string_output(x));
+        let (name, symbol) = (decode(&outputs[0]), decode(&outputs[1]));
 
         Ok((request.block_number()? as u32, request.address()?, name, symbol))
     }
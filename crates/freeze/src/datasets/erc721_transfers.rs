@@ -11,9 +11,13 @@ pub struct Erc721Transfers {
     transaction_index: Vec<u32>,
     log_index: Vec<u32>,
     transaction_hash: Vec<Vec<u8>>,
-    erc20: Vec<Vec<u8>>,
+    /// address of the erc721 token
+    erc721: Vec<Vec<u8>>,
+    /// address that sent the token
     from_address: Vec<Vec<u8>>,
+    /// address that received the token
     to_address: Vec<Vec<u8>>,
+    /// id of the transferred token
     token_id: Vec<U256>,
     chain_id: Vec<u64>,
 }
@@ -47,9 +51,9 @@ impl CollectByBlock for Erc721Transfers {
             topics[2] = Some(ValueOrArray::Value(Some(H256::from_slice(&v[..]))));
         }
         let filter = Filter { topics, ..request.ethers_log_filter()? };
-        let logs = source.fetcher.get_logs(&filter).await?;
+        let logs = source.fetcher.get_logs_chunked(&filter, source.inner_request_size).await?;
 
-        Ok(logs.into_iter().filter(|x| x.topics.len() == 4 && x.data.len() == 0).collect())
+        Ok(logs.into_iter().filter(is_erc721_transfer).collect())
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
@@ -73,6 +77,9 @@ impl CollectByTransaction for Erc721Transfers {
     }
 }
 
+/// erc721 Transfer events index from, to, and token id (4 topics, no data), which tells them
+/// apart from erc20 transfers that share topic0 but put value in data. Tokens that predate
+/// erc721 and emit Transfer without indexed arguments (e.g. CryptoKitties) are not matched
 fn is_erc721_transfer(log: &Log) -> bool {
     log.topics.len() == 4 && log.data.len() == 0 && log.topics[0] == *EVENT_ERC721_TRANSFER
 }
@@ -92,7 +99,7 @@ fn process_erc721_transfers(
             store!(schema, columns, transaction_index, ti.as_u32());
             store!(schema, columns, log_index, li.as_u32());
             store!(schema, columns, transaction_hash, tx.as_bytes().to_vec());
-            store!(schema, columns, erc20, log.address.as_bytes().to_vec());
+            store!(schema, columns, erc721, log.address.as_bytes().to_vec());
             store!(schema, columns, from_address, log.topics[1].as_bytes()[12..].to_vec());
             store!(schema, columns, to_address, log.topics[2].as_bytes()[12..].to_vec());
             store!(schema, columns, token_id, log.topics[3].as_bytes().into());
@@ -100,3 +107,25 @@ fn process_erc721_transfers(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer_log(n_topics: usize, data_len: usize) -> Log {
+        let mut topics = vec![*EVENT_ERC721_TRANSFER];
+        topics.extend((1..n_topics).map(|i| H256::from_low_u64_be(i as u64)));
+        Log { topics, data: vec![1; data_len].into(), ..Default::default() }
+    }
+
+    #[test]
+    fn matches_only_fully_indexed_transfers() {
+        assert!(is_erc721_transfer(&transfer_log(4, 0)));
+        // erc20 transfers put value in data
+        assert!(!is_erc721_transfer(&transfer_log(3, 32)));
+        // non-standard tokens that emit from, to, and token id as data
+        assert!(!is_erc721_transfer(&transfer_log(1, 96)));
+        // non-standard tokens that index the token id but also emit data
+        assert!(!is_erc721_transfer(&transfer_log(4, 32)));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::*;
 use ethers::prelude::*;
+use ethers_core::abi::{Function, Token};
 use polars::prelude::*;
 
 /// columns for transactions
@@ -8,11 +9,20 @@ use polars::prelude::*;
 pub struct EthCalls {
     n_rows: u64,
     block_number: Vec<u32>,
+    /// address that was called
     contract_address: Vec<Vec<u8>>,
+    /// call data sent to the contract
     call_data: Vec<Vec<u8>>,
+    /// keccak hash of the call data
     call_data_hash: Vec<Vec<u8>>,
+    /// first 4 bytes of the call data
+    function_selector: Vec<Vec<u8>>,
+    /// data returned by the call
     output_data: Vec<Vec<u8>>,
+    /// keccak hash of the output data
     output_data_hash: Vec<Vec<u8>>,
+    /// output decoded with --function-signature, comma separated
+    decoded_output: Vec<Option<String>>,
     chain_id: Vec<u64>,
 }
 
@@ -58,7 +68,7 @@ impl CollectByBlock for EthCalls {
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
         let schema = query.schemas.get_schema(&Datatype::EthCalls)?;
-        process_eth_call(response, columns, schema);
+        process_eth_call(response, columns, schema, &query.function);
         Ok(())
     }
 }
@@ -67,13 +77,96 @@ impl CollectByTransaction for EthCalls {
     type Response = ();
 }
 
-fn process_eth_call(response: EthCallsResponse, columns: &mut EthCalls, schema: &Table) {
+fn process_eth_call(
+    response: EthCallsResponse,
+    columns: &mut EthCalls,
+    schema: &Table,
+    function: &Option<Function>,
+) {
     let (block_number, contract_address, call_data, output_data) = response;
+    let decoded_output = function.as_ref().and_then(|f| decode_function_output(f, &output_data));
     columns.n_rows += 1;
     store!(schema, columns, block_number, block_number);
     store!(schema, columns, contract_address, contract_address);
     store!(schema, columns, call_data, call_data.clone());
+    store!(schema, columns, function_selector, call_data.iter().take(4).copied().collect());
     store!(schema, columns, call_data_hash, ethers_core::utils::keccak256(call_data).into());
     store!(schema, columns, output_data, output_data.to_vec());
     store!(schema, columns, output_data_hash, ethers_core::utils::keccak256(output_data).into());
+    store!(schema, columns, decoded_output, decoded_output);
+}
+
+/// decode the output of a call, multiple return values are comma separated
+///
+/// returns None if the function declares no return types or the output does not decode
+pub(crate) fn decode_function_output(function: &Function, output: &[u8]) -> Option<String> {
+    if function.outputs.is_empty() {
+        return None
+    }
+    let tokens = function.decode_output(output).ok()?;
+    Some(tokens.iter().map(token_to_string).collect::<Vec<_>>().join(","))
+}
+
+fn token_to_string(token: &Token) -> String {
+    match token {
+        Token::Address(address) => format!("{:?}", address),
+        Token::Uint(value) => value.to_string(),
+        Token::Int(value) => I256::from_raw(*value).to_string(),
+        Token::Bool(value) => value.to_string(),
+        Token::String(value) => value.clone(),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => prefix_hex::encode(bytes.clone()),
+        Token::Array(tokens) | Token::FixedArray(tokens) => {
+            format!("[{}]", tokens.iter().map(token_to_string).collect::<Vec<_>>().join(","))
+        }
+        Token::Tuple(tokens) => {
+            format!("({})", tokens.iter().map(token_to_string).collect::<Vec<_>>().join(","))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_typed_output() {
+        let function =
+            parse_function_signature("getReserves() returns (uint112, int112, address)").unwrap();
+        let output = ethers::abi::encode(&[
+            Token::Uint(1000.into()),
+            Token::Int(I256::from(-5).into_raw()),
+            Token::Address(H160::repeat_byte(0xab)),
+        ]);
+        let decoded = decode_function_output(&function, &output).unwrap();
+        assert_eq!(decoded, format!("1000,-5,0x{}", "ab".repeat(20)));
+    }
+
+    #[test]
+    fn stores_selector_and_decoded_output() {
+        let columns = Some(vec!["function_selector".to_string(), "decoded_output".to_string()]);
+        let schema = Datatype::EthCalls
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &columns, None, None)
+            .unwrap();
+        let function = parse_function_signature("balanceOf(address) returns (uint256)").ok();
+        let call_data = function.as_ref().unwrap().short_signature().to_vec();
+        let output = ethers::abi::encode(&[Token::Uint(7.into())]);
+        let mut eth_calls = EthCalls::default();
+        process_eth_call(
+            (1, vec![0; 20], call_data.clone(), output),
+            &mut eth_calls,
+            &schema,
+            &function,
+        );
+
+        assert_eq!(eth_calls.function_selector, vec![call_data]);
+        assert_eq!(eth_calls.decoded_output, vec![Some("7".to_string())]);
+    }
+
+    #[test]
+    fn skips_undecodable_output() {
+        let function = parse_function_signature("balanceOf(address) returns (uint256)").unwrap();
+        assert_eq!(decode_function_output(&function, &[]), None);
+        let function = parse_function_signature("balanceOf(address)").unwrap();
+        assert_eq!(decode_function_output(&function, &[0; 32]), None);
+    }
 }
@@ -10,8 +10,11 @@ pub struct FourByteCounts {
     pub(crate) block_number: Vec<Option<u32>>,
     pub(crate) transaction_index: Vec<Option<u32>>,
     pub(crate) transaction_hash: Vec<Option<Vec<u8>>>,
+    /// 4 byte function selector that was called
     pub(crate) signature: Vec<Vec<u8>>,
+    /// size of the call data after the selector, in bytes
     pub(crate) size: Vec<u64>,
+    /// number of calls with the selector and size in the transaction
     pub(crate) count: Vec<u64>,
     pub(crate) chain_id: Vec<u64>,
 }
@@ -21,6 +24,10 @@ impl Dataset for FourByteCounts {
     fn aliases() -> Vec<&'static str> {
         vec!["4byte_counts"]
     }
+
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::DebugTrace]
+    }
 }
 
 type BlockTxsTraces = (Option<u32>, Vec<Option<Vec<u8>>>, Vec<BTreeMap<String, u64>>);
@@ -10,14 +10,21 @@ pub struct GethBalanceDiffs {
     pub(crate) block_number: Vec<Option<u32>>,
     pub(crate) transaction_index: Vec<Option<u64>>,
     pub(crate) transaction_hash: Vec<Option<Vec<u8>>>,
+    /// address whose balance changed
     pub(crate) address: Vec<Vec<u8>>,
+    /// balance before the transaction, in wei
     pub(crate) from_value: Vec<U256>,
+    /// balance after the transaction, in wei
     pub(crate) to_value: Vec<U256>,
     pub(crate) chain_id: Vec<u64>,
 }
 
 #[async_trait::async_trait]
-impl Dataset for GethBalanceDiffs {}
+impl Dataset for GethBalanceDiffs {
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::DebugTrace]
+    }
+}
 
 #[async_trait::async_trait]
 impl CollectByBlock for GethBalanceDiffs {
@@ -7,24 +7,38 @@ use polars::prelude::*;
 #[derive(Default)]
 pub struct GethCalls {
     n_rows: u64,
+    /// type of the call, such as CALL, DELEGATECALL or CREATE
     typ: Vec<String>,
+    /// address that made the call
     from_address: Vec<Vec<u8>>,
+    /// address that was called
     to_address: Vec<Option<Vec<u8>>>,
+    /// value sent with the call, in wei
     value: Vec<Option<U256>>,
+    /// gas given to the call
     gas: Vec<U256>,
+    /// gas used by the call
     gas_used: Vec<U256>,
+    /// call data of the call
     input: Vec<Vec<u8>>,
+    /// data returned by the call
     output: Vec<Option<Vec<u8>>>,
+    /// error message if the trace reverted
     error: Vec<Option<String>>,
     block_number: Vec<Option<u32>>,
     transaction_hash: Vec<Option<Vec<u8>>>,
     transaction_index: Vec<u32>,
+    /// position of the trace in the call tree, as indices joined by underscores
     trace_address: Vec<String>,
     chain_id: Vec<u64>,
 }
 
 #[async_trait::async_trait]
-impl Dataset for GethCalls {}
+impl Dataset for GethCalls {
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::DebugTrace]
+    }
+}
 
 #[async_trait::async_trait]
 impl CollectByBlock for GethCalls {
@@ -9,14 +9,21 @@ pub struct GethCodeDiffs {
     pub(crate) block_number: Vec<Option<u32>>,
     pub(crate) transaction_index: Vec<Option<u64>>,
     pub(crate) transaction_hash: Vec<Option<Vec<u8>>>,
+    /// address whose code changed
     pub(crate) address: Vec<Vec<u8>>,
+    /// code before the transaction
     pub(crate) from_value: Vec<Vec<u8>>,
+    /// code after the transaction
     pub(crate) to_value: Vec<Vec<u8>>,
     pub(crate) chain_id: Vec<u64>,
 }
 
 #[async_trait::async_trait]
-impl Dataset for GethCodeDiffs {}
+impl Dataset for GethCodeDiffs {
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::DebugTrace]
+    }
+}
 
 #[async_trait::async_trait]
 impl CollectByBlock for GethCodeDiffs {
@@ -10,14 +10,21 @@ pub struct GethNonceDiffs {
     pub(crate) block_number: Vec<Option<u32>>,
     pub(crate) transaction_index: Vec<Option<u64>>,
     pub(crate) transaction_hash: Vec<Option<Vec<u8>>>,
+    /// address whose nonce changed
     pub(crate) address: Vec<Vec<u8>>,
+    /// nonce before the transaction
     pub(crate) from_value: Vec<U256>,
+    /// nonce after the transaction
     pub(crate) to_value: Vec<U256>,
     pub(crate) chain_id: Vec<u64>,
 }
 
 #[async_trait::async_trait]
-impl Dataset for GethNonceDiffs {}
+impl Dataset for GethNonceDiffs {
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::DebugTrace]
+    }
+}
 
 #[async_trait::async_trait]
 impl CollectByBlock for GethNonceDiffs {
@@ -10,18 +10,30 @@ pub struct GethOpcodes {
     block_number: Vec<Option<u32>>,
     transaction_hash: Vec<Option<Vec<u8>>>,
     transaction_index: Vec<u32>,
+    /// position of the trace in the call tree, as indices joined by underscores
     trace_address: Vec<String>,
+    /// call depth at which the opcode ran
     depth: Vec<u64>,
+    /// error raised by the opcode
     error: Vec<Option<String>>,
+    /// gas remaining before the opcode
     gas: Vec<u64>,
+    /// gas cost of the opcode
     gas_cost: Vec<u64>,
+    /// name of the opcode
     op: Vec<String>,
+    /// program counter of the opcode
     pc: Vec<u64>,
+    /// gas refund accumulated before the opcode
     refund_counter: Vec<Option<u64>>,
 
+    /// memory before the opcode
     memory: Vec<Option<String>>,
+    /// stack before the opcode
     stack: Vec<Option<String>>,
+    /// storage slots touched so far by the call
     storage: Vec<Option<String>>,
+    /// data returned by the last call
     return_data: Vec<Option<Vec<u8>>>,
     chain_id: Vec<u64>,
 }
@@ -32,6 +44,10 @@ impl Dataset for GethOpcodes {
         let f = |x: &&str| x != &"memory" && x != &"stack" && x != &"storage";
         Some(GethOpcodes::column_types().into_keys().filter(f).collect())
     }
+
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::DebugTrace]
+    }
 }
 
 #[async_trait::async_trait]
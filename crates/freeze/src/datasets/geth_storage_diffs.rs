@@ -9,14 +9,22 @@ pub struct GethStorageDiffs {
     pub(crate) block_number: Vec<Option<u32>>,
     pub(crate) transaction_index: Vec<Option<u64>>,
     pub(crate) transaction_hash: Vec<Option<Vec<u8>>>,
+    /// address whose storage changed
     pub(crate) address: Vec<Vec<u8>>,
+    /// storage slot that changed
     pub(crate) slot: Vec<Vec<u8>>,
+    /// value of the slot before the transaction
     pub(crate) from_value: Vec<Vec<u8>>,
+    /// value of the slot after the transaction
     pub(crate) to_value: Vec<Vec<u8>>,
     pub(crate) chain_id: Vec<u64>,
 }
 
-impl Dataset for GethStorageDiffs {}
+impl Dataset for GethStorageDiffs {
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::DebugTrace]
+    }
+}
 
 #[async_trait::async_trait]
 impl CollectByBlock for GethStorageDiffs {
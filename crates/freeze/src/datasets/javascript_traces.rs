@@ -9,6 +9,7 @@ pub struct JavascriptTraces {
     pub(crate) block_number: Vec<Option<u32>>,
     pub(crate) transaction_index: Vec<Option<u32>>,
     pub(crate) transaction_hash: Vec<Option<Vec<u8>>>,
+    /// json output of the javascript tracer
     pub(crate) output: Vec<String>,
     pub(crate) chain_id: Vec<u64>,
 }
@@ -18,6 +19,10 @@ impl Dataset for JavascriptTraces {
     fn aliases() -> Vec<&'static str> {
         vec!["js_traces"]
     }
+
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::DebugTrace]
+    }
 }
 
 type BlockTxsTraces = (Option<u32>, Vec<Option<Vec<u8>>>, Vec<serde_json::Value>);
@@ -11,11 +11,17 @@ pub struct Logs {
     transaction_index: Vec<u32>,
     log_index: Vec<u32>,
     transaction_hash: Vec<Vec<u8>>,
+    /// address of the contract that emitted the log
     address: Vec<Vec<u8>>,
+    /// first topic of the log, the event signature hash for non-anonymous events
     topic0: Vec<Option<Vec<u8>>>,
+    /// second topic of the log
     topic1: Vec<Option<Vec<u8>>>,
+    /// third topic of the log
     topic2: Vec<Option<Vec<u8>>>,
+    /// fourth topic of the log
     topic3: Vec<Option<Vec<u8>>>,
+    /// non-indexed data of the log
     data: Vec<Vec<u8>>,
     event_cols: indexmap::IndexMap<String, Vec<ethers_core::abi::Token>>,
     chain_id: Vec<u64>,
@@ -28,7 +34,7 @@ impl Dataset for Logs {
     }
 
     fn optional_parameters() -> Vec<Dim> {
-        vec![Dim::Contract, Dim::Topic0, Dim::Topic1, Dim::Topic2, Dim::Topic3]
+        vec![Dim::Address, Dim::Contract, Dim::Topic0, Dim::Topic1, Dim::Topic2, Dim::Topic3]
     }
 
     fn use_block_ranges() -> bool {
@@ -40,8 +46,13 @@ impl Dataset for Logs {
 impl CollectByBlock for Logs {
     type Response = Vec<Log>;
 
-    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
-        source.fetcher.get_logs(&request.ethers_log_filter()?).await
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
+        let filter = request.ethers_log_filter()?;
+        if query.use_bloom_filter {
+            source.fetcher.get_logs_bloom_filtered(&filter, source.inner_request_size).await
+        } else {
+            source.fetcher.get_logs_chunked(&filter, source.inner_request_size).await
+        }
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
@@ -66,6 +77,12 @@ impl CollectByTransaction for Logs {
 
 /// process block into columns
 fn process_logs(logs: Vec<Log>, columns: &mut Logs, schema: &Table) -> R<()> {
+    // when decoding, skip logs that are not instances of the decoded event
+    let logs = match &schema.log_decoder {
+        Some(decoder) => logs.into_iter().filter(|log| decoder.matches(log)).collect(),
+        None => logs,
+    };
+
     for log in logs.iter() {
         if let (Some(bn), Some(tx), Some(ti), Some(li)) =
             (log.block_number, log.transaction_hash, log.transaction_index, log.log_index)
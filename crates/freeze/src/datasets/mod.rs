@@ -1,5 +1,9 @@
+/// access lists
+pub mod access_lists;
 /// address appearances
 pub mod address_appearances;
+/// address traces
+pub mod address_traces;
 /// balance diffs
 pub mod balance_diffs;
 /// balance reads
@@ -8,6 +12,8 @@ pub mod balance_reads;
 pub mod balances;
 /// blocks
 pub mod blocks;
+/// calls
+pub mod calls;
 /// code diffs
 pub mod code_diffs;
 /// code reads
@@ -56,6 +62,10 @@ pub mod nonce_diffs;
 pub mod nonce_reads;
 /// nonces
 pub mod nonces;
+/// opcode reads
+pub mod opcode_reads;
+/// pending transactions
+pub mod pending_transactions;
 /// slots
 pub mod slots;
 /// storage diffs
@@ -66,16 +76,25 @@ pub mod storage_reads;
 pub mod trace_calls;
 /// traces
 pub mod traces;
+/// transaction receipts
+pub mod transaction_receipts;
 /// transactions
 pub mod transactions;
+/// uncles
+pub mod uncles;
 /// vm traces
 pub mod vm_traces;
+/// withdrawals
+pub mod withdrawals;
 
+pub use access_lists::*;
 pub use address_appearances::*;
+pub use address_traces::*;
 pub use balance_diffs::*;
 pub use balance_reads::*;
 pub use balances::*;
 pub use blocks::*;
+pub use calls::*;
 pub use code_diffs::*;
 pub use code_reads::*;
 pub use codes::*;
@@ -100,10 +119,15 @@ pub use native_transfers::*;
 pub use nonce_diffs::*;
 pub use nonce_reads::*;
 pub use nonces::*;
+pub use opcode_reads::*;
+pub use pending_transactions::*;
 pub use slots::*;
 pub use storage_diffs::*;
 pub use storage_reads::*;
 pub use trace_calls::*;
 pub use traces::*;
+pub use transaction_receipts::*;
 pub use transactions::*;
+pub use uncles::*;
 pub use vm_traces::*;
+pub use withdrawals::*;
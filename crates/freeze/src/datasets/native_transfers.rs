@@ -9,10 +9,16 @@ pub struct NativeTransfers {
     n_rows: u64,
     block_number: Vec<u32>,
     transaction_index: Vec<Option<u32>>,
+    /// index of the transfer within its block
     transfer_index: Vec<u32>,
     transaction_hash: Vec<Option<Vec<u8>>>,
+    /// position of the trace in the call tree, as indices joined by underscores
+    trace_address: Vec<String>,
+    /// address that sent the value
     from_address: Vec<Vec<u8>>,
+    /// address that received the value
     to_address: Vec<Vec<u8>>,
+    /// value transferred, in wei
     value: Vec<U256>,
     chain_id: Vec<u64>,
 }
@@ -22,48 +28,69 @@ impl Dataset for NativeTransfers {
     fn optional_parameters() -> Vec<Dim> {
         vec![Dim::FromAddress, Dim::ToAddress]
     }
+
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::Trace]
+    }
 }
 
 #[async_trait::async_trait]
 impl CollectByBlock for NativeTransfers {
-    type Response = Vec<Trace>;
+    type Response = (Vec<Trace>, Option<Vec<u8>>, Option<Vec<u8>>);
 
-    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
         let traces = source.fetcher.trace_block(request.block_number()?.into()).await?;
-        Ok(filter_traces_by_from_to_addresses(traces, &request.from_address, &request.to_address))
+        let traces = query.trace_status.filter(traces);
+        Ok((traces, request.from_address, request.to_address))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
-        let traces =
-            if query.exclude_failed { traces::filter_failed_traces(response) } else { response };
-        process_native_transfers(&traces, columns, &query.schemas)
+        let (traces, from_address, to_address) = response;
+        let addresses = (&from_address, &to_address);
+        process_native_transfers(traces, addresses, &query.trace_addresses, columns, &query.schemas)
     }
 }
 
 #[async_trait::async_trait]
 impl CollectByTransaction for NativeTransfers {
-    type Response = Vec<Trace>;
+    type Response = (Vec<Trace>, Option<Vec<u8>>, Option<Vec<u8>>);
 
-    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
         let traces = source.fetcher.trace_transaction(request.ethers_transaction_hash()?).await?;
-        Ok(filter_traces_by_from_to_addresses(traces, &request.from_address, &request.to_address))
+        let traces = query.trace_status.filter(traces);
+        Ok((traces, request.from_address, request.to_address))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
-        let traces =
-            if query.exclude_failed { traces::filter_failed_traces(response) } else { response };
-        process_native_transfers(&traces, columns, &query.schemas)
+        let (traces, from_address, to_address) = response;
+        let addresses = (&from_address, &to_address);
+        process_native_transfers(traces, addresses, &query.trace_addresses, columns, &query.schemas)
     }
 }
 
-/// process block into columns
+/// process block into columns, skipping traces that do not move value
 pub(crate) fn process_native_transfers(
-    traces: &[Trace],
+    traces: Vec<Trace>,
+    (from_address, to_address): (&Option<Vec<u8>>, &Option<Vec<u8>>),
+    trace_addresses: &TraceAddressFilter,
     columns: &mut NativeTransfers,
     schemas: &Schemas,
 ) -> R<()> {
     let schema = schemas.get(&Datatype::NativeTransfers).ok_or(err("schema not provided"))?;
-    for (transfer_index, trace) in traces.iter().enumerate() {
+    // value moved by reverted traces and their subtraces is returned to the sender. they are
+    // dropped before the address filters can drop the trace that reverted
+    let traces = traces::filter_failed_traces(traces);
+    let traces = filter_traces_by_from_to_addresses(traces, from_address, to_address);
+    let traces = trace_addresses.filter(traces);
+    let transfers = traces.iter().filter_map(|trace| {
+        let (from, to, value) = transfer_of(trace);
+        if value.is_zero() {
+            None
+        } else {
+            Some((trace, from, to, value))
+        }
+    });
+    for (transfer_index, (trace, from, to, value)) in transfers.enumerate() {
         columns.n_rows += 1;
         store!(schema, columns, block_number, trace.block_number as u32);
         store!(schema, columns, transaction_index, trace.transaction_position.map(|x| x as u32));
@@ -74,34 +101,145 @@ pub(crate) fn process_native_transfers(
             transaction_hash,
             trace.transaction_hash.map(|x| x.as_bytes().to_vec())
         );
+        store!(
+            schema,
+            columns,
+            trace_address,
+            trace.trace_address.iter().map(|n| n.to_string()).collect::<Vec<String>>().join("_")
+        );
+        store!(schema, columns, from_address, from);
+        store!(schema, columns, to_address, to);
+        store!(schema, columns, value, value);
+    }
+    Ok(())
+}
 
-        match &trace.action {
-            Action::Call(action) => {
-                store!(schema, columns, from_address, action.from.as_bytes().to_vec());
-                store!(schema, columns, to_address, action.to.as_bytes().to_vec());
-                store!(schema, columns, value, action.value);
-            }
-            Action::Create(action) => {
-                store!(schema, columns, from_address, action.from.as_bytes().to_vec());
-                match &trace.result.as_ref() {
-                    Some(Res::Create(res)) => {
-                        store!(schema, columns, to_address, res.address.0.into())
-                    }
-                    _ => store!(schema, columns, to_address, vec![0; 32]),
-                }
-                store!(schema, columns, value, action.value);
-            }
-            Action::Suicide(action) => {
-                store!(schema, columns, from_address, action.address.as_bytes().to_vec());
-                store!(schema, columns, to_address, action.refund_address.as_bytes().to_vec());
-                store!(schema, columns, value, action.balance);
-            }
-            Action::Reward(action) => {
-                store!(schema, columns, from_address, vec![0; 20]);
-                store!(schema, columns, to_address, action.author.as_bytes().to_vec());
-                store!(schema, columns, value, action.value);
+/// sender, recipient, and value moved by a trace
+fn transfer_of(trace: &Trace) -> (Vec<u8>, Vec<u8>, U256) {
+    match &trace.action {
+        // the value of delegatecall and callcode is the caller's msg.value, which is not moved
+        Action::Call(action)
+            if matches!(action.call_type, CallType::DelegateCall | CallType::CallCode) =>
+        {
+            (action.from.as_bytes().to_vec(), action.to.as_bytes().to_vec(), U256::zero())
+        }
+        Action::Call(action) => {
+            (action.from.as_bytes().to_vec(), action.to.as_bytes().to_vec(), action.value)
+        }
+        Action::Create(action) => {
+            let to = match &trace.result {
+                Some(Res::Create(res)) => res.address.as_bytes().to_vec(),
+                _ => vec![0; 20],
+            };
+            (action.from.as_bytes().to_vec(), to, action.value)
+        }
+        Action::Suicide(action) => (
+            action.address.as_bytes().to_vec(),
+            action.refund_address.as_bytes().to_vec(),
+            action.balance,
+        ),
+        Action::Reward(action) => (vec![0; 20], action.author.as_bytes().to_vec(), action.value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn call_trace(value: u64, trace_address: Vec<usize>) -> Trace {
+        Trace {
+            action: Action::Call(ethers::types::Call {
+                from: H160::from_low_u64_be(1),
+                to: H160::from_low_u64_be(2),
+                value: value.into(),
+                ..Default::default()
+            }),
+            result: None,
+            trace_address,
+            subtraces: 0,
+            transaction_position: Some(0),
+            transaction_hash: Some(H256::zero()),
+            block_number: 1,
+            block_hash: H256::zero(),
+            action_type: ActionType::Call,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn skips_zero_value_traces() {
+        let schema = Datatype::NativeTransfers
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &None, None, None)
+            .unwrap();
+        let schemas = HashMap::from([(Datatype::NativeTransfers, schema)]);
+        let traces = vec![call_trace(5, vec![]), call_trace(0, vec![0]), call_trace(7, vec![1, 0])];
+        let mut columns = NativeTransfers::default();
+        let no_filter = TraceAddressFilter::default();
+        process_native_transfers(traces, (&None, &None), &no_filter, &mut columns, &schemas)
+            .unwrap();
+
+        assert_eq!(columns.n_rows, 2);
+        assert_eq!(columns.value, vec![U256::from(5), U256::from(7)]);
+        assert_eq!(columns.trace_address, vec!["", "1_0"]);
+        assert_eq!(columns.transfer_index, vec![0, 1]);
+    }
+
+    #[test]
+    fn skips_delegated_calls_and_reverted_traces() {
+        let schema = Datatype::NativeTransfers
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &None, None, None)
+            .unwrap();
+        let schemas = HashMap::from([(Datatype::NativeTransfers, schema)]);
+        let with_call_type = |call_type: CallType, trace_address: Vec<usize>| {
+            let mut trace = call_trace(5, trace_address);
+            if let Action::Call(action) = &mut trace.action {
+                action.call_type = call_type;
             }
+            trace
+        };
+        let reverted = Trace { error: Some("Reverted".to_string()), ..call_trace(3, vec![2]) };
+        let traces = vec![
+            call_trace(1, vec![]),
+            with_call_type(CallType::DelegateCall, vec![0]),
+            with_call_type(CallType::CallCode, vec![1]),
+            reverted,
+            call_trace(4, vec![2, 0]),
+            call_trace(6, vec![3]),
+        ];
+        let mut columns = NativeTransfers::default();
+        let no_filter = TraceAddressFilter::default();
+        process_native_transfers(traces, (&None, &None), &no_filter, &mut columns, &schemas)
+            .unwrap();
+
+        assert_eq!(columns.value, vec![U256::from(1), U256::from(6)]);
+        assert_eq!(columns.trace_address, vec!["", "3"]);
+    }
+
+    #[test]
+    fn address_filters_keep_subtraces_of_reverted_traces_dropped() {
+        let schema = Datatype::NativeTransfers
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &None, None, None)
+            .unwrap();
+        let schemas = HashMap::from([(Datatype::NativeTransfers, schema)]);
+        // the reverted call is sent from another address than its value moving subtrace
+        let mut reverted = Trace { error: Some("Reverted".to_string()), ..call_trace(3, vec![]) };
+        if let Action::Call(action) = &mut reverted.action {
+            action.from = H160::from_low_u64_be(9);
         }
+        let traces = vec![reverted, call_trace(4, vec![0])];
+        let from_address = Some(H160::from_low_u64_be(1).as_bytes().to_vec());
+        let no_filter = TraceAddressFilter::default();
+        let mut columns = NativeTransfers::default();
+        process_native_transfers(
+            traces,
+            (&from_address, &None),
+            &no_filter,
+            &mut columns,
+            &schemas,
+        )
+        .unwrap();
+
+        assert_eq!(columns.n_rows, 0);
     }
-    Ok(())
 }
@@ -10,14 +10,21 @@ pub struct NonceDiffs {
     pub(crate) block_number: Vec<Option<u32>>,
     pub(crate) transaction_index: Vec<Option<u32>>,
     pub(crate) transaction_hash: Vec<Option<Vec<u8>>>,
+    /// address whose nonce changed
     pub(crate) address: Vec<Vec<u8>>,
+    /// nonce before the transaction
     pub(crate) from_value: Vec<u64>,
+    /// nonce after the transaction
     pub(crate) to_value: Vec<u64>,
     pub(crate) chain_id: Vec<u64>,
 }
 
 #[async_trait::async_trait]
-impl Dataset for NonceDiffs {}
+impl Dataset for NonceDiffs {
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::TraceReplay, Capability::DebugTrace]
+    }
+}
 
 type BlockTxsTraces = (Option<u32>, Vec<Option<Vec<u8>>>, Vec<ethers::types::BlockTrace>);
 
@@ -28,7 +35,7 @@ impl CollectByBlock for NonceDiffs {
     async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
         let schema = query.schemas.get_schema(&Datatype::NonceDiffs)?;
         let include_txs = schema.has_column("transaction_hash");
-        source.fetcher.trace_block_state_diffs(request.block_number()? as u32, include_txs).await
+        source.fetcher.block_state_diffs(request.block_number()? as u32, include_txs).await
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
@@ -41,7 +48,7 @@ impl CollectByTransaction for NonceDiffs {
     type Response = BlockTxsTraces;
 
     async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
-        source.fetcher.trace_transaction_state_diffs(request.transaction_hash()?).await
+        source.fetcher.transaction_state_diffs(request.transaction_hash()?).await
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
@@ -75,11 +82,9 @@ pub(crate) fn process_nonce_diff(
     columns: &mut NonceDiffs,
     schema: &Table,
 ) {
-    let (from, to) = match diff {
-        Diff::Same => return,
-        Diff::Born(value) => (U256::zero(), *value),
-        Diff::Died(value) => (*value, U256::zero()),
-        Diff::Changed(ChangedType { from, to }) => (*from, *to),
+    let (from, to) = match diff_from_to(diff) {
+        (None, None) => return,
+        (from, to) => from_to_or_default(from, to),
     };
     columns.n_rows += 1;
     store!(schema, columns, block_number, *block_number);
@@ -11,13 +11,19 @@ pub struct NonceReads {
     pub(crate) block_number: Vec<Option<u32>>,
     pub(crate) transaction_index: Vec<Option<u32>>,
     pub(crate) transaction_hash: Vec<Option<Vec<u8>>>,
+    /// address whose nonce is read
     pub(crate) address: Vec<Vec<u8>>,
+    /// nonce read by the transaction
     pub(crate) nonce: Vec<u64>,
     pub(crate) chain_id: Vec<u64>,
 }
 
 #[async_trait::async_trait]
-impl Dataset for NonceReads {}
+impl Dataset for NonceReads {
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::DebugTrace]
+    }
+}
 
 type BlockTxsTraces = (Option<u32>, Vec<Option<Vec<u8>>>, Vec<BTreeMap<H160, AccountState>>);
 
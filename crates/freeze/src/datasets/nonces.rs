@@ -8,7 +8,9 @@ use polars::prelude::*;
 pub struct Nonces {
     n_rows: usize,
     block_number: Vec<u32>,
+    /// address whose nonce is queried
     address: Vec<Vec<u8>>,
+    /// nonce of the address at the end of the block
     nonce: Vec<u64>,
     chain_id: Vec<u64>,
 }
@@ -0,0 +1,260 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// columns for storage and balance reads of executed opcodes
+#[cryo_to_df::to_df(Datatype::OpcodeReads)]
+#[derive(Default)]
+pub struct OpcodeReads {
+    n_rows: usize,
+    block_number: Vec<Option<u32>>,
+    transaction_hash: Vec<Option<Vec<u8>>>,
+    transaction_index: Vec<u32>,
+    /// opcode that read the value, SLOAD or BALANCE
+    op: Vec<String>,
+    /// address of the contract that ran the opcode
+    address: Vec<Option<Vec<u8>>>,
+    /// slot read by SLOAD or address read by BALANCE
+    slot_or_target: Vec<Vec<u8>>,
+    /// value returned by the opcode
+    value: Vec<Vec<u8>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for OpcodeReads {
+    fn default_sort() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "transaction_index"])
+    }
+
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::TraceReplay]
+    }
+}
+
+type BlockTxTraces = (Option<u32>, Option<Vec<u8>>, Vec<BlockTrace>);
+
+#[async_trait::async_trait]
+impl CollectByBlock for OpcodeReads {
+    type Response = BlockTxTraces;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let block_number = request.block_number()? as u32;
+        let trace_types = vec![TraceType::Trace, TraceType::VmTrace];
+        let traces = source
+            .fetcher
+            .trace_replay_block_transactions(block_number.into(), trace_types)
+            .await?;
+        Ok((Some(block_number), None, traces))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::OpcodeReads)?;
+        process_opcode_reads(response, columns, schema)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for OpcodeReads {
+    type Response = BlockTxTraces;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let tx_hash = request.transaction_hash()?;
+        let trace_types = vec![TraceType::Trace, TraceType::VmTrace];
+        let traces = source
+            .fetcher
+            .trace_replay_transaction(H256::from_slice(&tx_hash), trace_types)
+            .await?;
+        Ok((None, Some(tx_hash), vec![traces]))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::OpcodeReads)?;
+        process_opcode_reads(response, columns, schema)
+    }
+}
+
+fn process_opcode_reads(
+    response: BlockTxTraces,
+    columns: &mut OpcodeReads,
+    schema: &Table,
+) -> R<()> {
+    let (block_number, tx_hash, block_traces) = response;
+    for (tx_pos, block_trace) in block_traces.into_iter().enumerate() {
+        let address = block_trace.trace.as_deref().and_then(root_address);
+        if let Some(vm_trace) = block_trace.vm_trace {
+            let tx = (block_number, tx_hash.clone(), tx_pos as u32);
+            add_reads(vm_trace, address, &tx, columns, schema);
+        }
+    }
+    Ok(())
+}
+
+/// address of the contract executed by the top-level call of a transaction
+fn root_address(traces: &[TransactionTrace]) -> Option<H160> {
+    let root = traces.iter().find(|trace| trace.trace_address.is_empty())?;
+    match (&root.action, &root.result) {
+        (Action::Call(call), _) => Some(call.to),
+        (Action::Create(_), Some(Res::Create(result))) => Some(result.address),
+        _ => None,
+    }
+}
+
+/// record SLOAD and BALANCE reads of a vm trace, replaying stack pushes to recover the operands
+/// that the trace does not report
+fn add_reads(
+    vm_trace: VMTrace,
+    address: Option<H160>,
+    tx: &(Option<u32>, Option<Vec<u8>>, u32),
+    columns: &mut OpcodeReads,
+    schema: &Table,
+) {
+    let mut stack: Vec<U256> = Vec::new();
+    for operation in vm_trace.ops {
+        let opcode = match operation.op {
+            ExecutedInstruction::Known(opcode) => Some(opcode),
+            ExecutedInstruction::Unknown(_) => None,
+        };
+        let n_inputs = opcode.map(stack_inputs).unwrap_or(0);
+        // inputs are ordered from bottom to top of stack
+        let inputs = stack.split_off(stack.len().saturating_sub(n_inputs));
+        let output = operation.ex.as_ref().and_then(|ex| ex.push.last());
+
+        if let (Some(opcode @ (Opcode::SLOAD | Opcode::BALANCE)), Some(input), Some(output)) =
+            (opcode, inputs.last(), output)
+        {
+            let slot_or_target = match opcode {
+                Opcode::BALANCE => u256_to_address(input).as_bytes().to_vec(),
+                _ => input.to_vec_u8(),
+            };
+            let (block_number, tx_hash, tx_pos) = tx;
+            columns.n_rows += 1;
+            store!(schema, columns, block_number, *block_number);
+            store!(schema, columns, transaction_hash, tx_hash.clone());
+            store!(schema, columns, transaction_index, *tx_pos);
+            store!(schema, columns, op, opcode.to_string());
+            store!(schema, columns, address, address.map(|x| x.as_bytes().to_vec()));
+            store!(schema, columns, slot_or_target, slot_or_target);
+            store!(schema, columns, value, output.to_vec_u8());
+        }
+
+        if let Some(sub) = operation.sub {
+            let sub_address = match opcode {
+                Some(Opcode::CALL | Opcode::STATICCALL) => {
+                    inputs.len().checked_sub(2).map(|i| u256_to_address(&inputs[i]))
+                }
+                Some(Opcode::CALLCODE | Opcode::DELEGATECALL) => address,
+                Some(Opcode::CREATE | Opcode::CREATE2) => output.map(u256_to_address),
+                _ => None,
+            };
+            add_reads(sub, sub_address, tx, columns, schema);
+        }
+
+        if let Some(ex) = operation.ex {
+            stack.extend(ex.push);
+        }
+    }
+}
+
+fn u256_to_address(value: &U256) -> H160 {
+    H160::from_slice(&value.to_vec_u8()[12..])
+}
+
+/// number of stack items consumed by an opcode
+///
+/// traces report DUPn and SWAPn as rewriting every stack item they touch, so these ops consume
+/// all of the items that they push back
+fn stack_inputs(opcode: Opcode) -> usize {
+    match opcode as u8 {
+        0x01..=0x07 | 0x0a | 0x0b | 0x10..=0x14 | 0x16..=0x18 | 0x1a..=0x1d | 0x20 => 2,
+        0x08 | 0x09 => 3,
+        0x15 | 0x19 => 1,
+        0x31 | 0x35 | 0x3b | 0x3f | 0x40 | 0x49 => 1,
+        0x37 | 0x39 | 0x3e => 3,
+        0x3c => 4,
+        0x50 | 0x51 | 0x54 | 0x56 | 0x5c => 1,
+        0x52 | 0x53 | 0x55 | 0x57 | 0x5d => 2,
+        0x5e => 3,
+        op @ 0x80..=0x8f => (op - 0x7f) as usize,
+        op @ 0x90..=0x9f => (op - 0x8f) as usize + 1,
+        op @ 0xa0..=0xa4 => (op - 0xa0) as usize + 2,
+        0xf0 => 3,
+        0xf1 | 0xf2 => 7,
+        0xf3 | 0xfd => 2,
+        0xf4 | 0xfa => 6,
+        0xf5 => 4,
+        0xff => 1,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn operation(opcode: Opcode, push: Vec<u64>, sub: Option<VMTrace>) -> VMOperation {
+        let push = push.into_iter().map(U256::from).collect();
+        VMOperation {
+            pc: 0,
+            cost: 0,
+            ex: Some(VMExecutedOperation { used: 0, push, mem: None, store: None }),
+            sub,
+            op: ExecutedInstruction::Known(opcode),
+        }
+    }
+
+    #[test]
+    fn recovers_read_operands_from_stack() {
+        let callee = 0xbeef;
+        let sub = VMTrace {
+            code: Bytes::default(),
+            ops: vec![
+                operation(Opcode::PUSH1, vec![7], None),
+                operation(Opcode::SLOAD, vec![8], None),
+            ],
+        };
+        let mut ops = vec![
+            operation(Opcode::PUSH1, vec![1], None),
+            operation(Opcode::DUP1, vec![1, 1], None),
+            operation(Opcode::ADD, vec![2], None),
+            operation(Opcode::SLOAD, vec![42], None),
+            operation(Opcode::PUSH20, vec![0xabc], None),
+            operation(Opcode::BALANCE, vec![100], None),
+        ];
+        // CALL consumes gas, address, value, args offset, args size, ret offset, ret size
+        for value in [0, 0, 0, 0, 0, callee, 50_000] {
+            ops.push(operation(Opcode::PUSH1, vec![value], None));
+        }
+        ops.push(operation(Opcode::CALL, vec![1], Some(sub)));
+
+        let columns = Some(vec![
+            "op".to_string(),
+            "address".to_string(),
+            "slot_or_target".to_string(),
+            "value".to_string(),
+        ]);
+        let schema = Datatype::OpcodeReads
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &columns, None, None)
+            .unwrap();
+        let mut reads = OpcodeReads::default();
+        let root = H160::from_low_u64_be(0xaaaa);
+        add_reads(
+            VMTrace { code: Bytes::default(), ops },
+            Some(root),
+            &(Some(1), None, 0),
+            &mut reads,
+            &schema,
+        );
+
+        assert_eq!(reads.op, vec!["SLOAD", "BALANCE", "SLOAD"]);
+        let addresses: Vec<_> = vec![root, root, H160::from_low_u64_be(callee)]
+            .into_iter()
+            .map(|x| Some(x.as_bytes().to_vec()))
+            .collect();
+        assert_eq!(reads.address, addresses);
+        assert_eq!(reads.slot_or_target[0], U256::from(2).to_vec_u8());
+        assert_eq!(reads.slot_or_target[1], H160::from_low_u64_be(0xabc).as_bytes().to_vec());
+        assert_eq!(reads.slot_or_target[2], U256::from(7).to_vec_u8());
+        assert_eq!(reads.value[0], U256::from(42).to_vec_u8());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// columns for pending transactions
+///
+/// pending transactions are a point-in-time snapshot of the node's mempool, collecting them twice
+/// gives different results. block numbers given to the query are ignored
+#[cryo_to_df::to_df(Datatype::PendingTransactions)]
+#[derive(Default)]
+pub struct PendingTransactions {
+    n_rows: u64,
+    /// unix timestamp at which the mempool was read
+    snapshot_timestamp: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    /// nonce of the sender
+    nonce: Vec<u64>,
+    /// address that sent the transaction
+    from_address: Vec<Vec<u8>>,
+    /// address that receives the transaction, null for contract creation
+    to_address: Vec<Option<Vec<u8>>>,
+    /// value sent with the transaction, in wei
+    value: Vec<U256>,
+    /// call data of the transaction
+    input: Vec<Vec<u8>>,
+    /// maximum gas the transaction can use
+    gas_limit: Vec<u64>,
+    /// gas price of the transaction
+    gas_price: Vec<Option<u64>>,
+    /// type of the transaction
+    transaction_type: Vec<Option<u32>>,
+    /// maximum tip per unit of gas, for eip-1559 transactions
+    max_priority_fee_per_gas: Vec<Option<u64>>,
+    /// maximum fee per unit of gas, for eip-1559 transactions
+    max_fee_per_gas: Vec<Option<u64>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for PendingTransactions {
+    fn aliases() -> Vec<&'static str> {
+        vec!["mempool"]
+    }
+
+    fn default_blocks() -> Option<String> {
+        Some("latest".to_string())
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for PendingTransactions {
+    type Response = (u32, Vec<Transaction>);
+
+    async fn extract(_: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let snapshot_timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|_| err("system clock is before unix epoch"))?
+            .as_secs() as u32;
+        let transactions = source
+            .fetcher
+            .get_pending_block()
+            .await?
+            .map(|block| block.transactions)
+            .unwrap_or_default();
+        Ok((snapshot_timestamp, transactions))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::PendingTransactions)?;
+        let (snapshot_timestamp, transactions) = response;
+        for tx in transactions.into_iter() {
+            columns.n_rows += 1;
+            store!(schema, columns, snapshot_timestamp, snapshot_timestamp);
+            store!(schema, columns, transaction_hash, tx.hash.as_bytes().to_vec());
+            store!(schema, columns, nonce, tx.nonce.as_u64());
+            store!(schema, columns, from_address, tx.from.as_bytes().to_vec());
+            store!(schema, columns, to_address, tx.to.map(|x| x.as_bytes().to_vec()));
+            store!(schema, columns, value, tx.value);
+            store!(schema, columns, input, tx.input.to_vec());
+            store!(schema, columns, gas_limit, tx.gas.as_u64());
+            store!(schema, columns, gas_price, tx.gas_price.map(|x| x.as_u64()));
+            store!(schema, columns, transaction_type, tx.transaction_type.map(|x| x.as_u32()));
+            store!(
+                schema,
+                columns,
+                max_priority_fee_per_gas,
+                tx.max_priority_fee_per_gas.map(|x| x.as_u64())
+            );
+            store!(schema, columns, max_fee_per_gas, tx.max_fee_per_gas.map(|x| x.as_u64()));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for PendingTransactions {
+    type Response = ();
+}
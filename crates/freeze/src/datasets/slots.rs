@@ -8,8 +8,11 @@ use polars::prelude::*;
 pub struct Slots {
     n_rows: usize,
     block_number: Vec<u32>,
+    /// address whose storage is queried
     address: Vec<Vec<u8>>,
+    /// storage slot that is queried
     slot: Vec<Vec<u8>>,
+    /// value of the slot at the end of the block
     value: Vec<Vec<u8>>,
     chain_id: Vec<u64>,
 }
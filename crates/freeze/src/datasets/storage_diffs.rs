@@ -10,9 +10,13 @@ pub struct StorageDiffs {
     pub(crate) block_number: Vec<Option<u32>>,
     pub(crate) transaction_index: Vec<Option<u32>>,
     pub(crate) transaction_hash: Vec<Option<Vec<u8>>>,
+    /// address whose storage changed
     pub(crate) address: Vec<Vec<u8>>,
+    /// storage slot that changed
     pub(crate) slot: Vec<Vec<u8>>,
+    /// value of the slot before the transaction
     pub(crate) from_value: Vec<Vec<u8>>,
+    /// value of the slot after the transaction
     pub(crate) to_value: Vec<Vec<u8>>,
     pub(crate) chain_id: Vec<u64>,
 }
@@ -22,6 +26,10 @@ impl Dataset for StorageDiffs {
     fn aliases() -> Vec<&'static str> {
         vec!["slot_diffs"]
     }
+
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::TraceReplay, Capability::DebugTrace]
+    }
 }
 
 type BlockTxsTraces = (Option<u32>, Vec<Option<Vec<u8>>>, Vec<ethers::types::BlockTrace>);
@@ -33,7 +41,7 @@ impl CollectByBlock for StorageDiffs {
     async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
         let schema = query.schemas.get_schema(&Datatype::StorageDiffs)?;
         let include_txs = schema.has_column("transaction_hash");
-        source.fetcher.trace_block_state_diffs(request.block_number()? as u32, include_txs).await
+        source.fetcher.block_state_diffs(request.block_number()? as u32, include_txs).await
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
@@ -46,7 +54,7 @@ impl CollectByTransaction for StorageDiffs {
     type Response = BlockTxsTraces;
 
     async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
-        source.fetcher.trace_transaction_state_diffs(request.transaction_hash()?).await
+        source.fetcher.transaction_state_diffs(request.transaction_hash()?).await
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
@@ -81,11 +89,9 @@ pub(crate) fn process_storage_diff(
     schema: &Table,
 ) {
     for (s, sub_diff) in diff.iter() {
-        let (from, to) = match sub_diff {
-            Diff::Same => continue,
-            Diff::Born(value) => (H256::zero(), *value),
-            Diff::Died(value) => (*value, H256::zero()),
-            Diff::Changed(ChangedType { from, to }) => (*from, *to),
+        let (from, to) = match diff_from_to(sub_diff) {
+            (None, None) => continue,
+            (from, to) => from_to_or_default(from, to),
         };
         columns.n_rows += 1;
         store!(schema, columns, block_number, *block_number);
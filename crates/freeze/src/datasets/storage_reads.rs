@@ -11,8 +11,11 @@ pub struct StorageReads {
     pub(crate) block_number: Vec<Option<u32>>,
     pub(crate) transaction_index: Vec<Option<u32>>,
     pub(crate) transaction_hash: Vec<Option<Vec<u8>>>,
+    /// address whose storage is read
     pub(crate) contract_address: Vec<Vec<u8>>,
+    /// storage slot read by the transaction
     pub(crate) slot: Vec<Vec<u8>>,
+    /// value of the slot that was read
     pub(crate) value: Vec<Vec<u8>>,
     pub(crate) chain_id: Vec<u64>,
 }
@@ -22,6 +25,10 @@ impl Dataset for StorageReads {
     fn aliases() -> Vec<&'static str> {
         vec!["slot_reads"]
     }
+
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::DebugTrace]
+    }
 }
 
 type BlockTxsTraces = (Option<u32>, Vec<Option<Vec<u8>>>, Vec<BTreeMap<H160, AccountState>>);
@@ -10,23 +10,41 @@ pub struct TraceCalls {
     n_rows: u64,
     block_number: Vec<u32>,
     transaction_index: Vec<u32>,
+    /// address that made the call, create or selfdestruct
     action_from: Vec<Option<Vec<u8>>>,
+    /// address that was called
     action_to: Vec<Option<Vec<u8>>>,
+    /// value sent by the action, in wei
     action_value: Vec<String>,
+    /// gas given to the action
     action_gas: Vec<Option<u32>>,
+    /// call data of a call
     action_input: Vec<Option<Vec<u8>>>,
+    /// type of a call, such as call, delegatecall or staticcall
     action_call_type: Vec<Option<String>>,
+    /// init code of a create
     action_init: Vec<Option<Vec<u8>>>,
+    /// type of a reward, block or uncle
     action_reward_type: Vec<Option<String>>,
+    /// type of the action: call, create, suicide or reward
     action_type: Vec<String>,
+    /// gas used by the action
     result_gas_used: Vec<Option<u32>>,
+    /// data returned by a call
     result_output: Vec<Option<Vec<u8>>>,
+    /// code of the contract created by a create
     result_code: Vec<Option<Vec<u8>>>,
+    /// address of the contract created by a create
     result_address: Vec<Option<Vec<u8>>>,
+    /// position of the trace in the call tree, as indices joined by underscores
     trace_address: Vec<String>,
+    /// number of traces called directly by the trace
     subtraces: Vec<u32>,
+    /// error message if the trace reverted
     error: Vec<Option<String>>,
+    /// address called by the traced call
     tx_to_address: Vec<Vec<u8>>,
+    /// call data of the traced call
     tx_call_data: Vec<Vec<u8>>,
     chain_id: Vec<u64>,
 }
@@ -43,6 +61,10 @@ impl Dataset for TraceCalls {
     fn arg_aliases() -> Option<std::collections::HashMap<Dim, Dim>> {
         Some([(Dim::Address, Dim::Contract), (Dim::ToAddress, Dim::Contract)].into_iter().collect())
     }
+
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::Trace]
+    }
 }
 
 #[async_trait::async_trait]
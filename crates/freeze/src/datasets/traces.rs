@@ -7,25 +7,41 @@ use polars::prelude::*;
 #[derive(Default)]
 pub struct Traces {
     n_rows: u64,
+    /// address that made the call, create or selfdestruct
     action_from: Vec<Option<Vec<u8>>>,
+    /// address that was called
     action_to: Vec<Option<Vec<u8>>>,
+    /// value sent by the action, in wei
     action_value: Vec<String>,
+    /// gas given to the action
     action_gas: Vec<Option<u32>>,
+    /// call data of a call
     action_input: Vec<Option<Vec<u8>>>,
+    /// type of a call, such as call, delegatecall or staticcall
     action_call_type: Vec<Option<String>>,
+    /// init code of a create
     action_init: Vec<Option<Vec<u8>>>,
+    /// type of a reward, block or uncle
     action_reward_type: Vec<Option<String>>,
+    /// type of the action: call, create, suicide or reward
     action_type: Vec<String>,
+    /// gas used by the action
     result_gas_used: Vec<Option<u32>>,
+    /// data returned by a call
     result_output: Vec<Option<Vec<u8>>>,
+    /// code of the contract created by a create
     result_code: Vec<Option<Vec<u8>>>,
+    /// address of the contract created by a create
     result_address: Vec<Option<Vec<u8>>>,
+    /// position of the trace in the call tree, as indices joined by underscores
     trace_address: Vec<String>,
+    /// number of traces called directly by the trace
     subtraces: Vec<u32>,
     transaction_index: Vec<Option<u32>>,
     transaction_hash: Vec<Option<Vec<u8>>>,
     block_number: Vec<u32>,
     block_hash: Vec<Vec<u8>>,
+    /// error message if the trace reverted
     error: Vec<Option<String>>,
     chain_id: Vec<u64>,
 }
@@ -35,20 +51,25 @@ impl Dataset for Traces {
     fn optional_parameters() -> Vec<Dim> {
         vec![Dim::FromAddress, Dim::ToAddress]
     }
+
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::Trace]
+    }
 }
 
 #[async_trait::async_trait]
 impl CollectByBlock for Traces {
     type Response = Vec<Trace>;
 
-    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
         let traces = source.fetcher.trace_block(request.block_number()?.into()).await?;
-        Ok(filter_traces_by_from_to_addresses(traces, &request.from_address, &request.to_address))
+        Ok(filter_traces_by_status_and_addresses(traces, &request, &query.trace_status))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
         let traces =
             if query.exclude_failed { traces::filter_failed_traces(response) } else { response };
+        let traces = query.trace_addresses.filter(traces);
         process_traces(&traces, columns, &query.schemas)
     }
 }
@@ -57,18 +78,32 @@ impl CollectByBlock for Traces {
 impl CollectByTransaction for Traces {
     type Response = Vec<Trace>;
 
-    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+    async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
         let traces = source.fetcher.trace_transaction(request.ethers_transaction_hash()?).await?;
-        Ok(filter_traces_by_from_to_addresses(traces, &request.from_address, &request.to_address))
+        Ok(filter_traces_by_status_and_addresses(traces, &request, &query.trace_status))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
         let traces =
             if query.exclude_failed { traces::filter_failed_traces(response) } else { response };
+        let traces = query.trace_addresses.filter(traces);
         process_traces(&traces, columns, &query.schemas)
     }
 }
 
+/// keep traces of transactions with the given status, then traces of the request's addresses
+///
+/// the status of a transaction is read from its top level trace, so it must be determined before
+/// the address filters can drop that trace
+pub(crate) fn filter_traces_by_status_and_addresses(
+    traces: Vec<Trace>,
+    request: &Params,
+    trace_status: &TraceStatusFilter,
+) -> Vec<Trace> {
+    let traces = trace_status.filter(traces);
+    filter_traces_by_from_to_addresses(traces, &request.from_address, &request.to_address)
+}
+
 pub(crate) fn filter_traces_by_from_to_addresses(
     traces: Vec<Trace>,
     from_address: &Option<Vec<u8>>,
@@ -271,3 +306,38 @@ pub(crate) fn filter_failed_traces(traces: Vec<Trace>) -> Vec<Trace> {
 
     filtered
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(from: H160, to: H160, trace_address: Vec<usize>) -> Trace {
+        Trace {
+            action: Action::Call(ethers::types::Call { from, to, ..Default::default() }),
+            result: None,
+            trace_address,
+            subtraces: 0,
+            transaction_position: Some(0),
+            transaction_hash: Some(H256::zero()),
+            block_number: 1,
+            block_hash: H256::zero(),
+            action_type: ActionType::Call,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn status_of_transaction_survives_address_filter() {
+        let [a, b, c] = [1, 2, 3].map(H160::repeat_byte);
+        let root = Trace { error: Some("Reverted".to_string()), ..call(a, b, vec![]) };
+        let child = call(b, c, vec![0]);
+        let traces = vec![root, child.clone()];
+
+        // the reverted top level trace is not from b, but still marks its child as reverted
+        let request = Params { from_address: Some(b.as_bytes().to_vec()), ..Default::default() };
+        let success = TraceStatusFilter::Success;
+        let reverted = TraceStatusFilter::Reverted;
+        assert_eq!(filter_traces_by_status_and_addresses(traces.clone(), &request, &success), []);
+        assert_eq!(filter_traces_by_status_and_addresses(traces, &request, &reverted), [child]);
+    }
+}
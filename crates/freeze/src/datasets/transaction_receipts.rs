@@ -0,0 +1,142 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// columns for transaction receipts
+#[cryo_to_df::to_df(Datatype::TransactionReceipts)]
+#[derive(Default)]
+pub struct TransactionReceipts {
+    n_rows: u64,
+    block_number: Vec<Option<u32>>,
+    transaction_index: Vec<u64>,
+    transaction_hash: Vec<Vec<u8>>,
+    /// 1 if the transaction succeeded and 0 if it failed, null before byzantium
+    status: Vec<Option<u64>>,
+    /// gas used by the transaction
+    gas_used: Vec<Option<u64>>,
+    /// gas used by the block up to and including the transaction
+    cumulative_gas_used: Vec<u64>,
+    /// price paid per unit of gas
+    effective_gas_price: Vec<Option<u64>>,
+    /// address of the contract created by the transaction
+    contract_address: Vec<Option<Vec<u8>>>,
+    /// bloom filter of the transaction's logs
+    logs_bloom: Vec<Vec<u8>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for TransactionReceipts {
+    fn aliases() -> Vec<&'static str> {
+        vec!["receipts"]
+    }
+
+    fn estimate_requests(params: &Params) -> usize {
+        // block receipts, falling back to the block and a receipt per transaction when the node
+        // lacks eth_getBlockReceipts
+        match params.transaction_hash {
+            Some(_) => 1,
+            None => 2 + ESTIMATED_TRANSACTIONS_PER_BLOCK,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for TransactionReceipts {
+    type Response = Vec<TransactionReceipt>;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        source.get_receipts_in_block(request.block_number()?).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::TransactionReceipts)?;
+        for receipt in response.into_iter() {
+            process_receipt(receipt, columns, schema);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for TransactionReceipts {
+    type Response = TransactionReceipt;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let tx_hash = request.ethers_transaction_hash()?;
+        source
+            .fetcher
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .ok_or(CollectError::MissingReceipt { tx_hash })
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::TransactionReceipts)?;
+        process_receipt(response, columns, schema);
+        Ok(())
+    }
+}
+
+fn process_receipt(receipt: TransactionReceipt, columns: &mut TransactionReceipts, schema: &Table) {
+    columns.n_rows += 1;
+    store!(schema, columns, block_number, receipt.block_number.map(|x| x.as_u32()));
+    store!(schema, columns, transaction_index, receipt.transaction_index.as_u64());
+    store!(schema, columns, transaction_hash, receipt.transaction_hash.as_bytes().to_vec());
+    // status is only present in receipts after byzantium
+    store!(schema, columns, status, receipt.status.map(|x| x.as_u64()));
+    store!(schema, columns, gas_used, receipt.gas_used.map(|x| x.as_u64()));
+    store!(schema, columns, cumulative_gas_used, receipt.cumulative_gas_used.as_u64());
+    store!(schema, columns, effective_gas_price, receipt.effective_gas_price.map(|x| x.as_u64()));
+    store!(
+        schema,
+        columns,
+        contract_address,
+        receipt.contract_address.map(|x| x.as_bytes().to_vec())
+    );
+    store!(schema, columns, logs_bloom, receipt.logs_bloom.as_bytes().to_vec());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stores_contract_address_of_creations() {
+        let schema = Datatype::TransactionReceipts
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &None, None, None)
+            .unwrap();
+        let creation = TransactionReceipt {
+            contract_address: Some(H160::repeat_byte(7)),
+            status: Some(1.into()),
+            ..Default::default()
+        };
+        let call = TransactionReceipt {
+            transaction_index: 1.into(),
+            status: Some(0.into()),
+            ..Default::default()
+        };
+        let mut columns = TransactionReceipts::default();
+        process_receipt(creation, &mut columns, &schema);
+        process_receipt(call, &mut columns, &schema);
+
+        assert_eq!(columns.n_rows, 2);
+        assert_eq!(
+            columns.contract_address,
+            vec![Some(H160::repeat_byte(7).as_bytes().to_vec()), None]
+        );
+        assert_eq!(columns.status, vec![Some(1), Some(0)]);
+        assert_eq!(columns.transaction_index, vec![0, 1]);
+    }
+
+    #[test]
+    fn estimates_a_receipt_request_per_transaction() {
+        let block = Params { block_number: Some(1), ..Default::default() };
+        assert_eq!(
+            TransactionReceipts::estimate_requests(&block),
+            2 + ESTIMATED_TRANSACTIONS_PER_BLOCK
+        );
+        let tx = Params { transaction_hash: Some(vec![0; 32]), ..Default::default() };
+        assert_eq!(TransactionReceipts::estimate_requests(&tx), 1);
+    }
+}
@@ -10,19 +10,46 @@ pub struct Transactions {
     block_number: Vec<Option<u32>>,
     transaction_index: Vec<Option<u64>>,
     transaction_hash: Vec<Vec<u8>>,
+    /// nonce of the sender
     nonce: Vec<u64>,
+    /// address that sent the transaction
     from_address: Vec<Vec<u8>>,
+    /// address that receives the transaction, null for contract creation
     to_address: Vec<Option<Vec<u8>>>,
+    /// value sent with the transaction, in wei
     value: Vec<U256>,
+    /// call data of the transaction
     input: Vec<Vec<u8>>,
+    /// maximum gas the transaction can use
     gas_limit: Vec<u64>,
+    /// gas used by the transaction
     gas_used: Vec<Option<u64>>,
+    /// gas used by the block up to and including the transaction
+    cumulative_gas_used: Vec<Option<u64>>,
+    /// gas price of the transaction
     gas_price: Vec<Option<u64>>,
+    /// price paid per unit of gas
+    effective_gas_price: Vec<Option<u64>>,
+    /// gas used on l1 to post the transaction, on some l2 chains
+    l1_gas_used: Vec<Option<u64>>,
+    /// fee paid for posting the transaction to l1, on some l2 chains
+    l1_fee: Vec<Option<U256>>,
+    /// type of the transaction
     transaction_type: Vec<Option<u32>>,
+    /// maximum tip per unit of gas, for eip-1559 transactions
     max_priority_fee_per_gas: Vec<Option<u64>>,
+    /// maximum fee per unit of gas, for eip-1559 transactions
     max_fee_per_gas: Vec<Option<u64>>,
-    success: Vec<bool>,
+    /// maximum fee per unit of blob gas, for blob transactions
+    max_fee_per_blob_gas: Vec<Option<u64>>,
+    /// versioned hashes of the blobs of the transaction
+    blob_versioned_hashes: Vec<Vec<Vec<u8>>>,
+    /// blob gas used by the transaction
+    blob_gas_used: Vec<Option<u64>>,
+    /// whether the transaction succeeded, null without a receipt
+    success: Vec<Option<bool>>,
     chain_id: Vec<u64>,
+    /// unix timestamp of the block
     timestamp: Vec<u32>,
     block_hash: Vec<Vec<u8>>,
 }
@@ -57,6 +84,15 @@ impl Dataset for Transactions {
     fn optional_parameters() -> Vec<Dim> {
         vec![Dim::FromAddress, Dim::ToAddress]
     }
+
+    fn estimate_requests(params: &Params) -> usize {
+        // transaction, receipt, and block, or block and block receipts, falling back to a
+        // receipt per transaction when the node lacks eth_getBlockReceipts
+        match params.transaction_hash {
+            Some(_) => 3,
+            None => 2 + ESTIMATED_TRANSACTIONS_PER_BLOCK,
+        }
+    }
 }
 
 /// tuple representing transaction and optional receipt
@@ -64,71 +100,141 @@ pub type TransactionAndReceipt = (Transaction, Option<TransactionReceipt>);
 
 #[async_trait::async_trait]
 impl CollectByBlock for Transactions {
-    type Response = (Block<Transaction>, Vec<TransactionAndReceipt>, bool);
+    type Response = (u32, Vec<TransactionAndReceipt>, bool);
 
     async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
-        let block = source
-            .fetcher
-            .get_block_with_txs(request.block_number()?)
-            .await?
-            .ok_or(CollectError::CollectError("block not found".to_string()))?;
-        let schema = query.schemas.get_schema(&Datatype::Transactions)?;
-
-        // 1. collect transactions and filter them if optional parameters are supplied
-        // filter by from_address
-        let from_filter: Box<dyn Fn(&Transaction) -> bool + Send> =
-            if let Some(from_address) = &request.from_address {
-                Box::new(move |tx| tx.from.as_bytes() == from_address)
-            } else {
-                Box::new(|_| true)
-            };
-        // filter by to_address
-        let to_filter: Box<dyn Fn(&Transaction) -> bool + Send> =
-            if let Some(to_address) = &request.to_address {
-                Box::new(move |tx| tx.to.as_ref().map_or(false, |x| x.as_bytes() == to_address))
-            } else {
-                Box::new(|_| true)
-            };
-        let transactions =
-            block.transactions.clone().into_iter().filter(from_filter).filter(to_filter).collect();
-
-        // 2. collect receipts if necessary
-        // if transactions are filtered fetch by set of transaction hashes, else fetch all receipts
-        // in block
-        let receipts: Vec<Option<_>> =
-            if schema.has_column("gas_used") | schema.has_column("success") {
-                // receipts required
-                let receipts = if request.from_address.is_some() || request.to_address.is_some() {
-                    source.get_tx_receipts(&transactions).await?
-                } else {
-                    source.get_tx_receipts_in_block(&block).await?
-                };
-                receipts.into_iter().map(Some).collect()
-            } else {
-                vec![None; block.transactions.len()]
-            };
-
-        let transactions_with_receips = transactions.into_iter().zip(receipts).collect();
-        Ok((block, transactions_with_receips, query.exclude_failed))
+        let address_filtered = request.from_address.is_some() || request.to_address.is_some();
+        if query.use_trace_filter && address_filtered {
+            extract_by_trace_filter(request, source, query).await
+        } else {
+            let (block, transactions_with_receipts, exclude_failed) =
+                extract_block_transactions(request, source, query).await?;
+            Ok((block.timestamp.as_u32(), transactions_with_receipts, exclude_failed))
+        }
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
         let schema = query.schemas.get_schema(&Datatype::Transactions)?;
-        let (block, transactions_with_receipts, exclude_failed) = response;
+        let (timestamp, transactions_with_receipts, exclude_failed) = response;
         for (tx, receipt) in transactions_with_receipts.into_iter() {
-            process_transaction(
-                tx,
-                receipt,
-                columns,
-                schema,
-                exclude_failed,
-                block.timestamp.as_u32(),
-            )?;
+            process_transaction(tx, receipt, columns, schema, exclude_failed, timestamp)?;
         }
         Ok(())
     }
 }
 
+/// fetch a block with its transactions, keeping transactions that match the address filters
+pub(crate) async fn extract_block_transactions(
+    request: Params,
+    source: Arc<Source>,
+    query: Arc<Query>,
+) -> R<(Block<Transaction>, Vec<TransactionAndReceipt>, bool)> {
+    let block_number = request.block_number()?;
+    let block = source
+        .fetcher
+        .get_block_with_txs(block_number)
+        .await?
+        .ok_or(CollectError::BlockNotFound { number: block_number })?;
+    let schema = query.schemas.get_schema(&Datatype::Transactions)?;
+
+    // 1. collect transactions and filter them if optional parameters are supplied
+    let transactions: Vec<Transaction> = block
+        .transactions
+        .clone()
+        .into_iter()
+        .filter(|tx| matches_address_filters(tx, &request))
+        .collect();
+
+    // 2. collect receipts if necessary
+    // if transactions are filtered fetch by set of transaction hashes, else fetch all receipts
+    // in block
+    let receipts: Vec<Option<_>> = if needs_receipts(schema) {
+        // receipts required
+        if request.from_address.is_some() || request.to_address.is_some() {
+            source.get_tx_receipts(&transactions).await?
+        } else {
+            source.get_tx_receipts_in_block(&block).await?
+        }
+    } else {
+        vec![None; block.transactions.len()]
+    };
+
+    let transactions_with_receips = transactions.into_iter().zip(receipts).collect();
+    Ok((block, transactions_with_receips, query.exclude_failed))
+}
+
+/// find transactions matching the address filters with trace_filter, fetching only those
+/// transactions instead of the whole block
+async fn extract_by_trace_filter(
+    request: Params,
+    source: Arc<Source>,
+    query: Arc<Query>,
+) -> R<(u32, Vec<TransactionAndReceipt>, bool)> {
+    let block_number = request.block_number()?;
+    let mut filter = TraceFilter::default().from_block(block_number).to_block(block_number);
+    if let Some(from_address) = &request.from_address {
+        filter = filter.from_address(vec![H160::from_slice(from_address)]);
+    }
+    if let Some(to_address) = &request.to_address {
+        filter = filter.to_address(vec![H160::from_slice(to_address)]);
+    }
+    let traces = source.fetcher.trace_filter(filter).await?;
+
+    let fetcher = &source.fetcher;
+    let transactions: Vec<Transaction> = futures::future::try_join_all(
+        top_level_transaction_hashes(&traces).into_iter().map(|tx_hash| async move {
+            fetcher
+                .get_transaction(tx_hash)
+                .await?
+                .ok_or(CollectError::TransactionNotFound { tx_hash })
+        }),
+    )
+    .await?
+    .into_iter()
+    // trace_filter also matches contract creations by their created address
+    .filter(|tx| matches_address_filters(tx, &request))
+    .collect();
+
+    let schema = query.schemas.get_schema(&Datatype::Transactions)?;
+    let receipts = if needs_receipts(schema) {
+        source.get_tx_receipts(&transactions).await?
+    } else {
+        vec![None; transactions.len()]
+    };
+    let block = source
+        .fetcher
+        .get_block(block_number)
+        .await?
+        .ok_or(CollectError::BlockNotFound { number: block_number })?;
+
+    let transactions_with_receipts = transactions.into_iter().zip(receipts).collect();
+    Ok((block.timestamp.as_u32(), transactions_with_receipts, query.exclude_failed))
+}
+
+/// hashes of the transactions whose top-level call is among `traces`, in order
+fn top_level_transaction_hashes(traces: &[Trace]) -> Vec<H256> {
+    let mut tx_hashes: Vec<H256> = traces
+        .iter()
+        .filter(|trace| trace.trace_address.is_empty())
+        .filter_map(|trace| trace.transaction_hash)
+        .collect();
+    tx_hashes.dedup();
+    tx_hashes
+}
+
+/// whether a transaction is sent from `from_address` and to `to_address`, when given
+fn matches_address_filters(tx: &Transaction, request: &Params) -> bool {
+    let from_matches = match &request.from_address {
+        Some(from) => tx.from.as_bytes() == from.as_slice(),
+        None => true,
+    };
+    let to_matches = match &request.to_address {
+        Some(to) => tx.to.as_ref().is_some_and(|x| x.as_bytes() == to.as_slice()),
+        None => true,
+    };
+    from_matches && to_matches
+}
+
 #[async_trait::async_trait]
 impl CollectByTransaction for Transactions {
     type Response = (TransactionAndReceipt, bool, u32);
@@ -140,8 +246,8 @@ impl CollectByTransaction for Transactions {
             .fetcher
             .get_transaction(tx_hash)
             .await?
-            .ok_or(CollectError::CollectError("transaction not found".to_string()))?;
-        let receipt = if schema.has_column("gas_used") {
+            .ok_or(CollectError::TransactionNotFound { tx_hash })?;
+        let receipt = if needs_receipts(schema) {
             source.fetcher.get_transaction_receipt(tx_hash).await?
         } else {
             None
@@ -155,7 +261,7 @@ impl CollectByTransaction for Transactions {
             .fetcher
             .get_block(block_number.as_u64())
             .await?
-            .ok_or(CollectError::CollectError("block not found".to_string()))?;
+            .ok_or(CollectError::BlockNotFound { number: block_number.as_u64() })?;
 
         let timestamp = block.timestamp.as_u32();
 
@@ -170,6 +276,21 @@ impl CollectByTransaction for Transactions {
     }
 }
 
+/// whether any of the schema's columns are read from transaction receipts
+fn needs_receipts(schema: &Table) -> bool {
+    [
+        "gas_used",
+        "cumulative_gas_used",
+        "effective_gas_price",
+        "l1_gas_used",
+        "l1_fee",
+        "blob_gas_used",
+        "success",
+    ]
+    .iter()
+    .any(|column| schema.has_column(column))
+}
+
 pub(crate) fn process_transaction(
     tx: Transaction,
     receipt: Option<TransactionReceipt>,
@@ -178,14 +299,15 @@ pub(crate) fn process_transaction(
     exclude_failed: bool,
     timestamp: u32,
 ) -> R<()> {
+    // status is unknown without a receipt, so such transactions are not excluded as failed
     let success = if exclude_failed | schema.has_column("success") {
         let success = tx_success(&tx, &receipt)?;
-        if exclude_failed & !success {
+        if exclude_failed & (success == Some(false)) {
             return Ok(())
         }
         success
     } else {
-        false
+        None
     };
 
     columns.n_rows += 1;
@@ -199,8 +321,36 @@ pub(crate) fn process_transaction(
     store!(schema, columns, input, tx.input.to_vec());
     store!(schema, columns, gas_limit, tx.gas.as_u64());
     store!(schema, columns, success, success);
-    store!(schema, columns, gas_used, receipt.and_then(|r| r.gas_used.map(|x| x.as_u64())));
+    store!(
+        schema,
+        columns,
+        gas_used,
+        receipt.as_ref().and_then(|r| r.gas_used.map(|x| x.as_u64()))
+    );
+    store!(
+        schema,
+        columns,
+        cumulative_gas_used,
+        receipt.as_ref().map(|r| r.cumulative_gas_used.as_u64())
+    );
     store!(schema, columns, gas_price, tx.gas_price.map(|gas_price| gas_price.as_u64()));
+    store!(
+        schema,
+        columns,
+        effective_gas_price,
+        receipt.as_ref().and_then(|r| r.effective_gas_price.map(|x| x.as_u64()))
+    );
+    // l1 fields are only present in receipts of some l2 chains
+    store!(
+        schema,
+        columns,
+        l1_gas_used,
+        receipt
+            .as_ref()
+            .and_then(|r| other_field::<U256>(&r.other, "l1GasUsed"))
+            .map(|x| x.as_u64())
+    );
+    store!(schema, columns, l1_fee, receipt.as_ref().and_then(|r| other_field(&r.other, "l1Fee")));
     store!(schema, columns, transaction_type, tx.transaction_type.map(|value| value.as_u32()));
     store!(schema, columns, max_fee_per_gas, tx.max_fee_per_gas.map(|value| value.as_u64()));
     store!(
@@ -209,20 +359,54 @@ pub(crate) fn process_transaction(
         max_priority_fee_per_gas,
         tx.max_priority_fee_per_gas.map(|value| value.as_u64())
     );
+    // blob fields are only present in type 3 transactions
+    store!(
+        schema,
+        columns,
+        max_fee_per_blob_gas,
+        other_field::<U256>(&tx.other, "maxFeePerBlobGas").map(|x| x.as_u64())
+    );
+    store!(
+        schema,
+        columns,
+        blob_versioned_hashes,
+        other_field::<Vec<H256>>(&tx.other, "blobVersionedHashes")
+            .unwrap_or_default()
+            .iter()
+            .map(|hash| hash.as_bytes().to_vec())
+            .collect()
+    );
+    store!(
+        schema,
+        columns,
+        blob_gas_used,
+        receipt
+            .as_ref()
+            .and_then(|r| other_field::<U256>(&r.other, "blobGasUsed"))
+            .map(|x| x.as_u64())
+    );
     store!(schema, columns, timestamp, timestamp);
     store!(schema, columns, block_hash, tx.block_hash.unwrap_or_default().as_bytes().to_vec());
 
     Ok(())
 }
 
-fn tx_success(tx: &Transaction, receipt: &Option<TransactionReceipt>) -> R<bool> {
-    if let Some(status) = receipt.as_ref().and_then(|x| x.status) {
-        Ok(status.as_u64() == 1)
+fn other_field<T: serde::de::DeserializeOwned>(other: &OtherFields, key: &str) -> Option<T> {
+    other.get_deserialized(key).and_then(|value| value.ok())
+}
+
+fn tx_success(tx: &Transaction, receipt: &Option<TransactionReceipt>) -> R<Option<bool>> {
+    let receipt = match receipt {
+        Some(receipt) => receipt,
+        None => return Ok(None),
+    };
+    if let Some(status) = receipt.status {
+        Ok(Some(status.as_u64() == 1))
     } else if let (Some(1), Some(true)) =
         (tx.chain_id.map(|x| x.as_u64()), tx.block_number.map(|x| x.as_u64() < 4370000))
     {
-        if let Some(gas_used) = receipt.as_ref().and_then(|x| x.gas_used.map(|x| x.as_u64())) {
-            Ok(gas_used == 0)
+        if let Some(gas_used) = receipt.gas_used.map(|x| x.as_u64()) {
+            Ok(Some(gas_used == 0))
         } else {
             return Err(err("could not determine status of transaction"))
         }
@@ -230,3 +414,104 @@ fn tx_success(tx: &Transaction, receipt: &Option<TransactionReceipt>) -> R<bool>
         return Err(err("could not determine status of transaction"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blob_schema(binary_type: ColumnEncoding) -> Table {
+        let columns = Some(vec!["blob_versioned_hashes".to_string()]);
+        Datatype::Transactions
+            .table_schema(&[], &binary_type, &None, &None, &columns, None, None)
+            .unwrap()
+    }
+
+    fn blob_transactions(schema: &Table) -> Transactions {
+        let mut blob_tx = Transaction::default();
+        let hashes = vec![H256::repeat_byte(1), H256::repeat_byte(2)];
+        blob_tx.other.insert("blobVersionedHashes".to_string(), serde_json::json!(hashes));
+        let mut columns = Transactions::default();
+        for tx in [blob_tx, Transaction::default()] {
+            process_transaction(tx, None, &mut columns, schema, false, 0).unwrap();
+        }
+        columns
+    }
+
+    #[test]
+    fn selects_top_level_transaction_hashes() {
+        let trace = |tx_hash: u64, trace_address: Vec<usize>| Trace {
+            action: Action::Call(ethers::types::Call::default()),
+            result: None,
+            trace_address,
+            subtraces: 0,
+            transaction_position: None,
+            transaction_hash: Some(H256::from_low_u64_be(tx_hash)),
+            block_number: 1,
+            block_hash: H256::zero(),
+            action_type: ActionType::Call,
+            error: None,
+        };
+        let traces = vec![trace(1, vec![]), trace(1, vec![0]), trace(2, vec![3]), trace(3, vec![])];
+        let tx_hashes = top_level_transaction_hashes(&traces);
+        assert_eq!(tx_hashes, vec![H256::from_low_u64_be(1), H256::from_low_u64_be(3)]);
+    }
+
+    #[test]
+    fn missing_receipt_has_null_success() {
+        let columns = Some(vec!["transaction_hash".to_string(), "success".to_string()]);
+        let schema = Datatype::Transactions
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &columns, None, None)
+            .unwrap();
+        let receipt = TransactionReceipt { status: Some(1.into()), ..Default::default() };
+        let mut transactions = Transactions::default();
+        for receipt in [Some(receipt), None] {
+            process_transaction(
+                Transaction::default(),
+                receipt,
+                &mut transactions,
+                &schema,
+                true,
+                0,
+            )
+            .unwrap();
+        }
+
+        let schemas = std::collections::HashMap::from([(Datatype::Transactions, schema)]);
+        let dfs = transactions.create_dfs(&schemas, 1).unwrap();
+        let success = dfs[&Datatype::Transactions].column("success").unwrap();
+        assert_eq!(success.bool().unwrap().into_iter().collect::<Vec<_>>(), [Some(true), None]);
+    }
+
+    #[test]
+    fn blob_versioned_hashes_are_list_column() {
+        let schema = blob_schema(ColumnEncoding::Binary);
+        let schemas = std::collections::HashMap::from([(Datatype::Transactions, schema.clone())]);
+        let dfs = blob_transactions(&schema).create_dfs(&schemas, 1).unwrap();
+        let column = dfs[&Datatype::Transactions].column("blob_versioned_hashes").unwrap();
+        assert_eq!(column.dtype(), &DataType::List(Box::new(DataType::Binary)));
+        let lengths: Vec<_> =
+            column.list().unwrap().into_iter().map(|x| x.unwrap().len()).collect();
+        assert_eq!(lengths, vec![2, 0]);
+    }
+
+    #[test]
+    fn blob_versioned_hashes_as_hex() {
+        let schema = blob_schema(ColumnEncoding::Hex);
+        let schemas = std::collections::HashMap::from([(Datatype::Transactions, schema.clone())]);
+        let dfs = blob_transactions(&schema).create_dfs(&schemas, 1).unwrap();
+        let column = dfs[&Datatype::Transactions].column("blob_versioned_hashes").unwrap();
+        let first = column.list().unwrap().into_iter().next().unwrap().unwrap();
+        assert_eq!(
+            first.utf8().unwrap().get(0),
+            Some(format!("{:?}", H256::repeat_byte(1)).as_str())
+        );
+    }
+
+    #[test]
+    fn estimates_a_receipt_request_per_transaction() {
+        let block = Params { block_number: Some(1), ..Default::default() };
+        assert_eq!(Transactions::estimate_requests(&block), 2 + ESTIMATED_TRANSACTIONS_PER_BLOCK);
+        let tx = Params { transaction_hash: Some(vec![0; 32]), ..Default::default() };
+        assert_eq!(Transactions::estimate_requests(&tx), 3);
+    }
+}
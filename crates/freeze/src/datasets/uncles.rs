@@ -0,0 +1,71 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// columns for uncles
+#[cryo_to_df::to_df(Datatype::Uncles)]
+#[derive(Default)]
+pub struct Uncles {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    /// index of the uncle within its block
+    uncle_index: Vec<u32>,
+    /// hash of the uncle block
+    uncle_hash: Vec<Option<Vec<u8>>>,
+    /// address that received the uncle reward
+    uncle_author: Vec<Option<Vec<u8>>>,
+    /// gas used by the uncle block
+    uncle_gas_used: Vec<u64>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for Uncles {
+    fn aliases() -> Vec<&'static str> {
+        vec!["ommers"]
+    }
+
+    fn default_sort() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "uncle_index"])
+    }
+
+    fn estimate_requests(_params: &Params) -> usize {
+        // uncle count, then each uncle
+        2
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for Uncles {
+    type Response = (u32, Vec<Option<Block<TxHash>>>);
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let block_number = request.block_number()?;
+        let n_uncles = source.fetcher.get_uncle_count(block_number).await?;
+        let uncles = futures::future::try_join_all(
+            (0..n_uncles).map(|index| source.fetcher.get_uncle(block_number, index)),
+        )
+        .await?;
+        Ok((block_number as u32, uncles))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::Uncles)?;
+        let (block_number, uncles) = response;
+        for (index, uncle) in uncles.into_iter().enumerate() {
+            let uncle = uncle.ok_or(CollectError::BlockNotFound { number: block_number as u64 })?;
+            columns.n_rows += 1;
+            store!(schema, columns, block_number, block_number);
+            store!(schema, columns, uncle_index, index as u32);
+            store!(schema, columns, uncle_hash, uncle.hash.map(|x| x.0.to_vec()));
+            store!(schema, columns, uncle_author, uncle.author.map(|x| x.0.to_vec()));
+            store!(schema, columns, uncle_gas_used, uncle.gas_used.as_u64());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for Uncles {
+    type Response = ();
+}
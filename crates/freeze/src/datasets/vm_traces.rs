@@ -9,15 +9,30 @@ pub struct VmTraces {
     block_number: Vec<Option<u32>>,
     transaction_hash: Vec<Option<Vec<u8>>>,
     transaction_index: Vec<u32>,
+    /// program counter of the operation
     pc: Vec<u64>,
+    /// gas cost of the operation
     cost: Vec<u64>,
+    /// gas remaining after the operation
     used: Vec<Option<u64>>,
+    /// values pushed to the stack by the operation
     push: Vec<Option<Vec<u8>>>,
+    /// offset of the memory written by the operation
     mem_off: Vec<Option<u32>>,
+    /// memory written by the operation
     mem_data: Vec<Option<Vec<u8>>>,
+    /// storage slot written by the operation
     storage_key: Vec<Option<Vec<u8>>>,
+    /// value written to the storage slot
     storage_val: Vec<Option<Vec<u8>>>,
+    /// name of the opcode
     op: Vec<String>,
+    /// call depth of the operation
+    depth: Vec<u32>,
+    /// position of the trace in the call tree, as indices joined by underscores
+    trace_address: Vec<String>,
+    /// operation as json
+    trace_json: Vec<String>,
     n_rows: usize,
     chain_id: Vec<u64>,
 }
@@ -35,11 +50,25 @@ impl Dataset for VmTraces {
     fn default_sort() -> Option<Vec<&'static str>> {
         Some(vec!["block_number", "transaction_index", "used"])
     }
+
+    fn estimate_requests(params: &Params) -> usize {
+        // traces, plus the block to position them by transaction hash
+        match params.transaction_hash {
+            Some(_) => 1,
+            None => 2,
+        }
+    }
+
+    fn required_capabilities() -> Vec<Capability> {
+        vec![Capability::TraceReplay]
+    }
 }
 
+type BlockVmTraces = (Option<u32>, Option<Vec<u8>>, Vec<(u32, BlockTrace)>);
+
 #[async_trait::async_trait]
 impl CollectByBlock for VmTraces {
-    type Response = (Option<u32>, Option<Vec<u8>>, Vec<ethers::types::BlockTrace>);
+    type Response = BlockVmTraces;
 
     async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
         source.fetcher.trace_block_vm_traces(request.block_number()? as u32).await
@@ -52,7 +81,7 @@ impl CollectByBlock for VmTraces {
 
 #[async_trait::async_trait]
 impl CollectByTransaction for VmTraces {
-    type Response = (Option<u32>, Option<Vec<u8>>, Vec<ethers::types::BlockTrace>);
+    type Response = BlockVmTraces;
 
     async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
         source.fetcher.trace_transaction_vm_traces(request.transaction_hash()?).await
@@ -63,16 +92,55 @@ impl CollectByTransaction for VmTraces {
     }
 }
 
-fn process_vm_traces(
-    response: (Option<u32>, Option<Vec<u8>>, Vec<ethers::types::BlockTrace>),
-    columns: &mut VmTraces,
-    schemas: &Schemas,
-) -> R<()> {
+fn process_vm_traces(response: BlockVmTraces, columns: &mut VmTraces, schemas: &Schemas) -> R<()> {
     let (block_number, tx, block_traces) = response;
     let schema = schemas.get(&Datatype::VmTraces).ok_or(err("schema not provided"))?;
-    for (tx_pos, block_trace) in block_traces.into_iter().enumerate() {
+    if schema.has_column("trace_json") {
+        return process_vm_trace_jsons(block_number, tx, block_traces, columns, schema)
+    }
+    for (tx_pos, block_trace) in block_traces {
+        if let Some(vm_trace) = block_trace.vm_trace {
+            add_ops(vm_trace, schema, columns, block_number, tx.clone(), tx_pos, &[]);
+        }
+    }
+    Ok(())
+}
+
+/// columns that hold one row per executed op
+const OP_COLUMNS: [&str; 11] = [
+    "pc",
+    "cost",
+    "used",
+    "push",
+    "mem_off",
+    "mem_data",
+    "storage_key",
+    "storage_val",
+    "op",
+    "depth",
+    "trace_address",
+];
+
+/// store each transaction's nested vm trace as a single json row
+fn process_vm_trace_jsons(
+    block_number: Option<u32>,
+    tx: Option<Vec<u8>>,
+    block_traces: Vec<(u32, ethers::types::BlockTrace)>,
+    columns: &mut VmTraces,
+    schema: &Table,
+) -> R<()> {
+    if OP_COLUMNS.iter().any(|column| schema.has_column(column)) {
+        return Err(err("trace_json cannot be combined with per-op vm_traces columns"))
+    }
+    for (tx_pos, block_trace) in block_traces {
         if let Some(vm_trace) = block_trace.vm_trace {
-            add_ops(vm_trace, schema, columns, block_number, tx.clone(), tx_pos);
+            let trace_json = serde_json::to_string(&vm_trace)
+                .map_err(|_| err("could not serialize vm trace"))?;
+            columns.n_rows += 1;
+            store!(schema, columns, block_number, block_number);
+            store!(schema, columns, transaction_hash, tx.clone());
+            store!(schema, columns, transaction_index, tx_pos);
+            store!(schema, columns, trace_json, trace_json);
         }
     }
     Ok(())
@@ -84,16 +152,25 @@ fn add_ops(
     columns: &mut VmTraces,
     number: Option<u32>,
     tx_hash: Option<Vec<u8>>,
-    tx_pos: usize,
+    tx_pos: u32,
+    trace_address: &[u32],
 ) {
+    let trace_address_str = if schema.has_column("trace_address") {
+        trace_address.iter().map(|n| n.to_string()).collect::<Vec<_>>().join("_")
+    } else {
+        String::new()
+    };
+    let mut n_subtraces = 0;
     for opcode in vm_trace.ops {
         columns.n_rows += 1;
 
         store!(schema, columns, block_number, number);
         store!(schema, columns, transaction_hash, tx_hash.clone());
-        store!(schema, columns, transaction_index, tx_pos as u32);
+        store!(schema, columns, transaction_index, tx_pos);
         store!(schema, columns, pc, opcode.pc as u64);
         store!(schema, columns, cost, opcode.cost);
+        store!(schema, columns, depth, trace_address.len() as u32);
+        store!(schema, columns, trace_address, trace_address_str.clone());
         if let Some(ex) = opcode.ex {
             store!(schema, columns, used, Some(ex.used));
             store!(schema, columns, push, Some(ex.push.to_vec_u8()));
@@ -128,7 +205,80 @@ fn add_ops(
         };
 
         if let Some(sub) = opcode.sub {
-            add_ops(sub, schema, columns, number, tx_hash.clone(), tx_pos)
+            let mut sub_trace_address = trace_address.to_vec();
+            sub_trace_address.push(n_subtraces);
+            n_subtraces += 1;
+            add_ops(sub, schema, columns, number, tx_hash.clone(), tx_pos, &sub_trace_address)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn uses_vm_traces_schema() {
+        let schema = |datatype: Datatype| {
+            datatype
+                .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &None, None, None)
+                .unwrap()
+        };
+        let schemas = HashMap::from([
+            (Datatype::BalanceDiffs, schema(Datatype::BalanceDiffs)),
+            (Datatype::VmTraces, schema(Datatype::VmTraces)),
+        ]);
+        let op = VMOperation {
+            pc: 7,
+            cost: 3,
+            op: ExecutedInstruction::Known(Opcode::PUSH1),
+            ..Default::default()
+        };
+        let block_trace = BlockTrace {
+            output: Bytes::default(),
+            trace: None,
+            vm_trace: Some(VMTrace { code: Bytes::default(), ops: vec![op] }),
+            state_diff: None,
+            transaction_hash: None,
+        };
+        let mut columns = VmTraces::default();
+        process_vm_traces((Some(1), None, vec![(4, block_trace)]), &mut columns, &schemas).unwrap();
+
+        assert_eq!(columns.n_rows, 1);
+        assert_eq!(columns.transaction_index, vec![4]);
+        assert_eq!(columns.pc, vec![7]);
+        assert_eq!(columns.op, vec!["PUSH1"]);
+
+        let schemas = HashMap::from([(Datatype::BalanceDiffs, schema(Datatype::BalanceDiffs))]);
+        let result = process_vm_traces((Some(1), None, vec![]), &mut columns, &schemas);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn joins_trace_addresses_of_nested_ops_with_underscores() {
+        let columns =
+            Some(vec!["pc".to_string(), "depth".to_string(), "trace_address".to_string()]);
+        let schema = Datatype::VmTraces
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &columns, None, None)
+            .unwrap();
+        let op = |pc: usize, sub: Option<VMTrace>| VMOperation { pc, sub, ..Default::default() };
+        let trace = |ops: Vec<VMOperation>| VMTrace { code: Bytes::default(), ops };
+        let nested = trace(vec![op(3, None)]);
+        let vm_trace = trace(vec![op(1, None), op(2, Some(trace(vec![op(0, Some(nested))])))]);
+        let mut columns = VmTraces::default();
+        add_ops(vm_trace, &schema, &mut columns, Some(1), None, 0, &[]);
+
+        assert_eq!(columns.pc, vec![1, 2, 0, 3]);
+        assert_eq!(columns.depth, vec![0, 0, 1, 2]);
+        assert_eq!(columns.trace_address, vec!["", "", "0", "0_0"]);
+    }
+
+    #[test]
+    fn estimates_a_block_request_per_block() {
+        let block = Params { block_number: Some(1), ..Default::default() };
+        assert_eq!(VmTraces::estimate_requests(&block), 2);
+        let tx = Params { transaction_hash: Some(vec![0; 32]), ..Default::default() };
+        assert_eq!(VmTraces::estimate_requests(&tx), 1);
+    }
+}
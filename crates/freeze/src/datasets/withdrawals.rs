@@ -0,0 +1,62 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+
+/// columns for withdrawals
+#[cryo_to_df::to_df(Datatype::Withdrawals)]
+#[derive(Default)]
+pub struct Withdrawals {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    /// index of the withdrawal on the beacon chain
+    withdrawal_index: Vec<u64>,
+    /// index of the validator that withdrew
+    validator_index: Vec<u64>,
+    /// address that received the withdrawal
+    address: Vec<Vec<u8>>,
+    /// amount withdrawn, in gwei
+    amount: Vec<u64>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for Withdrawals {
+    fn default_sort() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "withdrawal_index"])
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for Withdrawals {
+    type Response = Block<TxHash>;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Arc<Query>) -> R<Self::Response> {
+        let block_number = request.block_number()?;
+        source
+            .fetcher
+            .get_block(block_number)
+            .await?
+            .ok_or(CollectError::BlockNotFound { number: block_number })
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
+        let schema = query.schemas.get_schema(&Datatype::Withdrawals)?;
+        let block_number = response.number.ok_or(CollectError::MissingBlockNumber)?.as_u32();
+        // blocks before shanghai have no withdrawals
+        for withdrawal in response.withdrawals.unwrap_or_default().into_iter() {
+            columns.n_rows += 1;
+            store!(schema, columns, block_number, block_number);
+            store!(schema, columns, withdrawal_index, withdrawal.index.as_u64());
+            store!(schema, columns, validator_index, withdrawal.validator_index.as_u64());
+            store!(schema, columns, address, withdrawal.address.as_bytes().to_vec());
+            // rpc nodes report withdrawal amounts in gwei
+            store!(schema, columns, amount, withdrawal.amount.as_u64());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for Withdrawals {
+    type Response = ();
+}
@@ -1,15 +1,17 @@
 use crate::{
-    collect_partition, dataframes, err, reports, summaries, CollectError, Datatype, ExecutionEnv,
-    FileOutput, FreezeSummary, MetaDatatype, Partition, Query, Source,
+    collect_partition, collect_partition_with_flush, dataframes, err, reports, summaries,
+    Capability, CollectError, Datatype, ExecutionEnv, FileFormat, FileOutput, FreezeSummary,
+    MetaDatatype, Partition, Query, RowFlush, Source,
 };
 use chrono::{DateTime, Local};
-use futures::{stream::FuturesUnordered, StreamExt};
+use futures::{Future, Stream, StreamExt};
+use polars::prelude::DataFrame;
 use std::{
     collections::{HashMap, HashSet},
     path::PathBuf,
     sync::Arc,
 };
-use tokio::sync::Semaphore;
+use tokio::{sync::mpsc, task::JoinError};
 
 type PartitionPayload = (
     Partition,
@@ -19,7 +21,6 @@ type PartitionPayload = (
     Arc<Source>,
     FileOutput,
     ExecutionEnv,
-    Option<std::sync::Arc<Semaphore>>,
 );
 
 /// collect data and output as files
@@ -37,7 +38,8 @@ pub async fn freeze(
 
     // print summary
     if env.verbose >= 1 {
-        summaries::print_cryo_intro(query, source, sink, env, payloads.len() as u64)?;
+        let n_requests = if env.dry { Some(estimate_requests(&payloads, source)?) } else { None };
+        summaries::print_cryo_intro(query, source, sink, env, payloads.len() as u64, n_requests)?;
     }
 
     // check dry run
@@ -49,42 +51,92 @@ pub async fn freeze(
     if payloads.is_empty() {
         let results = FreezeSummary { skipped: skipping, ..Default::default() };
         if env.verbose >= 1 {
-            summaries::print_cryo_conclusion(&results, query, env)
+            summaries::print_cryo_conclusion(&results, query, source, env)
         }
         return Ok(Some(results))
     }
 
+    // fail before collecting if the node lacks trace methods that the datatypes need
+    check_capabilities(query, source).await?;
+
     // create initial report
     if env.report {
-        reports::write_report(env, query, sink, None)?;
+        reports::write_report(env, query, source, sink, None)?;
     };
 
     // perform collection
-    let results = freeze_partitions(env, payloads, skipping).await;
+    let results = freeze_partitions(env, payloads, skipping, source.max_concurrent_chunks).await;
 
     // create summary
     if env.verbose >= 1 {
-        summaries::print_cryo_conclusion(&results, query, env)
+        summaries::print_cryo_conclusion(&results, query, source, env)
     }
 
     // create final report
     if env.report {
-        reports::write_report(env, query, sink, Some(&results))?;
+        reports::write_report(env, query, source, sink, Some(&results))?;
     };
 
     // return
     Ok(Some(results))
 }
 
+/// probe the node's trace methods if any datatype of the query requires them
+async fn check_capabilities(query: &Query, source: &Source) -> Result<(), CollectError> {
+    let required: Vec<(Datatype, Vec<Capability>)> = query
+        .schemas
+        .keys()
+        .map(|datatype| (*datatype, datatype.required_capabilities()))
+        .filter(|(_, capabilities)| !capabilities.is_empty())
+        .collect();
+    if required.is_empty() {
+        return Ok(())
+    }
+    let capabilities = source.fetcher.capabilities().await?;
+    let mut missing: Vec<String> = required
+        .into_iter()
+        .filter(|(_, required)| {
+            !required.iter().any(|capability| capabilities.supports(*capability))
+        })
+        .map(|(datatype, required)| {
+            let methods: Vec<_> =
+                required.iter().map(|capability| capability.probe_method()).collect();
+            format!("{} requires {}", datatype.name(), methods.join(" or "))
+        })
+        .collect();
+    if missing.is_empty() {
+        return Ok(())
+    }
+    missing.sort();
+    Err(err(&format!("rpc node does not support required methods: {}", missing.join(", "))))
+}
+
+/// estimate number of rpc requests needed to collect payloads
+fn estimate_requests(
+    payloads: &[PartitionPayload],
+    source: &Source,
+) -> Result<usize, CollectError> {
+    let mut n_requests = 0;
+    for (partition, datatype, ..) in payloads.iter() {
+        let inner_request_size = match datatype {
+            MetaDatatype::Scalar(datatype) if datatype.use_block_ranges() => {
+                Some(source.inner_request_size)
+            }
+            _ => None,
+        };
+        for params in partition.param_sets(inner_request_size)?.iter() {
+            n_requests += datatype.estimate_requests(params);
+        }
+    }
+    Ok(n_requests)
+}
+
 fn get_payloads(
     query: &Query,
     source: &Source,
     sink: &FileOutput,
     env: &ExecutionEnv,
 ) -> Result<(Vec<PartitionPayload>, Vec<Partition>), CollectError> {
-    let semaphore = source
-        .max_concurrent_chunks
-        .map(|x| std::sync::Arc::new(tokio::sync::Semaphore::new(x as usize)));
     let source = Arc::new(source.clone());
     let arc_query = Arc::new(query.clone());
     let mut payloads = Vec::new();
@@ -116,7 +168,6 @@ fn get_payloads(
                 source.clone(),
                 sink.clone(),
                 env.clone(),
-                semaphore.clone(),
             );
             payloads.push(payload);
         }
@@ -128,31 +179,39 @@ async fn freeze_partitions(
     env: &ExecutionEnv,
     payloads: Vec<PartitionPayload>,
     skipped: Vec<Partition>,
+    max_concurrent_chunks: Option<u64>,
 ) -> FreezeSummary {
     if let Some(bar) = &env.bar {
         bar.set_length(payloads.len() as u64);
         if let Some(payload) = &payloads.first() {
-            let (_, _, _, _, _, _, env, _) = payload;
+            let (_, _, _, _, _, _, env) = payload;
             let dt_start: DateTime<Local> = env.t_start.into();
             bar.set_message(format!("started at {}", dt_start.format("%Y-%m-%d %H:%M:%S%.3f")));
         }
     }
 
-    // spawn task for each partition
-    let mut futures = FuturesUnordered::new();
-    for payload in payloads.into_iter() {
-        futures.push(tokio::spawn(
-            async move { (payload.0.clone(), freeze_partition(payload).await) },
-        ));
-    }
+    // spawn task for each partition, at most max_concurrent_chunks at a time
+    // once cancelled, partitions that have not started are returned without being collected
+    let mut futures = spawn_bounded(payloads, max_concurrent_chunks, |payload| async move {
+        if payload.6.is_cancelled() {
+            return (payload.0, None)
+        }
+        (payload.0.clone(), Some(freeze_partition(payload).await))
+    });
 
     // aggregate results
     let mut completed = Vec::new();
     let mut errored = Vec::new();
+    let mut cancelled = Vec::new();
+    let mut n_rows = 0;
     while let Some(result) = futures.next().await {
         match result {
-            Ok((partition, Ok(()))) => completed.push(partition),
-            Ok((partition, Err(e))) => errored.push((Some(partition), e)),
+            Ok((partition, Some(Ok(partition_rows)))) => {
+                n_rows += partition_rows;
+                completed.push(partition)
+            }
+            Ok((partition, Some(Err(e)))) => errored.push((Some(partition), e)),
+            Ok((partition, None)) => cancelled.push(partition),
             Err(_e) => errored.push((None, err("error joining chunks"))),
         }
     }
@@ -161,34 +220,201 @@ async fn freeze_partitions(
         bar.finish_and_clear();
     }
 
-    FreezeSummary { completed, errored, skipped }
+    FreezeSummary { completed, errored, skipped, cancelled, n_rows }
+}
+
+/// spawn a task for each item, yielding results as tasks complete
+///
+/// tasks are spawned lazily so that no more than `max_concurrent` exist at once
+fn spawn_bounded<I, F, Fut, T>(
+    items: Vec<I>,
+    max_concurrent: Option<u64>,
+    f: F,
+) -> impl Stream<Item = Result<T, JoinError>> + Unpin
+where
+    F: Fn(I) -> Fut,
+    Fut: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    let limit = match max_concurrent {
+        Some(max_concurrent) => (max_concurrent as usize).max(1),
+        None => items.len().max(1),
+    };
+    futures::stream::iter(items).map(move |item| tokio::spawn(f(item))).buffer_unordered(limit)
 }
 
-async fn freeze_partition(payload: PartitionPayload) -> Result<(), CollectError> {
-    let (partition, datatype, paths, query, source, sink, env, semaphore) = payload;
+/// collect partition and write its dataframes, returning number of rows written
+async fn freeze_partition(payload: PartitionPayload) -> Result<u64, CollectError> {
+    let (partition, datatype, paths, query, source, sink, env) = payload;
 
-    // acquire chunk semaphore
-    let _permit = match &semaphore {
-        Some(semaphore) => Some(semaphore.acquire().await),
-        None => None,
+    // collect data, streaming it to disk in row groups if flushing is enabled
+    let n_rows = match sink.flush_rows {
+        Some(flush_rows) if sink.format == FileFormat::Parquet => {
+            freeze_partition_batched(datatype, partition, query, source, &sink, paths, flush_rows)
+                .await?
+        }
+        _ => freeze_partition_whole(datatype, partition, query, source, &sink, paths).await?,
     };
 
-    // collect data
+    // update progress bar
+    if let Some(bar) = env.bar {
+        bar.inc(1);
+    }
+
+    Ok(n_rows)
+}
+
+/// collect partition into memory and write each dataframe once
+async fn freeze_partition_whole(
+    datatype: MetaDatatype,
+    partition: Partition,
+    query: Arc<Query>,
+    source: Arc<Source>,
+    sink: &FileOutput,
+    paths: HashMap<Datatype, PathBuf>,
+) -> Result<u64, CollectError> {
     let dfs = collect_partition(datatype, partition, query, source).await?;
 
     // write dataframes to disk
+    let mut n_rows = 0;
     for (datatype, mut df) in dfs {
+        n_rows += df.height() as u64;
         let path = paths.get(&datatype).ok_or_else(|| {
             CollectError::CollectError("could not get path for datatype".to_string())
         })?;
-        let result = dataframes::df_to_file(&mut df, path, &sink);
-        result.map_err(|_| CollectError::CollectError("error writing file".to_string()))?
+        let result = dataframes::df_to_file(&mut df, path, sink);
+        result.map_err(|_| CollectError::CollectError("error writing file".to_string()))?;
+        if sink.checksums {
+            crate::write_checksum(path)?;
+        }
     }
 
-    // update progress bar
-    if let Some(bar) = env.bar {
-        bar.inc(1);
+    Ok(n_rows)
+}
+
+/// collect partition while appending a row group every time flush_rows rows are collected,
+/// bounding memory use by the flush threshold rather than by the partition size
+async fn freeze_partition_batched(
+    datatype: MetaDatatype,
+    partition: Partition,
+    query: Arc<Query>,
+    source: Arc<Source>,
+    sink: &FileOutput,
+    paths: HashMap<Datatype, PathBuf>,
+    flush_rows: u64,
+) -> Result<u64, CollectError> {
+    let (sender, receiver) = mpsc::channel(1);
+    let flush = RowFlush { n_rows: flush_rows, sender };
+    let files = dataframes::BatchedParquetFiles::new(paths.clone(), &query.schemas, sink.clone());
+    let collect = collect_partition_with_flush(datatype, partition, query, source, Some(flush));
+    let n_rows = write_flushed(collect, receiver, files).await?;
+    if sink.checksums {
+        for path in paths.values().filter(|path| path.exists()) {
+            crate::write_checksum(path)?;
+        }
     }
+    Ok(n_rows)
+}
 
-    Ok(())
+/// write dataframes to files as `collect` flushes them, then the dataframes that it returns
+async fn write_flushed(
+    collect: impl Future<Output = Result<HashMap<Datatype, DataFrame>, CollectError>>,
+    mut receiver: mpsc::Receiver<HashMap<Datatype, DataFrame>>,
+    mut files: dataframes::BatchedParquetFiles,
+) -> Result<u64, CollectError> {
+    let write_error = |_| CollectError::CollectError("error writing file".to_string());
+    let write = async {
+        let mut n_rows = 0;
+        while let Some(dfs) = receiver.recv().await {
+            match files.write(dfs) {
+                Ok(n) => n_rows += n,
+                Err(e) => {
+                    // close the channel so that the collector fails instead of waiting to flush
+                    receiver.close();
+                    return Err(write_error(e))
+                }
+            }
+        }
+        Ok::<u64, CollectError>(n_rows)
+    };
+    let (remaining, n_flushed) = tokio::join!(collect, write);
+
+    let n_rows = n_flushed? + files.write(remaining?).map_err(write_error)?;
+    files.finish().map_err(write_error)?;
+    Ok(n_rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::NamedFrom;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn spawn_bounded_caps_concurrent_tasks() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let tasks = spawn_bounded((0..10).collect(), Some(3), |i: u64| {
+            spawned.fetch_add(1, Ordering::SeqCst);
+            let (running, max_running) = (running.clone(), max_running.clone());
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                i
+            }
+        });
+
+        // tasks are not spawned until the stream is polled
+        assert_eq!(spawned.load(Ordering::SeqCst), 0);
+        let mut results: Vec<u64> = tasks.map(|result| result.unwrap()).collect().await;
+        results.sort();
+        assert_eq!(results, (0..10).collect::<Vec<_>>());
+        assert_eq!(max_running.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn batched_write_error_stops_collection() {
+        let dir = std::env::temp_dir().join(format!("cryo_missing_dir_{}", std::process::id()));
+        let path = dir.join("blocks.parquet");
+        let file_output = FileOutput {
+            output_dir: dir,
+            prefix: "test".to_string(),
+            suffix: None,
+            subdirs: vec![],
+            overwrite: true,
+            format: FileFormat::Parquet,
+            row_group_size: None,
+            parquet_statistics: true,
+            parquet_compression: polars::prelude::ParquetCompression::Lz4Raw,
+            flush_rows: Some(1),
+            checksums: false,
+        };
+        let paths = HashMap::from([(Datatype::Blocks, path.clone())]);
+        let files = dataframes::BatchedParquetFiles::new(paths, &HashMap::new(), file_output);
+
+        // flush more batches than the channel holds, as a collector would
+        let (sender, receiver) = mpsc::channel(1);
+        let collect = async move {
+            for block_number in 0..4u64 {
+                let df = polars::df!("block_number" => &[block_number]).unwrap();
+                let dfs = HashMap::from([(Datatype::Blocks, df)]);
+                sender.send(dfs).await.map_err(|_| err("tokio mpsc send failure"))?;
+            }
+            Ok(HashMap::new())
+        };
+        let result =
+            tokio::time::timeout(Duration::from_secs(10), write_flushed(collect, receiver, files))
+                .await
+                .expect("write error should not hang collection");
+        assert!(
+            matches!(result, Err(CollectError::CollectError(message)) if message == "error writing file")
+        );
+        assert!(!path.with_extension("_batches").exists());
+    }
 }
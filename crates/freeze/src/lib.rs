@@ -12,12 +12,17 @@ mod datasets;
 mod freeze;
 mod multi_datasets;
 mod types;
+mod verify;
 // mod event_hash;
 pub use collect::collect;
 pub use datasets::*;
 pub use freeze::freeze;
 pub use multi_datasets::*;
 pub use types::*;
+pub use verify::{
+    checksum_path, file_checksum, verify_checksum, verify_file, write_checksum, Mismatch,
+    VerifySummary,
+};
 
 // pub fn display_topics() {
 //     // ...
@@ -1,4 +1,5 @@
 use crate::{datasets::transactions, types::collection::*, Datatype, *};
+use ethers::types::{Block, Transaction};
 use polars::prelude::*;
 use std::collections::HashMap;
 
@@ -22,17 +23,19 @@ impl ToDataFrames for BlocksAndTransactions {
 
 #[async_trait::async_trait]
 impl CollectByBlock for BlocksAndTransactions {
-    type Response = <Transactions as CollectByBlock>::Response;
+    type Response = (Block<Transaction>, Vec<transactions::TransactionAndReceipt>, bool);
 
     async fn extract(request: Params, source: Arc<Source>, query: Arc<Query>) -> R<Self::Response> {
-        <Transactions as CollectByBlock>::extract(request, source, query).await
+        transactions::extract_block_transactions(request, source, query).await
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
         let BlocksAndTransactions(blocks, transactions) = columns;
-        let (block, _, _) = response.clone();
+        let (block, transactions_with_receipts, exclude_failed) = response;
+        let timestamp = block.timestamp.as_u32();
         let schema = query.schemas.get_schema(&Datatype::Blocks)?;
         blocks::process_block(block, blocks, schema)?;
+        let response = (timestamp, transactions_with_receipts, exclude_failed);
         <Transactions as CollectByBlock>::transform(response, transactions, query)?;
         Ok(())
     }
@@ -41,9 +41,7 @@ impl CollectByBlock for CallTraceDerivatives {
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
-        let traces =
-            if query.exclude_failed { traces::filter_failed_traces(response) } else { response };
-        process_call_trace_derivatives(traces, columns, &query.schemas)
+        process_call_trace_derivatives(response, columns, query)
     }
 }
 
@@ -56,24 +54,33 @@ impl CollectByTransaction for CallTraceDerivatives {
     }
 
     fn transform(response: Self::Response, columns: &mut Self, query: &Arc<Query>) -> R<()> {
-        let traces =
-            if query.exclude_failed { traces::filter_failed_traces(response) } else { response };
-        process_call_trace_derivatives(traces, columns, &query.schemas)
+        process_call_trace_derivatives(response, columns, query)
     }
 }
 
 fn process_call_trace_derivatives(
     response: Vec<Trace>,
     columns: &mut CallTraceDerivatives,
-    schemas: &HashMap<Datatype, Table>,
+    query: &Arc<Query>,
 ) -> R<()> {
+    let schemas = &query.schemas;
+    let response = query.trace_status.filter(response);
+    let response =
+        if query.exclude_failed { traces::filter_failed_traces(response) } else { response };
     let CallTraceDerivatives(contracts, native_transfers, traces) = columns;
+    if schemas.contains_key(&Datatype::NativeTransfers) {
+        native_transfers::process_native_transfers(
+            response.clone(),
+            (&None, &None),
+            &query.trace_addresses,
+            native_transfers,
+            schemas,
+        )?;
+    }
+    let response = query.trace_addresses.filter(response);
     if schemas.contains_key(&Datatype::Contracts) {
         contracts::process_contracts(&response, contracts, schemas)?;
     }
-    if schemas.contains_key(&Datatype::NativeTransfers) {
-        native_transfers::process_native_transfers(&response, native_transfers, schemas)?;
-    }
     if schemas.contains_key(&Datatype::Traces) {
         traces::process_traces(&response, traces, schemas)?;
     }
@@ -154,7 +154,7 @@ fn add_balances(
     schema: &Table,
     index: &(Option<u32>, u32, Option<Vec<u8>>),
 ) -> R<()> {
-    let (from_value, to_value) = parse_pre_post(pre, post, U256::zero);
+    let (from_value, to_value) = from_to_or_default(pre, post);
     let (block_number, transaction_index, transaction_hash) = index;
     columns.n_rows += 1;
     store!(schema, columns, block_number, *block_number);
@@ -174,13 +174,7 @@ fn add_codes(
     schema: &Table,
     index: &(Option<u32>, u32, Option<Vec<u8>>),
 ) -> R<()> {
-    let blank = String::new();
-    let (from_value, to_value) = match (pre, post) {
-        (Some(pre), Some(post)) => (pre, post),
-        (Some(pre), None) => (pre, &blank),
-        (None, Some(post)) => (&blank, post),
-        (None, None) => (&blank, &blank),
-    };
+    let (from_value, to_value) = from_to_or_default(pre.as_deref(), post.as_deref());
     let (block_number, transaction_index, transaction_hash) = index;
     columns.n_rows += 1;
     store!(schema, columns, block_number, *block_number);
@@ -210,7 +204,7 @@ fn add_nonces(
     schema: &Table,
     index: &(Option<u32>, u32, Option<Vec<u8>>),
 ) -> R<()> {
-    let (from_value, to_value) = parse_pre_post(pre, post, U256::zero);
+    let (from_value, to_value) = from_to_or_default(pre, post);
     let (block_number, transaction_index, transaction_hash) = index;
     columns.n_rows += 1;
     store!(schema, columns, block_number, *block_number);
@@ -245,14 +239,8 @@ fn add_storages(
         .collect::<HashSet<_>>()
         .into_iter()
         .collect();
-    let blank = H256::zero();
     for slot in slots.into_iter() {
-        let (from, to) = match (pre.get(&slot), post.get(&slot)) {
-            (Some(pre), Some(post)) => (pre, post),
-            (Some(pre), None) => (pre, &blank),
-            (None, Some(post)) => (&blank, post),
-            (None, None) => (&blank, &blank),
-        };
+        let (from, to) = from_to_or_default(pre.get(&slot).copied(), post.get(&slot).copied());
         columns.n_rows += 1;
         store!(schema, columns, block_number, *block_number);
         store!(schema, columns, transaction_index, Some(*transaction_index as u64));
@@ -264,12 +252,3 @@ fn add_storages(
     }
     Ok(())
 }
-
-fn parse_pre_post<T>(pre: Option<T>, post: Option<T>, new: fn() -> T) -> (T, T) {
-    match (pre, post) {
-        (Some(pre), Some(post)) => (pre, post),
-        (Some(pre), None) => (pre, new()),
-        (None, Some(post)) => (new(), post),
-        (None, None) => (new(), new()),
-    }
-}
@@ -72,15 +72,16 @@ impl NumberChunk {
         }
     }
 
-    /// align boundaries of chunk to clean boundaries
+    /// align boundaries of chunk to clean boundaries, dropping partial chunks at the edges
     pub fn align(self, chunk_size: u64) -> Option<NumberChunk> {
         match self {
             NumberChunk::Numbers(numbers) => Some(NumberChunk::Numbers(numbers)),
             NumberChunk::Range(start, end) => {
-                let start = ((start + chunk_size - 1) / chunk_size) * chunk_size;
-                let end = (end / chunk_size) * chunk_size;
-                if end > start {
-                    Some(NumberChunk::Range(start, end))
+                let start = start.div_ceil(chunk_size) * chunk_size;
+                // end is inclusive, so the last full chunk ends one block before a boundary
+                let end_boundary = ((end + 1) / chunk_size) * chunk_size;
+                if end_boundary > start {
+                    Some(NumberChunk::Range(start, end_boundary - 1))
                 } else {
                     None
                 }
@@ -104,3 +105,38 @@ pub(crate) fn range_to_chunks(start: &u64, end: &u64, chunk_size: &u64) -> Vec<(
     }
     chunks
 }
+
+/// split range into chunks whose boundaries are multiples of chunk_size
+pub(crate) fn range_to_aligned_chunks(start: &u64, end: &u64, chunk_size: &u64) -> Vec<(u64, u64)> {
+    let mut chunks: Vec<(u64, u64)> = Vec::new();
+    let mut chunk_start = *start;
+    while chunk_start <= *end {
+        let chunk_end = ((chunk_start / chunk_size + 1) * chunk_size - 1).min(*end);
+        chunks.push((chunk_start, chunk_end));
+        chunk_start = chunk_end + 1;
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn align_keeps_full_chunks() {
+        let aligned = NumberChunk::Range(1106, 3105).align(1000);
+        assert!(matches!(aligned, Some(NumberChunk::Range(2000, 2999))));
+        let aligned = NumberChunk::Range(1000, 2999).align(1000);
+        assert!(matches!(aligned, Some(NumberChunk::Range(1000, 2999))));
+        assert!(NumberChunk::Range(1106, 1999).align(1000).is_none());
+    }
+
+    #[test]
+    fn aligned_chunks_split_at_boundaries() {
+        assert_eq!(
+            range_to_aligned_chunks(&1106, &3105, &1000),
+            vec![(1106, 1999), (2000, 2999), (3000, 3105)]
+        );
+        assert_eq!(range_to_aligned_chunks(&2000, &2999, &1000), vec![(2000, 2999)]);
+    }
+}
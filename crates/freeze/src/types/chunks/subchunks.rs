@@ -1,4 +1,8 @@
-use super::{chunk::BlockChunk, chunk_ops::ChunkData, number_chunk::range_to_chunks};
+use super::{
+    chunk::BlockChunk,
+    chunk_ops::ChunkData,
+    number_chunk::{range_to_aligned_chunks, range_to_chunks},
+};
 
 /// Aggregation operations related to chunks
 pub trait Subchunk {
@@ -7,6 +11,9 @@ pub trait Subchunk {
 
     /// divide into number of subchunks
     fn subchunk_by_count(&self, n_chunks: &u64) -> Vec<BlockChunk>;
+
+    /// divide into subchunks whose boundaries are multiples of chunk size
+    fn subchunk_by_aligned_size(&self, chunk_size: &u64) -> Vec<BlockChunk>;
 }
 
 impl Subchunk for BlockChunk {
@@ -30,6 +37,29 @@ impl Subchunk for BlockChunk {
         let chunk_size = (total_blocks + n_chunks - 1) / n_chunks;
         self.subchunk_by_size(&chunk_size)
     }
+
+    fn subchunk_by_aligned_size(&self, chunk_size: &u64) -> Vec<BlockChunk> {
+        match &self {
+            BlockChunk::Numbers(numbers) => {
+                let mut subchunks: Vec<Vec<u64>> = Vec::new();
+                for number in numbers.iter() {
+                    match subchunks.last_mut() {
+                        Some(last) if last[0] / chunk_size == number / chunk_size => {
+                            last.push(*number)
+                        }
+                        _ => subchunks.push(vec![*number]),
+                    }
+                }
+                subchunks.into_iter().map(BlockChunk::Numbers).collect()
+            }
+            BlockChunk::Range(start_block, end_block) => {
+                range_to_aligned_chunks(start_block, end_block, chunk_size)
+                    .iter()
+                    .map(|(start, end)| BlockChunk::Range(*start, *end))
+                    .collect()
+            }
+        }
+    }
 }
 
 impl Subchunk for Vec<BlockChunk> {
@@ -40,6 +70,10 @@ impl Subchunk for Vec<BlockChunk> {
     fn subchunk_by_count(&self, n_chunks: &u64) -> Vec<BlockChunk> {
         to_single_chunk(self).subchunk_by_count(n_chunks)
     }
+
+    fn subchunk_by_aligned_size(&self, chunk_size: &u64) -> Vec<BlockChunk> {
+        self.iter().flat_map(|chunk| chunk.subchunk_by_aligned_size(chunk_size)).collect()
+    }
 }
 
 fn to_single_chunk(chunks: &Vec<BlockChunk>) -> BlockChunk {
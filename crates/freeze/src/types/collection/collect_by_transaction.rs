@@ -1,4 +1,4 @@
-use super::collect_generic::{fetch_partition, join_partition_handles};
+use super::collect_generic::{fetch_partition, join_partition_handles, RowFlush};
 use crate::{CollectError, Datatype, Params, Partition, Query, Source, ToDataFrames};
 use polars::prelude::*;
 use std::collections::HashMap;
@@ -28,6 +28,7 @@ pub trait CollectByTransaction: 'static + Send + Default + ToDataFrames {
         source: Arc<Source>,
         query: Arc<Query>,
         inner_request_size: Option<u64>,
+        flush: Option<RowFlush>,
     ) -> R<HashMap<Datatype, DataFrame>> {
         let (sender, receiver) = mpsc::channel(1);
         let chain_id = source.chain_id;
@@ -40,7 +41,7 @@ pub trait CollectByTransaction: 'static + Send + Default + ToDataFrames {
             sender,
         )
         .await?;
-        let columns = Self::transform_channel(receiver, &query).await?;
+        let columns = Self::transform_channel(receiver, &query, flush, chain_id).await?;
         join_partition_handles(handles).await?;
         columns.create_dfs(&query.schemas, chain_id)
     }
@@ -49,6 +50,8 @@ pub trait CollectByTransaction: 'static + Send + Default + ToDataFrames {
     async fn transform_channel(
         mut receiver: mpsc::Receiver<R<Self::Response>>,
         query: &Arc<Query>,
+        flush: Option<RowFlush>,
+        chain_id: u64,
     ) -> R<Self> {
         let mut columns = Self::default();
         while let Some(message) = receiver.recv().await {
@@ -56,6 +59,7 @@ pub trait CollectByTransaction: 'static + Send + Default + ToDataFrames {
                 Ok(message) => Self::transform(message, &mut columns, query)?,
                 Err(e) => return Err(e),
             }
+            RowFlush::maybe_flush(&flush, &mut columns, query, chain_id).await?;
         }
         Ok(columns)
     }
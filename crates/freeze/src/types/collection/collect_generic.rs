@@ -4,17 +4,60 @@ use polars::prelude::*;
 use std::collections::HashMap;
 use tokio::{sync::mpsc, task};
 
+/// sends dataframes of a partition to a writer each time enough rows have been collected
+#[derive(Clone)]
+pub struct RowFlush {
+    /// number of collected rows that triggers a flush
+    pub n_rows: u64,
+    /// receiver of flushed dataframes
+    pub sender: mpsc::Sender<HashMap<Datatype, DataFrame>>,
+}
+
+impl RowFlush {
+    /// convert columns to dataframes and send them if flush threshold is reached
+    pub(crate) async fn maybe_flush<T: ToDataFrames + Default>(
+        flush: &Option<RowFlush>,
+        columns: &mut T,
+        query: &Arc<Query>,
+        chain_id: u64,
+    ) -> Result<(), CollectError> {
+        match flush {
+            Some(flush) if columns.n_rows() >= flush.n_rows => {
+                let dfs = std::mem::take(columns).create_dfs(&query.schemas, chain_id)?;
+                flush
+                    .sender
+                    .send(dfs)
+                    .await
+                    .map_err(|_| CollectError::CollectError("tokio mpsc send failure".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 /// collect single partition
 pub async fn collect_partition(
     datatype: MetaDatatype,
     partition: Partition,
     query: Arc<Query>,
     source: Arc<Source>,
+) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
+    collect_partition_with_flush(datatype, partition, query, source, None).await
+}
+
+/// collect single partition, sending partial dataframes to flush whenever its threshold is
+/// reached and returning the remaining rows
+pub async fn collect_partition_with_flush(
+    datatype: MetaDatatype,
+    partition: Partition,
+    query: Arc<Query>,
+    source: Arc<Source>,
+    flush: Option<RowFlush>,
 ) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
     match query.time_dimension {
-        TimeDimension::Blocks => collect_by_block(datatype, partition, source, query).await,
+        TimeDimension::Blocks => collect_by_block(datatype, partition, source, query, flush).await,
         TimeDimension::Transactions => {
-            collect_by_transaction(datatype, partition, source, query).await
+            collect_by_transaction(datatype, partition, source, query, flush).await
         }
     }
 }
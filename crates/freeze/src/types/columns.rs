@@ -1,4 +1,4 @@
-use crate::{CollectError, ColumnType, Datatype, Dim, Table};
+use crate::{Capability, CollectError, ColumnType, Datatype, Dim, Params, Table};
 use polars::prelude::*;
 use std::collections::HashMap;
 
@@ -12,11 +12,17 @@ macro_rules! store {
     };
 }
 
+/// typical number of transactions in a block, used to estimate requests made per transaction
+pub(crate) const ESTIMATED_TRANSACTIONS_PER_BLOCK: usize = 150;
+
 /// container for a dataset partition
 pub trait ColumnData: Default + crate::Dataset {
     /// column types
     fn column_types() -> indexmap::IndexMap<&'static str, ColumnType>;
 
+    /// descriptions of columns, taken from doc comments of the dataset's fields
+    fn column_descriptions() -> HashMap<&'static str, &'static str>;
+
     /// default columns extracted for Dataset
     fn base_default_columns() -> Vec<&'static str> {
         match Self::default_columns() {
@@ -67,6 +73,11 @@ pub trait ToDataFrames: Sized {
         schemas: &HashMap<Datatype, Table>,
         chain_id: u64,
     ) -> Result<HashMap<Datatype, DataFrame>, CollectError>;
+
+    /// number of rows collected so far, datasets reporting 0 are never flushed early
+    fn n_rows(&self) -> u64 {
+        0
+    }
 }
 
 /// Dataset manages collection and management of a particular datatype
@@ -110,4 +121,17 @@ pub trait Dataset: Sync + Send {
     fn arg_aliases() -> Option<HashMap<Dim, Dim>> {
         None
     }
+
+    /// estimated number of rpc requests needed to collect a single param set
+    ///
+    /// datasets that make a request per transaction of a block assume
+    /// `ESTIMATED_TRANSACTIONS_PER_BLOCK` transactions
+    fn estimate_requests(_params: &Params) -> usize {
+        1
+    }
+
+    /// trace methods of which the node must support at least one to collect dataset
+    fn required_capabilities() -> Vec<Capability> {
+        vec![]
+    }
 }
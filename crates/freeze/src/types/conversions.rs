@@ -1,6 +1,6 @@
-use crate::CollectError;
+use crate::{CollectError, ColumnEncoding};
 /// conversion operations
-use ethers::prelude::*;
+use ethers::{prelude::*, utils::hex};
 use prefix_hex;
 
 /// convert Bytes to u32
@@ -68,8 +68,19 @@ pub trait ToVecHex {
     /// Output type
     type Output;
 
-    /// Convert to Vec of hex String
+    /// Convert to Vec of 0x prefixed hex String
     fn to_vec_hex(&self) -> Self::Output;
+
+    /// Convert to Vec of hex String without the 0x prefix
+    fn to_vec_unprefixed_hex(&self) -> Self::Output;
+
+    /// Convert to Vec of hex String, prefixed unless the encoding is UnprefixedHex
+    fn to_vec_hex_as(&self, encoding: &ColumnEncoding) -> Self::Output {
+        match encoding {
+            ColumnEncoding::UnprefixedHex => self.to_vec_unprefixed_hex(),
+            _ => self.to_vec_hex(),
+        }
+    }
 }
 
 impl ToVecHex for Vec<Vec<u8>> {
@@ -78,6 +89,10 @@ impl ToVecHex for Vec<Vec<u8>> {
     fn to_vec_hex(&self) -> Self::Output {
         self.iter().map(|v| prefix_hex::encode(v.clone())).collect()
     }
+
+    fn to_vec_unprefixed_hex(&self) -> Self::Output {
+        self.iter().map(hex::encode).collect()
+    }
 }
 
 impl ToVecHex for Vec<Option<Vec<u8>>> {
@@ -86,4 +101,53 @@ impl ToVecHex for Vec<Option<Vec<u8>>> {
     fn to_vec_hex(&self) -> Self::Output {
         self.iter().map(|opt| opt.as_ref().map(|v| prefix_hex::encode(v.clone()))).collect()
     }
+
+    fn to_vec_unprefixed_hex(&self) -> Self::Output {
+        self.iter().map(|opt| opt.as_ref().map(hex::encode)).collect()
+    }
+}
+
+/// from and to values of a state diff, None where the value did not exist
+///
+/// unchanged values give (None, None)
+pub fn diff_from_to<T: Clone>(diff: &Diff<T>) -> (Option<T>, Option<T>) {
+    match diff {
+        Diff::Same => (None, None),
+        Diff::Born(value) => (None, Some(value.clone())),
+        Diff::Died(value) => (Some(value.clone()), None),
+        Diff::Changed(ChangedType { from, to }) => (Some(from.clone()), Some(to.clone())),
+    }
+}
+
+/// from and to values of a state diff, with missing values replaced by the empty value
+pub fn from_to_or_default<T: Default>(from: Option<T>, to: Option<T>) -> (T, T) {
+    (from.unwrap_or_default(), to.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_encodes_with_and_without_prefix() {
+        let values = vec![Some(vec![0xab, 0x01]), None];
+        assert_eq!(
+            values.to_vec_hex_as(&ColumnEncoding::Hex),
+            vec![Some("0xab01".to_string()), None]
+        );
+        assert_eq!(
+            values.to_vec_hex_as(&ColumnEncoding::UnprefixedHex),
+            vec![Some("ab01".to_string()), None]
+        );
+    }
+
+    #[test]
+    fn diffs_give_from_and_to_values() {
+        assert_eq!(diff_from_to::<U256>(&Diff::Same), (None, None));
+        assert_eq!(diff_from_to(&Diff::Born(U256::one())), (None, Some(U256::one())));
+        assert_eq!(diff_from_to(&Diff::Died(U256::one())), (Some(U256::one()), None));
+        let changed = Diff::Changed(ChangedType { from: U256::one(), to: U256::zero() });
+        assert_eq!(diff_from_to(&changed), (Some(U256::one()), Some(U256::zero())));
+        assert_eq!(from_to_or_default(None, Some(U256::one())), (U256::zero(), U256::one()));
+    }
 }
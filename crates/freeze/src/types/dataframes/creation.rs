@@ -14,7 +14,7 @@ macro_rules! with_series_binary {
     ($all_series:expr, $name:expr, $value:expr, $schema:expr) => {
         if $schema.has_column($name) {
             if let Some(ColumnType::Hex) = $schema.column_type($name) {
-                $all_series.push(Series::new($name, $value.to_vec_hex()));
+                $all_series.push(Series::new($name, $value.to_vec_hex_as(&$schema.binary_type)));
             } else {
                 $all_series.push(Series::new($name, $value));
             }
@@ -22,6 +22,33 @@ macro_rules! with_series_binary {
     };
 }
 
+/// convert a Vec of binary lists to a list Series, as hex if specified, and add to Vec<Series>
+#[macro_export]
+macro_rules! with_series_binary_list {
+    ($all_series:expr, $name:expr, $value:expr, $schema:expr) => {
+        if $schema.has_column($name) {
+            let (rows, inner_dtype): (Vec<Series>, DataType) =
+                if let Some(ColumnType::HexList) = $schema.column_type($name) {
+                    let rows = $value
+                        .iter()
+                        .map(|v| Series::new("", v.to_vec_hex_as(&$schema.binary_type)))
+                        .collect();
+                    (rows, DataType::Utf8)
+                } else {
+                    let rows = $value.iter().map(|v| Series::new("", v.clone())).collect();
+                    (rows, DataType::Binary)
+                };
+            let list_dtype = DataType::List(Box::new(inner_dtype));
+            let series = if rows.is_empty() {
+                Series::new_empty($name, &list_dtype)
+            } else {
+                Series::new($name, rows).cast(&list_dtype).map_err(CollectError::PolarsError)?
+            };
+            $all_series.push(series);
+        }
+    };
+}
+
 /// convert a Vec<U256> to variety of u256 Series representations
 #[macro_export]
 macro_rules! with_series_u256 {
@@ -33,8 +60,9 @@ macro_rules! with_series_u256 {
                 let name = name.as_str();
 
                 let converted: Vec<Vec<u8>> = $value.iter().map(|v| v.to_vec_u8()).collect();
-                if ColumnEncoding::Hex == $schema.binary_type {
-                    $all_series.push(Series::new(name, converted.to_vec_hex()));
+                if $schema.binary_type.is_hex() {
+                    $all_series
+                        .push(Series::new(name, converted.to_vec_hex_as(&$schema.binary_type)));
                 } else {
                     $all_series.push(Series::new(name, converted));
                 }
@@ -89,7 +117,12 @@ macro_rules! with_series_u256 {
 
             // decimal128
             if $schema.u256_types.contains(&U256Type::Decimal128) {
-                return Err(CollectError::CollectError("DECIMAL128 not implemented".to_string()))
+                let name = $name.to_string() + U256Type::Decimal128.suffix().as_str();
+                let name = name.as_str();
+
+                let converted: Vec<Option<i128>> =
+                    $value.iter().map(|v| v.to_decimal128()).collect();
+                $all_series.push(decimal128_series(name, converted));
             }
         }
     };
@@ -107,8 +140,9 @@ macro_rules! with_series_option_u256 {
 
                 let converted: Vec<Option<Vec<u8>>> =
                     $value.iter().map(|v| v.map(|x| x.to_vec_u8())).collect();
-                if ColumnEncoding::Hex == $schema.binary_type {
-                    $all_series.push(Series::new(name, converted.to_vec_hex()));
+                if $schema.binary_type.is_hex() {
+                    $all_series
+                        .push(Series::new(name, converted.to_vec_hex_as(&$schema.binary_type)));
                 } else {
                     $all_series.push(Series::new(name, converted));
                 }
@@ -170,7 +204,12 @@ macro_rules! with_series_option_u256 {
 
             // decimal128
             if $schema.u256_types.contains(&U256Type::Decimal128) {
-                return Err(CollectError::CollectError("DECIMAL128 not implemented".to_string()))
+                let name = $name.to_string() + U256Type::Decimal128.suffix().as_str();
+                let name = name.as_str();
+
+                let converted: Vec<Option<i128>> =
+                    $value.iter().map(|v| v.and_then(|x| x.to_decimal128())).collect();
+                $all_series.push(decimal128_series(name, converted));
             }
         }
     };
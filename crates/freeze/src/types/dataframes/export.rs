@@ -1,8 +1,12 @@
-use std::path::Path;
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::{Path, PathBuf},
+};
 
-use polars::prelude::*;
+use polars::{io::parquet::BatchedWriter, prelude::*};
 
-use crate::types::{FileError, FileOutput};
+use crate::types::{Datatype, FileError, FileOutput, Table};
 
 /// write polars dataframe to file
 pub(crate) fn df_to_file(
@@ -15,6 +19,7 @@ pub(crate) fn df_to_file(
         Some("parquet") => df_to_parquet(df, &tmp_filename, file_output),
         Some("csv") => df_to_csv(df, &tmp_filename),
         Some("json") => df_to_json(df, &tmp_filename),
+        Some("arrow") => df_to_ipc(df, &tmp_filename),
         _ => return Err(FileError::FileWriteError),
     };
     match result {
@@ -23,6 +28,152 @@ pub(crate) fn df_to_file(
     }
 }
 
+/// parquet files of a partition that dataframes are appended to as row groups
+///
+/// each appended dataframe is only sorted within itself, so files of datatypes with sort columns
+/// are sorted as a whole by a streaming sort when they are finished. temporary files of
+/// unfinished writes are removed when this is dropped
+pub(crate) struct BatchedParquetFiles {
+    paths: HashMap<Datatype, PathBuf>,
+    sort_columns: HashMap<Datatype, Vec<String>>,
+    file_output: FileOutput,
+    writers: HashMap<Datatype, BatchedWriter<File>>,
+}
+
+impl BatchedParquetFiles {
+    pub(crate) fn new(
+        paths: HashMap<Datatype, PathBuf>,
+        schemas: &HashMap<Datatype, Table>,
+        file_output: FileOutput,
+    ) -> Self {
+        let sort_columns = schemas
+            .iter()
+            .filter_map(|(datatype, schema)| Some((*datatype, schema.sort_columns.clone()?)))
+            .filter(|(_, sort_columns)| !sort_columns.is_empty())
+            .collect();
+        BatchedParquetFiles { paths, sort_columns, file_output, writers: HashMap::new() }
+    }
+
+    /// append dataframes to their files, returning number of rows written
+    pub(crate) fn write(&mut self, dfs: HashMap<Datatype, DataFrame>) -> Result<u64, FileError> {
+        let mut n_rows = 0;
+        for (datatype, df) in dfs.into_iter() {
+            if df.height() == 0 && self.writers.contains_key(&datatype) {
+                continue
+            }
+            let writer = match self.writers.entry(datatype) {
+                std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    let path = self.paths.get(&datatype).ok_or(FileError::FileWriteError)?;
+                    let file =
+                        File::create(batches_path(path)).map_err(|_e| FileError::FileWriteError)?;
+                    let writer = ParquetWriter::new(file)
+                        .with_statistics(self.file_output.parquet_statistics)
+                        .with_compression(self.file_output.parquet_compression)
+                        .batched(&df.schema())
+                        .map_err(|_e| FileError::FileWriteError)?;
+                    entry.insert(writer)
+                }
+            };
+            // the batched writer writes each dataframe as one row group, so split it
+            let row_group_size = self.file_output.row_group_size.unwrap_or(usize::MAX).max(1);
+            for i in 0..df.height().div_ceil(row_group_size).max(1) {
+                let row_group = df.slice((i * row_group_size) as i64, row_group_size);
+                writer.write_batch(&row_group).map_err(|_e| FileError::FileWriteError)?;
+            }
+            n_rows += df.height() as u64;
+        }
+        Ok(n_rows)
+    }
+
+    /// finish writing files and move them to their final paths
+    pub(crate) fn finish(mut self) -> Result<(), FileError> {
+        let datatypes: Vec<Datatype> = self.writers.keys().copied().collect();
+        for datatype in datatypes {
+            let result = self.finish_file(datatype);
+            if result.is_err() {
+                if let Some(path) = self.paths.get(&datatype) {
+                    remove_tmp_files(path);
+                }
+            }
+            result?;
+        }
+        Ok(())
+    }
+
+    fn finish_file(&mut self, datatype: Datatype) -> Result<(), FileError> {
+        let mut writer = self.writers.remove(&datatype).ok_or(FileError::FileWriteError)?;
+        let path = self.paths.get(&datatype).ok_or(FileError::FileWriteError)?;
+        writer.finish().map_err(|_e| FileError::FileWriteError)?;
+        drop(writer);
+        match self.sort_columns.get(&datatype) {
+            Some(sort_columns) => {
+                sort_parquet(&batches_path(path), path, sort_columns, &self.file_output)?;
+                std::fs::remove_file(batches_path(path)).map_err(|_e| FileError::FileWriteError)
+            }
+            None => {
+                std::fs::rename(batches_path(path), path).map_err(|_e| FileError::FileWriteError)
+            }
+        }
+    }
+}
+
+impl Drop for BatchedParquetFiles {
+    fn drop(&mut self) {
+        for datatype in self.writers.keys() {
+            if let Some(path) = self.paths.get(datatype) {
+                remove_tmp_files(path);
+            }
+        }
+    }
+}
+
+/// path that batches are appended to before the file is finished
+fn batches_path(path: &Path) -> PathBuf {
+    path.with_extension("_batches")
+}
+
+fn remove_tmp_files(path: &Path) {
+    let _ = std::fs::remove_file(batches_path(path));
+    let _ = std::fs::remove_file(path.with_extension("_tmp"));
+}
+
+/// sort parquet file by the sort columns it contains without loading it into memory
+fn sort_parquet(
+    source: &Path,
+    destination: &Path,
+    sort_columns: &[String],
+    file_output: &FileOutput,
+) -> Result<(), FileError> {
+    let tmp_filename = destination.with_extension("_tmp");
+    let lf = LazyFrame::scan_parquet(source, ScanArgsParquet::default())
+        .map_err(|_e| FileError::FileWriteError)?;
+    let schema = lf.schema().map_err(|_e| FileError::FileWriteError)?;
+    let sort_columns: Vec<Expr> = sort_columns
+        .iter()
+        .filter(|column| schema.contains(column))
+        .map(|column| col(column))
+        .collect();
+    if sort_columns.is_empty() {
+        return std::fs::copy(source, destination)
+            .map(|_| ())
+            .map_err(|_e| FileError::FileWriteError)
+    }
+    let descending = vec![false; sort_columns.len()];
+    let options = ParquetWriteOptions {
+        compression: file_output.parquet_compression,
+        statistics: file_output.parquet_statistics,
+        row_group_size: file_output.row_group_size,
+        data_pagesize_limit: None,
+        maintain_order: true,
+    };
+    // polars can only stream sorts that do not maintain the order of ties
+    lf.sort_by_exprs(sort_columns, descending, false, false)
+        .sink_parquet(tmp_filename.clone(), options)
+        .map_err(|_e| FileError::FileWriteError)?;
+    std::fs::rename(tmp_filename, destination).map_err(|_e| FileError::FileWriteError)
+}
+
 /// write polars dataframe to parquet file
 fn df_to_parquet(
     df: &mut DataFrame,
@@ -60,3 +211,119 @@ fn df_to_json(df: &mut DataFrame, filename: &Path) -> Result<(), FileError> {
         _ => Ok(()),
     }
 }
+
+/// write polars dataframe to arrow ipc file
+fn df_to_ipc(df: &mut DataFrame, filename: &Path) -> Result<(), FileError> {
+    let file = std::fs::File::create(filename).map_err(|_e| FileError::FileWriteError)?;
+    let result = IpcWriter::new(file).finish(df);
+    match result {
+        Err(_e) => Err(FileError::FileWriteError),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ColumnEncoding;
+
+    #[test]
+    fn arrow_ipc_round_trip() {
+        let mut df = df!(
+            "block_number" => &[1u64, 2, 3],
+            "block_hash" => &[b"a".as_slice(), b"b".as_slice(), b"c".as_slice()],
+        )
+        .unwrap();
+        let path = std::env::temp_dir()
+            .join(format!("cryo_arrow_ipc_round_trip_{}.arrow", std::process::id()));
+        df_to_ipc(&mut df, &path).unwrap();
+
+        let file = std::fs::File::open(&path).unwrap();
+        let read = IpcReader::new(file).finish().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(read.frame_equal(&df));
+    }
+
+    fn batched_file_output(row_group_size: Option<usize>) -> FileOutput {
+        FileOutput {
+            output_dir: std::env::temp_dir(),
+            prefix: "test".to_string(),
+            suffix: None,
+            subdirs: vec![],
+            overwrite: true,
+            format: crate::FileFormat::Parquet,
+            row_group_size,
+            parquet_statistics: true,
+            parquet_compression: ParquetCompression::Lz4Raw,
+            flush_rows: Some(2),
+            checksums: false,
+        }
+    }
+
+    fn blocks_schemas(sort: Option<Vec<String>>) -> HashMap<Datatype, Table> {
+        let columns = Some(vec!["block_number".to_string()]);
+        let schema = Datatype::Blocks
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &columns, sort, None)
+            .unwrap();
+        HashMap::from([(Datatype::Blocks, schema)])
+    }
+
+    fn n_row_groups(path: &Path) -> usize {
+        let mut file = File::open(path).unwrap();
+        polars::export::arrow::io::parquet::read::read_metadata(&mut file).unwrap().row_groups.len()
+    }
+
+    #[test]
+    fn batched_parquet_appends_row_groups() {
+        let path = std::env::temp_dir()
+            .join(format!("cryo_batched_parquet_{}.parquet", std::process::id()));
+        let paths = HashMap::from([(Datatype::Blocks, path.clone())]);
+        let schemas = blocks_schemas(None);
+        let mut files = BatchedParquetFiles::new(paths, &schemas, batched_file_output(Some(2)));
+        let first = df!("block_number" => &[1u64, 2, 3]).unwrap();
+        let second = df!("block_number" => &[4u64]).unwrap();
+        assert_eq!(files.write(HashMap::from([(Datatype::Blocks, first)])).unwrap(), 3);
+        assert_eq!(files.write(HashMap::from([(Datatype::Blocks, second)])).unwrap(), 1);
+        files.finish().unwrap();
+
+        let read = ParquetReader::new(File::open(&path).unwrap()).finish().unwrap();
+        let row_groups = n_row_groups(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read, df!("block_number" => &[1u64, 2, 3, 4]).unwrap());
+        assert_eq!(row_groups, 3);
+    }
+
+    #[test]
+    fn batched_parquet_sorts_across_batches() {
+        let path = std::env::temp_dir()
+            .join(format!("cryo_batched_parquet_sorted_{}.parquet", std::process::id()));
+        let paths = HashMap::from([(Datatype::Blocks, path.clone())]);
+        let schemas = blocks_schemas(Some(Datatype::Blocks.default_sort()));
+        let mut files = BatchedParquetFiles::new(paths, &schemas, batched_file_output(None));
+        let first = df!("block_number" => &[3u64, 4]).unwrap();
+        let second = df!("block_number" => &[1u64, 2]).unwrap();
+        files.write(HashMap::from([(Datatype::Blocks, first)])).unwrap();
+        files.write(HashMap::from([(Datatype::Blocks, second)])).unwrap();
+        files.finish().unwrap();
+
+        let read = ParquetReader::new(File::open(&path).unwrap()).finish().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read, df!("block_number" => &[1u64, 2, 3, 4]).unwrap());
+        assert!(!batches_path(&path).exists());
+    }
+
+    #[test]
+    fn unfinished_batched_parquet_removes_tmp_files() {
+        let path = std::env::temp_dir()
+            .join(format!("cryo_batched_parquet_unfinished_{}.parquet", std::process::id()));
+        let paths = HashMap::from([(Datatype::Blocks, path.clone())]);
+        let schemas = blocks_schemas(None);
+        let mut files = BatchedParquetFiles::new(paths, &schemas, batched_file_output(None));
+        let df = df!("block_number" => &[1u64]).unwrap();
+        files.write(HashMap::from([(Datatype::Blocks, df)])).unwrap();
+        assert!(batches_path(&path).exists());
+        drop(files);
+        assert!(!batches_path(&path).exists());
+        assert!(!path.exists());
+    }
+}
@@ -7,12 +7,61 @@ pub(crate) trait SortableDataFrame {
 }
 
 impl SortableDataFrame for Result<DataFrame, CollectError> {
+    /// sort by the schema's sort columns that were collected
+    ///
+    /// the sort is stable, so rows that tie keep the order of the response they came from and
+    /// output does not depend on the order in which concurrent requests completed
     fn sort_by_schema(self, schema: &Table) -> Self {
         match (self, &schema.sort_columns) {
             (Ok(df), Some(sort_columns)) => {
-                df.sort(sort_columns, false, false).map_err(CollectError::PolarsError)
+                let sort_columns: Vec<&str> = sort_columns
+                    .iter()
+                    .map(|column| column.as_str())
+                    .filter(|column| df.column(column).is_ok())
+                    .collect();
+                if sort_columns.is_empty() {
+                    return Ok(df)
+                }
+                df.sort(sort_columns, false, true).map_err(CollectError::PolarsError)
             }
             (df, _) => df,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{datasets::process_block, Blocks, ColumnEncoding, Datatype, ToDataFrames};
+    use ethers::prelude::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn sorts_rows_collected_out_of_order() {
+        let columns = Some(vec!["block_number".to_string(), "block_hash".to_string()]);
+        let sort = Some(Datatype::Blocks.default_sort());
+        let schema = Datatype::Blocks
+            .table_schema(&[], &ColumnEncoding::Binary, &None, &None, &columns, sort, None)
+            .unwrap();
+
+        // blocks arrive in the order their requests complete
+        let mut blocks = Blocks::default();
+        for number in [3u64, 1, 4, 2] {
+            let block = Block::<TxHash> {
+                number: Some(number.into()),
+                hash: Some(H256::from_low_u64_be(number)),
+                ..Default::default()
+            };
+            process_block(block, &mut blocks, &schema).unwrap();
+        }
+        let schemas = HashMap::from([(Datatype::Blocks, schema)]);
+        let df = blocks.create_dfs(&schemas, 1).unwrap().remove(&Datatype::Blocks).unwrap();
+
+        let numbers: Vec<_> =
+            df.column("block_number").unwrap().u32().unwrap().into_iter().collect();
+        assert_eq!(numbers, vec![Some(1), Some(2), Some(3), Some(4)]);
+        let first_hash =
+            df.column("block_hash").unwrap().binary().unwrap().get(0).unwrap().to_vec();
+        assert_eq!(first_hash, H256::from_low_u64_be(1).as_bytes().to_vec());
+    }
+}
@@ -27,7 +27,9 @@ impl ToU256Series for Vec<U256> {
             U256Type::Binary => {
                 let converted: Vec<Vec<u8>> = self.iter().map(|v| v.to_vec_u8()).collect();
                 match column_encoding {
-                    ColumnEncoding::Hex => Ok(Series::new(name, converted.to_vec_hex())),
+                    ColumnEncoding::Hex | ColumnEncoding::UnprefixedHex => {
+                        Ok(Series::new(name, converted.to_vec_hex_as(column_encoding)))
+                    }
                     ColumnEncoding::Binary => Ok(Series::new(name, converted)),
                 }
             }
@@ -54,7 +56,8 @@ impl ToU256Series for Vec<U256> {
                 Ok(Series::new(name, converted))
             }
             U256Type::Decimal128 => {
-                Err(CollectError::CollectError("DECIMAL128 not implemented".to_string()))
+                let converted: Vec<Option<i128>> = self.iter().map(|v| v.to_decimal128()).collect();
+                Ok(decimal128_series(name, converted))
             }
         }
     }
@@ -75,7 +78,9 @@ impl ToU256Series for Vec<Option<U256>> {
                 let converted: Vec<Option<Vec<u8>>> =
                     self.iter().map(|v| v.map(|x| x.to_vec_u8())).collect();
                 match column_encoding {
-                    ColumnEncoding::Hex => Ok(Series::new(name, converted.to_vec_hex())),
+                    ColumnEncoding::Hex | ColumnEncoding::UnprefixedHex => {
+                        Ok(Series::new(name, converted.to_vec_hex_as(column_encoding)))
+                    }
                     ColumnEncoding::Binary => Ok(Series::new(name, converted)),
                 }
             }
@@ -109,7 +114,9 @@ impl ToU256Series for Vec<Option<U256>> {
                 Ok(Series::new(name, converted))
             }
             U256Type::Decimal128 => {
-                Err(CollectError::CollectError("DECIMAL128 not implemented".to_string()))
+                let converted: Vec<Option<i128>> =
+                    self.iter().map(|v| v.and_then(|x| x.to_decimal128())).collect();
+                Ok(decimal128_series(name, converted))
             }
         }
     }
@@ -129,7 +136,9 @@ impl ToU256Series for Vec<I256> {
             U256Type::Binary => {
                 let converted: Vec<Vec<u8>> = self.iter().map(|v| v.to_vec_u8()).collect();
                 match column_encoding {
-                    ColumnEncoding::Hex => Ok(Series::new(name, converted.to_vec_hex())),
+                    ColumnEncoding::Hex | ColumnEncoding::UnprefixedHex => {
+                        Ok(Series::new(name, converted.to_vec_hex_as(column_encoding)))
+                    }
                     ColumnEncoding::Binary => Ok(Series::new(name, converted)),
                 }
             }
@@ -156,7 +165,8 @@ impl ToU256Series for Vec<I256> {
                 Ok(Series::new(name, converted))
             }
             U256Type::Decimal128 => {
-                Err(CollectError::CollectError("DECIMAL128 not implemented".to_string()))
+                let converted: Vec<Option<i128>> = self.iter().map(|v| v.to_decimal128()).collect();
+                Ok(decimal128_series(name, converted))
             }
         }
     }
@@ -177,7 +187,9 @@ impl ToU256Series for Vec<Option<I256>> {
                 let converted: Vec<Option<Vec<u8>>> =
                     self.iter().map(|v| v.map(|x| x.to_vec_u8())).collect();
                 match column_encoding {
-                    ColumnEncoding::Hex => Ok(Series::new(name, converted.to_vec_hex())),
+                    ColumnEncoding::Hex | ColumnEncoding::UnprefixedHex => {
+                        Ok(Series::new(name, converted.to_vec_hex_as(column_encoding)))
+                    }
                     ColumnEncoding::Binary => Ok(Series::new(name, converted)),
                 }
             }
@@ -211,8 +223,64 @@ impl ToU256Series for Vec<Option<I256>> {
                 Ok(Series::new(name, converted))
             }
             U256Type::Decimal128 => {
-                Err(CollectError::CollectError("DECIMAL128 not implemented".to_string()))
+                let converted: Vec<Option<i128>> =
+                    self.iter().map(|v| v.and_then(|x| x.to_decimal128())).collect();
+                Ok(decimal128_series(name, converted))
             }
         }
     }
 }
+
+/// most digits a Decimal128 column can hold
+const DECIMAL128_PRECISION: u32 = 38;
+
+/// Converts U256-like data to an i128 for storage in a Decimal128 column
+pub trait ToDecimal128 {
+    /// convert to i128, returns None if the value has more than 38 digits
+    fn to_decimal128(&self) -> Option<i128>;
+}
+
+impl ToDecimal128 for U256 {
+    fn to_decimal128(&self) -> Option<i128> {
+        if *self < U256::exp10(DECIMAL128_PRECISION as usize) {
+            Some(self.as_u128() as i128)
+        } else {
+            None
+        }
+    }
+}
+
+impl ToDecimal128 for I256 {
+    fn to_decimal128(&self) -> Option<i128> {
+        let value = self.unsigned_abs().to_decimal128()?;
+        Some(if self.is_negative() { -value } else { value })
+    }
+}
+
+/// build a Decimal128 Series with scale 0, values that do not fit are null
+pub fn decimal128_series(name: &str, values: Vec<Option<i128>>) -> Series {
+    Int128Chunked::from_iter_options(name, values.into_iter())
+        .into_decimal_unchecked(Some(DECIMAL128_PRECISION as usize), 0)
+        .into_series()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_u256_to_decimal128() {
+        let largest = U256::exp10(38) - 1;
+        let values = vec![U256::from(12345), largest, U256::exp10(38), U256::MAX];
+        let series = values
+            .to_u256_series("value".to_string(), U256Type::Decimal128, &ColumnEncoding::Binary)
+            .unwrap();
+
+        assert_eq!(series.name(), "value_d128");
+        assert_eq!(series.dtype(), &DataType::Decimal(Some(38), Some(0)));
+        assert_eq!(series.null_count(), 2);
+        let i128s: Vec<_> = values.iter().map(|v| v.to_decimal128()).collect();
+        assert_eq!(i128s, vec![Some(12345), Some(largest.as_u128() as i128), None, None]);
+        assert_eq!(I256::from(-7).to_decimal128(), Some(-7));
+    }
+}
@@ -62,6 +62,13 @@ macro_rules! define_datatypes {
                 }
             }
 
+            /// column descriptions of datatype
+            pub fn column_descriptions(&self) -> std::collections::HashMap<&'static str, &'static str> {
+                match *self {
+                    $(Datatype::$datatype => $datatype::column_descriptions(),)*
+                }
+            }
+
             /// whether to use block ranges instead of individual blocks
             pub fn use_block_ranges(&self) -> bool {
                 match *self {
@@ -90,6 +97,20 @@ macro_rules! define_datatypes {
                 }
             }
 
+            /// estimated number of rpc requests needed to collect a single param set
+            pub fn estimate_requests(&self, params: &Params) -> usize {
+                match *self {
+                    $(Datatype::$datatype => $datatype::estimate_requests(params),)*
+                }
+            }
+
+            /// trace methods of which the node must support at least one to collect datatype
+            pub fn required_capabilities(&self) -> Vec<Capability> {
+                match *self {
+                    $(Datatype::$datatype => $datatype::required_capabilities(),)*
+                }
+            }
+
             /// whether datatype can be collected by block
             pub fn can_collect_by_block(&self) -> bool {
                 match *self {
@@ -110,7 +131,8 @@ macro_rules! define_datatypes {
             datatype: MetaDatatype,
             partition: Partition,
             source: Arc<Source>,
-            query: Arc<Query>
+            query: Arc<Query>,
+            flush: Option<RowFlush>,
         ) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
             let task = match datatype {
                 MetaDatatype::Scalar(datatype) => {
@@ -121,25 +143,25 @@ macro_rules! define_datatypes {
                     };
                     match datatype {
                     $(
-                        Datatype::$datatype => $datatype::collect_by_block(partition, source, query, inner_request_size),
+                        Datatype::$datatype => $datatype::collect_by_block(partition, source, query, inner_request_size, flush),
                     )*
                     }
                 },
                 MetaDatatype::Multi(datatype) => match datatype {
                     MultiDatatype::BlocksAndTransactions => {
-                        BlocksAndTransactions::collect_by_block(partition, source, query, None)
+                        BlocksAndTransactions::collect_by_block(partition, source, query, None, flush)
                     }
                     MultiDatatype::CallTraceDerivatives => {
-                        CallTraceDerivatives::collect_by_block(partition, source, query, None)
+                        CallTraceDerivatives::collect_by_block(partition, source, query, None, flush)
                     }
                     MultiDatatype::GethStateDiffs => {
-                        GethStateDiffs::collect_by_block(partition, source, query, None)
+                        GethStateDiffs::collect_by_block(partition, source, query, None, flush)
                     },
                     MultiDatatype::StateDiffs => {
-                        StateDiffs::collect_by_block(partition, source, query, None)
+                        StateDiffs::collect_by_block(partition, source, query, None, flush)
                     },
                     MultiDatatype::StateReads => {
-                        StateReads::collect_by_block(partition, source, query, None)
+                        StateReads::collect_by_block(partition, source, query, None, flush)
                     },
                 },
             };
@@ -152,6 +174,7 @@ macro_rules! define_datatypes {
             partition: Partition,
             source: Arc<Source>,
             query: Arc<Query>,
+            flush: Option<RowFlush>,
         ) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
             let task = match datatype {
                 MetaDatatype::Scalar(datatype) => {
@@ -162,7 +185,7 @@ macro_rules! define_datatypes {
                     };
                     match datatype {
                     $(
-                        Datatype::$datatype => $datatype::collect_by_transaction(partition, source, query, inner_request_size),
+                        Datatype::$datatype => $datatype::collect_by_transaction(partition, source, query, inner_request_size, flush),
                     )*
                     }
                 },
@@ -170,19 +193,19 @@ macro_rules! define_datatypes {
                     let inner_request_size = None;
                     match datatype {
                         MultiDatatype::BlocksAndTransactions => {
-                            BlocksAndTransactions::collect_by_transaction(partition, source, query, inner_request_size)
+                            BlocksAndTransactions::collect_by_transaction(partition, source, query, inner_request_size, flush)
                         }
                         MultiDatatype::CallTraceDerivatives => {
-                            CallTraceDerivatives::collect_by_transaction(partition, source, query, None)
+                            CallTraceDerivatives::collect_by_transaction(partition, source, query, None, flush)
                         }
                         MultiDatatype::GethStateDiffs => {
-                            GethStateDiffs::collect_by_transaction(partition, source, query, None)
+                            GethStateDiffs::collect_by_transaction(partition, source, query, None, flush)
                         },
                         MultiDatatype::StateDiffs => {
-                            StateDiffs::collect_by_transaction(partition, source, query, inner_request_size)
+                            StateDiffs::collect_by_transaction(partition, source, query, inner_request_size, flush)
                         },
                         MultiDatatype::StateReads => {
-                            StateReads::collect_by_transaction(partition, source, query, inner_request_size)
+                            StateReads::collect_by_transaction(partition, source, query, inner_request_size, flush)
                         },
                     }
                 },
@@ -1,4 +1,5 @@
 use super::{multi::MultiDatatype, scalar::Datatype};
+use crate::Params;
 
 /// datatype representing either a Datatype or MultiDatatype
 #[derive(Clone, Debug, serde::Serialize)]
@@ -17,6 +18,16 @@ impl MetaDatatype {
             MetaDatatype::Multi(multi_datatype) => multi_datatype.datatypes(),
         }
     }
+
+    /// estimated number of rpc requests needed to collect a single param set
+    /// datatypes of a MultiDatatype share their requests
+    pub fn estimate_requests(&self, params: &Params) -> usize {
+        self.datatypes()
+            .iter()
+            .map(|datatype| datatype.estimate_requests(params))
+            .max()
+            .unwrap_or(0)
+    }
 }
 
 /// cluster datatypes into MultiDatatype / ScalarDatatype groups
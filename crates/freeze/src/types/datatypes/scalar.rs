@@ -3,11 +3,14 @@ use polars::prelude::*;
 use std::collections::HashMap;
 
 define_datatypes!(
+    AccessLists,
     AddressAppearances,
+    AddressTraces,
     BalanceDiffs,
     BalanceReads,
     Balances,
     Blocks,
+    Calls,
     CodeDiffs,
     CodeReads,
     Codes,
@@ -32,13 +35,18 @@ define_datatypes!(
     NonceDiffs,
     NonceReads,
     Nonces,
+    OpcodeReads,
+    PendingTransactions,
     Slots,
     StorageDiffs,
     StorageReads,
     Traces,
     TraceCalls,
+    TransactionReceipts,
     Transactions,
+    Uncles,
     VmTraces,
+    Withdrawals,
 );
 
 impl Datatype {
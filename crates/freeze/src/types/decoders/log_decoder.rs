@@ -1,6 +1,8 @@
 use crate::{err, CollectError, ColumnEncoding, ToU256Series, U256Type};
-use ethers::prelude::*;
-use ethers_core::abi::{AbiEncode, EventParam, HumanReadableParser, ParamType, RawLog, Token};
+use ethers::{prelude::*, utils::hex};
+use ethers_core::abi::{
+    AbiEncode, EventExt, EventParam, HumanReadableParser, ParamType, RawLog, Token,
+};
 use polars::prelude::*;
 use std::collections::HashSet;
 
@@ -29,24 +31,83 @@ impl LogDecoder {
         }
     }
 
+    /// create a new LogDecoder from a json abi
+    /// the json can be either a full contract abi or a single event entry, if the abi contains
+    /// multiple events then `event_name` selects which one to decode
+    pub fn from_abi_json(json: &str, event_name: Option<&str>) -> Result<Self, String> {
+        let events: Vec<abi::Event> = match serde_json::from_str::<abi::Abi>(json) {
+            Ok(abi) => abi.events().cloned().collect(),
+            Err(_) => match serde_json::from_str::<abi::Event>(json) {
+                Ok(event) => vec![event],
+                Err(e) => return Err(format!("could not parse event abi: {}", e)),
+            },
+        };
+        let mut events = events
+            .into_iter()
+            .filter(|event| event_name.map(|name| event.name == name).unwrap_or(true));
+        let event = match (events.next(), events.next()) {
+            (Some(event), None) => event,
+            (None, _) => return Err("no matching event found in abi".to_string()),
+            (Some(_), Some(_)) => {
+                return Err("abi contains multiple events, specify which to decode".to_string())
+            }
+        };
+        Ok(Self { raw: event.abi_signature(), event })
+    }
+
+    /// whether a log is an instance of the decoded event
+    pub fn matches(&self, log: &Log) -> bool {
+        self.event.parse_log(RawLog::from(log.clone())).is_ok()
+    }
+
     /// get field names of event inputs
     pub fn field_names(&self) -> Vec<String> {
         self.event.inputs.iter().map(|i| i.name.clone()).collect()
     }
 
+    /// whether an event param is stored as the keccak hash of its value in a topic
+    /// indexed dynamic types (string, bytes, arrays, tuples) cannot be recovered from the log
+    pub fn is_hashed(param: &EventParam) -> bool {
+        param.indexed &&
+            matches!(
+                param.kind,
+                ParamType::String |
+                    ParamType::Bytes |
+                    ParamType::Array(_) |
+                    ParamType::FixedArray(_, _) |
+                    ParamType::Tuple(_)
+            )
+    }
+
     /// converts from a log type to an abi token type
+    /// hashed params are stored as their 32 byte topic under `<name>_hash`, along with a
+    /// `<name>_hashed` flag
     /// this function assumes all logs are of the same type and skips fields if they don't match the
     /// passed event definition
     pub fn parse_log_from_event(&self, logs: Vec<Log>) -> indexmap::IndexMap<String, Vec<Token>> {
         let mut map: indexmap::IndexMap<String, Vec<Token>> = indexmap::IndexMap::new();
         let known_keys =
             self.event.inputs.clone().into_iter().map(|i| i.name).collect::<HashSet<String>>();
+        let hashed_keys = self
+            .event
+            .inputs
+            .iter()
+            .filter(|i| Self::is_hashed(i))
+            .map(|i| i.name.clone())
+            .collect::<HashSet<String>>();
 
         for log in logs {
             match self.event.parse_log(RawLog::from(log)) {
                 Ok(log) => {
                     for param in log.params {
-                        if known_keys.contains(param.name.as_str()) {
+                        if hashed_keys.contains(param.name.as_str()) {
+                            map.entry(format!("{}_hash", param.name))
+                                .or_default()
+                                .push(param.value);
+                            map.entry(format!("{}_hashed", param.name))
+                                .or_default()
+                                .push(Token::Bool(true));
+                        } else if known_keys.contains(param.name.as_str()) {
                             let tokens = map.entry(param.name).or_default();
                             tokens.push(param.value);
                         }
@@ -93,14 +154,17 @@ impl LogDecoder {
                 Token::Address(a) => match column_encoding {
                     ColumnEncoding::Binary => bytes.push(a.to_fixed_bytes().into()),
                     ColumnEncoding::Hex => hexes.push(format!("{:?}", a)),
+                    ColumnEncoding::UnprefixedHex => hexes.push(hex::encode(a)),
                 },
                 Token::FixedBytes(b) => match column_encoding {
                     ColumnEncoding::Binary => bytes.push(b),
                     ColumnEncoding::Hex => hexes.push(b.encode_hex()),
+                    ColumnEncoding::UnprefixedHex => hexes.push(hex::encode(b)),
                 },
                 Token::Bytes(b) => match column_encoding {
                     ColumnEncoding::Binary => bytes.push(b),
                     ColumnEncoding::Hex => hexes.push(b.encode_hex()),
+                    ColumnEncoding::UnprefixedHex => hexes.push(hex::encode(b)),
                 },
                 Token::Uint(i) => match param {
                     Some(param) => match param.kind.clone() {
@@ -201,3 +265,97 @@ impl LogDecoder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ERC20_ABI: &str = r#"[
+        {"type": "function", "name": "totalSupply", "inputs": [], "outputs": [{"name": "", "type": "uint256"}], "stateMutability": "view"},
+        {"type": "event", "name": "Transfer", "anonymous": false, "inputs": [
+            {"name": "from", "type": "address", "indexed": true},
+            {"name": "to", "type": "address", "indexed": true},
+            {"name": "amount", "type": "uint256", "indexed": false}
+        ]},
+        {"type": "event", "name": "Approval", "anonymous": false, "inputs": [
+            {"name": "owner", "type": "address", "indexed": true},
+            {"name": "spender", "type": "address", "indexed": true},
+            {"name": "amount", "type": "uint256", "indexed": false}
+        ]}
+    ]"#;
+
+    fn transfer_log() -> Log {
+        let from = H160::repeat_byte(1);
+        let to = H160::repeat_byte(2);
+        Log {
+            topics: vec![
+                "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+                    .parse()
+                    .unwrap(),
+                H256::from(from),
+                H256::from(to),
+            ],
+            data: U256::from(100).encode().into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn decoder_from_abi_selects_event() {
+        let decoder = LogDecoder::from_abi_json(ERC20_ABI, Some("Transfer")).unwrap();
+        assert_eq!(decoder.field_names(), vec!["from", "to", "amount"]);
+        assert!(LogDecoder::from_abi_json(ERC20_ABI, None).is_err());
+        assert!(LogDecoder::from_abi_json(ERC20_ABI, Some("Deposit")).is_err());
+
+        let event = r#"{"type": "event", "name": "Deposit", "anonymous": false, "inputs": [
+            {"name": "dst", "type": "address", "indexed": true},
+            {"name": "wad", "type": "uint256", "indexed": false}
+        ]}"#;
+        let decoder = LogDecoder::from_abi_json(event, None).unwrap();
+        assert_eq!(decoder.raw, "Deposit(address,uint256)");
+    }
+
+    #[test]
+    fn decodes_matching_logs_only() {
+        let decoder = LogDecoder::from_abi_json(ERC20_ABI, Some("Transfer")).unwrap();
+        let other = Log { topics: vec![H256::repeat_byte(3)], ..Default::default() };
+        assert!(decoder.matches(&transfer_log()));
+        assert!(!decoder.matches(&other));
+
+        let decoded = decoder.parse_log_from_event(vec![transfer_log()]);
+        assert_eq!(decoded["from"], vec![Token::Address(H160::repeat_byte(1))]);
+        assert_eq!(decoded["amount"], vec![Token::Uint(U256::from(100))]);
+    }
+
+    #[test]
+    fn stores_hash_of_indexed_string() {
+        let decoder =
+            LogDecoder::new("event NameRegistered(string indexed name, address owner)".to_string())
+                .unwrap();
+        let name_hash = H256::from(ethers::utils::keccak256("vitalik"));
+        let log = Log {
+            topics: vec![decoder.event.signature(), name_hash],
+            data: H160::repeat_byte(1).encode().into(),
+            ..Default::default()
+        };
+        assert!(decoder.matches(&log));
+
+        let decoded = decoder.parse_log_from_event(vec![log]);
+        assert!(!decoded.contains_key("name"));
+        assert_eq!(decoded["name_hash"], vec![Token::FixedBytes(name_hash.as_bytes().to_vec())]);
+        assert_eq!(decoded["name_hashed"], vec![Token::Bool(true)]);
+        assert_eq!(decoded["owner"], vec![Token::Address(H160::repeat_byte(1))]);
+
+        let series = decoder
+            .make_series(
+                "name_hash".to_string(),
+                decoded["name_hash"].clone(),
+                1,
+                &[],
+                &ColumnEncoding::Binary,
+            )
+            .unwrap();
+        assert_eq!(series[0].name(), "event__name_hash");
+        assert_eq!(series[0].dtype(), &DataType::Binary);
+    }
+}
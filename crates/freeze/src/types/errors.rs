@@ -85,6 +85,62 @@ pub enum CollectError {
     /// Generic RPC Error
     #[error("RPC call error")]
     RPCError(String),
+
+    /// Block returned without a block number, e.g. a pending block
+    #[error("Block has no block number")]
+    MissingBlockNumber,
+
+    /// Block not returned by the node
+    #[error("Block {number} not found")]
+    BlockNotFound {
+        /// number of missing block
+        number: u64,
+    },
+
+    /// Transaction not returned by the node
+    #[error("Transaction {tx_hash:?} not found")]
+    TransactionNotFound {
+        /// hash of missing transaction
+        tx_hash: H256,
+    },
+
+    /// Transaction receipt not returned by the node
+    #[error("Receipt of transaction {tx_hash:?} not found")]
+    MissingReceipt {
+        /// hash of transaction with missing receipt
+        tx_hash: H256,
+    },
+
+    /// Trace returned in an unexpected format
+    #[error("Invalid trace result")]
+    InvalidTraceResult,
+
+    /// Node serves a different chain than the one configured
+    #[error("Expected chain_id {expected} but rpc reports chain_id {actual}")]
+    ChainIdMismatch {
+        /// configured chain id
+        expected: u64,
+        /// chain id reported by node
+        actual: u64,
+    },
+
+    /// Node has pruned the historical state needed for a request
+    #[error("State at block {block} is unavailable, rpc node is not an archive node")]
+    ArchiveDataUnavailable {
+        /// block whose state was requested
+        block: u64,
+    },
+
+    /// Requests are paused after too many consecutive failures
+    #[error("Circuit breaker open, rpc endpoint failed {0} consecutive requests")]
+    CircuitOpen(u32),
+
+    /// Request did not complete within its timeout, on every attempt
+    #[error("Request {method} timed out")]
+    Timeout {
+        /// fetcher method that timed out
+        method: &'static str,
+    },
 }
 
 /// Error related to parsing
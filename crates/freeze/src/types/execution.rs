@@ -1,6 +1,13 @@
 use crate::CollectError;
 use indicatif::ProgressBar;
-use std::{path::PathBuf, sync::Arc, time::SystemTime};
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::SystemTime,
+};
 
 /// configuration of execution environment
 #[derive(Clone)]
@@ -25,6 +32,8 @@ pub struct ExecutionEnv {
     pub t_end: Option<SystemTime>,
     /// report directory
     pub report_dir: Option<PathBuf>,
+    /// set when collection should stop, shared by clones
+    pub cancelled: Arc<AtomicBool>,
 }
 
 impl ExecutionEnv {
@@ -37,6 +46,16 @@ impl ExecutionEnv {
     pub fn set_end_time(self) -> Self {
         ExecutionEnv { t_end: Some(SystemTime::now()), ..self }
     }
+
+    /// stop starting new chunks, chunks already in progress still finish
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst)
+    }
+
+    /// whether collection has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
 }
 
 impl Default for ExecutionEnv {
@@ -147,6 +166,7 @@ impl ExecutionEnvBuilder {
             t_start: self.t_start,
             t_end: self.t_end,
             report_dir: self.report_dir,
+            cancelled: Arc::new(AtomicBool::new(false)),
         }
     }
 }
@@ -0,0 +1,157 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use ethers::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::sources::is_recoverable_error;
+use crate::{err, CollectError};
+
+/// Json rpc transport that rotates through fallback endpoints when a request fails
+///
+/// Requests are sent to the most recently successful endpoint first. Connection errors and
+/// recoverable errors (timeouts, rate limits) cause the next endpoint to be tried, while errors
+/// returned by the node itself (e.g. invalid params) are returned immediately.
+#[derive(Debug)]
+pub struct FallbackClient<C> {
+    clients: Vec<(String, C)>,
+    active: AtomicUsize,
+    served: Vec<AtomicU64>,
+    failed: Vec<AtomicU64>,
+}
+
+impl<C> FallbackClient<C> {
+    /// create a new FallbackClient from (url, client) pairs, ordered by priority
+    pub fn new(clients: Vec<(String, C)>) -> Result<Self, CollectError> {
+        if clients.is_empty() {
+            return Err(err("at least one rpc endpoint is required"))
+        }
+        let served = clients.iter().map(|_| AtomicU64::new(0)).collect();
+        let failed = clients.iter().map(|_| AtomicU64::new(0)).collect();
+        Ok(FallbackClient { clients, active: AtomicUsize::new(0), served, failed })
+    }
+
+    /// urls of all endpoints, ordered by priority
+    pub fn urls(&self) -> Vec<&str> {
+        self.clients.iter().map(|(url, _)| url.as_str()).collect()
+    }
+
+    /// url of the endpoint that served the most recent successful request
+    pub fn active_url(&self) -> &str {
+        &self.clients[self.active.load(Ordering::Relaxed)].0
+    }
+
+    /// number of requests served and failed by each endpoint, ordered by priority
+    pub fn request_counts(&self) -> Vec<(&str, u64, u64)> {
+        self.clients
+            .iter()
+            .zip(self.served.iter().zip(self.failed.iter()))
+            .map(|((url, _), (served, failed))| {
+                (url.as_str(), served.load(Ordering::Relaxed), failed.load(Ordering::Relaxed))
+            })
+            .collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl<C> JsonRpcClient for FallbackClient<C>
+where
+    C: JsonRpcClient,
+{
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, ProviderError>
+    where
+        T: std::fmt::Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        // zero-sized params are omitted from the request, so they are not serialized up front
+        let params = if std::mem::size_of::<T>() == 0 {
+            None
+        } else {
+            Some(serde_json::to_value(params).map_err(ProviderError::SerdeJson)?)
+        };
+
+        let start = self.active.load(Ordering::Relaxed);
+        let mut last_error = None;
+        for offset in 0..self.clients.len() {
+            let index = (start + offset) % self.clients.len();
+            let client = &self.clients[index].1;
+            let result = match &params {
+                Some(params) => client.request(method, params).await,
+                None => client.request(method, ()).await,
+            };
+            match result {
+                Ok(value) => {
+                    self.active.store(index, Ordering::Relaxed);
+                    self.served[index].fetch_add(1, Ordering::Relaxed);
+                    return Ok(value)
+                }
+                Err(e) => {
+                    self.failed[index].fetch_add(1, Ordering::Relaxed);
+                    let e: ProviderError = e.into();
+                    if !should_fall_back(&e) {
+                        return Err(e)
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or(ProviderError::CustomError("no rpc endpoints".to_string())))
+    }
+}
+
+/// whether a failed request should be retried against the next endpoint
+fn should_fall_back(error: &ProviderError) -> bool {
+    match error {
+        ProviderError::HTTPError(_) => true,
+        ProviderError::JsonRpcClientError(e) => {
+            (e.as_error_response().is_none() && e.as_serde_error().is_none()) ||
+                is_recoverable_error(error)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fallback_provider() -> (Provider<FallbackClient<MockProvider>>, MockProvider, MockProvider) {
+        let primary = MockProvider::new();
+        let secondary = MockProvider::new();
+        let clients = vec![
+            ("primary".to_string(), primary.clone()),
+            ("secondary".to_string(), secondary.clone()),
+        ];
+        (Provider::new(FallbackClient::new(clients).unwrap()), primary, secondary)
+    }
+
+    #[tokio::test]
+    async fn falls_back_on_connection_error() {
+        let (provider, _primary, secondary) = fallback_provider();
+        // primary has no queued responses, which fails like an unreachable endpoint
+        secondary.push(U64::from(1)).unwrap();
+        assert_eq!(provider.get_chainid().await.unwrap(), U256::from(1));
+        assert_eq!(provider.as_ref().active_url(), "secondary");
+        assert_eq!(
+            provider.as_ref().request_counts(),
+            vec![("primary", 0, 1), ("secondary", 1, 0)]
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_node_errors_without_fallback() {
+        let (provider, primary, secondary) = fallback_provider();
+        let error =
+            JsonRpcError { code: -32602, message: "invalid params".to_string(), data: None };
+        primary.push_response(MockResponse::Error(error));
+        secondary.push(U64::from(1)).unwrap();
+        assert!(provider.get_chainid().await.is_err());
+        assert_eq!(provider.as_ref().active_url(), "primary");
+        assert!(secondary.assert_request("eth_chainId", ()).is_err());
+        assert_eq!(
+            provider.as_ref().request_counts(),
+            vec![("primary", 0, 1), ("secondary", 0, 0)]
+        );
+    }
+}
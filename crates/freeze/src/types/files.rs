@@ -22,6 +22,10 @@ pub struct FileOutput {
     pub parquet_statistics: bool,
     /// Parquet compression options
     pub parquet_compression: polars::prelude::ParquetCompression,
+    /// Flush collected rows to parquet row groups after this many rows
+    pub flush_rows: Option<u64>,
+    /// Write a sha256 checksum of each output file's data to a `.sha256` sidecar file
+    pub checksums: bool,
 }
 
 /// Possible item to use as subdirectory
@@ -112,6 +116,8 @@ pub enum FileFormat {
     Csv,
     /// Json file format
     Json,
+    /// Arrow IPC (feather) file format
+    ArrowIpc,
 }
 
 impl FileFormat {
@@ -121,6 +127,7 @@ impl FileFormat {
             FileFormat::Parquet => "parquet",
             FileFormat::Csv => "csv",
             FileFormat::Json => "json",
+            FileFormat::ArrowIpc => "arrow",
         }
     }
 }
@@ -130,8 +137,10 @@ impl FileFormat {
 pub enum ColumnEncoding {
     /// Raw binary encoding
     Binary,
-    /// Hex binary encoding
+    /// Hex binary encoding, prefixed with 0x
     Hex,
+    /// Hex binary encoding without the 0x prefix
+    UnprefixedHex,
 }
 
 impl ColumnEncoding {
@@ -140,6 +149,12 @@ impl ColumnEncoding {
         match *self {
             ColumnEncoding::Binary => "binary",
             ColumnEncoding::Hex => "hex",
+            ColumnEncoding::UnprefixedHex => "unprefixed_hex",
         }
     }
+
+    /// whether binary data is encoded as hex strings
+    pub fn is_hex(&self) -> bool {
+        matches!(self, ColumnEncoding::Hex | ColumnEncoding::UnprefixedHex)
+    }
 }
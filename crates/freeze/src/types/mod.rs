@@ -6,11 +6,16 @@ pub mod chunks;
 pub mod conversions;
 /// type specifications for collectable types
 pub mod datatypes;
+/// rpc transport with fallback endpoints
+pub mod fallback;
 /// type specifications for data sources
 pub mod sources;
+/// rpc transports
+pub mod transport;
 
 /// column data specification
 pub mod columns;
+pub(crate) use columns::ESTIMATED_TRANSACTIONS_PER_BLOCK;
 pub use columns::{ColumnData, Dataset, ToDataFrames};
 
 /// partitions
@@ -19,7 +24,9 @@ pub mod partitions;
 pub mod rpc_params;
 
 pub use partitions::{Dim, Partition, PartitionLabels};
-pub use rpc_params::Params;
+pub use rpc_params::{
+    event_signature_topic0, parse_function_signature, EventSignatureFilter, Params,
+};
 
 /// collection traits
 pub mod collection;
@@ -53,13 +60,21 @@ pub use chunks::{
     AddressChunk, BlockChunk, CallDataChunk, Chunk, ChunkData, ChunkStats, SlotChunk, Subchunk,
     TopicChunk, TransactionChunk,
 };
-pub use conversions::{bytes_to_u32, ToVecHex, ToVecU8};
+pub use conversions::{bytes_to_u32, diff_from_to, from_to_or_default, ToVecHex, ToVecU8};
 pub use dataframes::*;
 pub use datatypes::*;
+pub use fallback::FallbackClient;
 pub use files::{ColumnEncoding, FileFormat, FileOutput, SubDir};
-pub use queries::{Query, QueryLabels, TimeDimension};
-pub use schemas::{ColumnType, SchemaFunctions, Schemas, Table, U256Type};
-pub use sources::{Fetcher, RateLimiter, Source, SourceLabels};
+pub use queries::{Query, QueryLabels, TimeDimension, TraceAddressFilter, TraceStatusFilter};
+pub use schemas::{
+    ColumnSchema, ColumnType, SchemaFunctions, Schemas, Table, TableSchema, U256Type,
+};
+pub use sources::{
+    bloom_contains, Capabilities, Capability, CircuitBreaker, CircuitState, CodeCache, Fetcher,
+    LatencyStats, LatencySummary, MethodCategory, RateLimiter, RequestProgress, RetryConfig,
+    Source, SourceLabels,
+};
+pub use transport::Transport;
 // pub(crate) use summaries::FreezeSummaryAgg;
 // pub use summaries::{FreezeChunkSummary, FreezeSummary};
 pub use summaries::{print_all_datasets, print_dataset_info, FreezeSummary};
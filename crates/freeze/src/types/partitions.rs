@@ -361,7 +361,18 @@ impl Partition {
                 Dim::ToAddress => parametrize!(outputs, new, self.to_addresses, to_address),
                 Dim::CallData => parametrize!(outputs, new, self.call_datas, call_data),
                 Dim::Slot => parametrize!(outputs, new, self.slots, slot),
-                Dim::Topic0 => parametrize!(outputs, new, self.topic0s, topic0),
+                // request all topic0s of partition at once, as alternatives in one filter
+                Dim::Topic0 => {
+                    let chunks = self
+                        .topic0s
+                        .as_ref()
+                        .ok_or(CollectError::CollectError("missing topic0s".to_string()))?;
+                    let topic0s: Vec<Vec<u8>> =
+                        chunks.iter().flat_map(|chunk| chunk.values().to_vec()).collect();
+                    for output in outputs.into_iter() {
+                        new.push(Params { topic0s: Some(topic0s.clone()), ..output })
+                    }
+                }
                 Dim::Topic1 => parametrize!(outputs, new, self.topic1s, topic1),
                 Dim::Topic2 => parametrize!(outputs, new, self.topic2s, topic2),
                 Dim::Topic3 => parametrize!(outputs, new, self.topic3s, topic3),
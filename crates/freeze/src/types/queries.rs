@@ -1,4 +1,5 @@
 use crate::{CollectError, Datatype, Dim, MetaDatatype, Partition, Table};
+use ethers::types::{Action, Res, Trace, H160};
 use std::collections::{HashMap, HashSet};
 
 /// Query
@@ -18,6 +19,16 @@ pub struct Query {
     pub exclude_failed: bool,
     /// Javascript tracer
     pub js_tracer: Option<String>,
+    /// Find address filtered transactions with trace_filter instead of scanning blocks
+    pub use_trace_filter: bool,
+    /// Skip blocks whose logs bloom cannot contain the requested logs before calling eth_getLogs
+    pub use_bloom_filter: bool,
+    /// Function called by eth_calls, used to decode outputs
+    pub function: Option<ethers_core::abi::Function>,
+    /// Addresses used to keep or drop traces of trace-based datasets
+    pub trace_addresses: TraceAddressFilter,
+    /// Status of transactions whose traces are kept by trace-based datasets
+    pub trace_status: TraceStatusFilter,
     /// Labels (these are non-functional)
     pub labels: QueryLabels,
 }
@@ -66,6 +77,15 @@ impl Query {
                 )))
             }
         }
+
+        // reverted transactions move no value, so their native transfers would always be empty
+        if self.trace_status == TraceStatusFilter::Reverted &&
+            all_datatypes.contains(&Datatype::NativeTransfers)
+        {
+            return Err(CollectError::CollectError(
+                "native_transfers cannot be collected with trace status reverted".to_string(),
+            ))
+        }
         Ok(())
     }
 }
@@ -78,3 +98,165 @@ pub enum TimeDimension {
     /// Transactions
     Transactions,
 }
+
+/// addresses that traces must or must not touch to be collected
+///
+/// a trace touches the sender, recipient, created contract, or reward author of its action
+#[derive(Clone, Debug, Default)]
+pub struct TraceAddressFilter {
+    /// if given, keep only traces touching one of these addresses
+    pub include: Option<HashSet<H160>>,
+    /// drop traces touching any of these addresses
+    pub exclude: HashSet<H160>,
+}
+
+impl TraceAddressFilter {
+    /// whether the filter keeps every trace
+    pub fn is_empty(&self) -> bool {
+        self.include.is_none() && self.exclude.is_empty()
+    }
+
+    /// whether a trace passes the filter
+    pub fn allows(&self, trace: &Trace) -> bool {
+        let touched = touched_addresses(trace);
+        if let Some(include) = &self.include {
+            if !touched.iter().any(|address| include.contains(address)) {
+                return false
+            }
+        }
+        !touched.iter().any(|address| self.exclude.contains(address))
+    }
+
+    /// remove traces that do not pass the filter
+    pub fn filter(&self, traces: Vec<Trace>) -> Vec<Trace> {
+        if self.is_empty() {
+            return traces
+        }
+        traces.into_iter().filter(|trace| self.allows(trace)).collect()
+    }
+}
+
+/// status of the transactions whose traces are collected
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TraceStatusFilter {
+    /// keep traces of every transaction
+    #[default]
+    All,
+    /// keep only traces of transactions that succeeded
+    Success,
+    /// keep only traces of transactions that reverted
+    Reverted,
+}
+
+impl TraceStatusFilter {
+    /// remove traces of transactions that do not have the status
+    ///
+    /// a transaction reverted if its top level trace has an error, so the traces given must
+    /// include the top level trace of each transaction. block rewards count as successful
+    pub fn filter(&self, traces: Vec<Trace>) -> Vec<Trace> {
+        let keep_reverted = match self {
+            TraceStatusFilter::All => return traces,
+            TraceStatusFilter::Success => false,
+            TraceStatusFilter::Reverted => true,
+        };
+        let reverted: HashSet<_> = traces
+            .iter()
+            .filter(|trace| trace.trace_address.is_empty() && trace.error.is_some())
+            .map(|trace| (trace.block_number, trace.transaction_position))
+            .collect();
+        traces
+            .into_iter()
+            .filter(|trace| {
+                reverted.contains(&(trace.block_number, trace.transaction_position)) ==
+                    keep_reverted
+            })
+            .collect()
+    }
+}
+
+fn touched_addresses(trace: &Trace) -> Vec<H160> {
+    match (&trace.action, &trace.result) {
+        (Action::Call(action), _) => vec![action.from, action.to],
+        (Action::Create(action), Some(Res::Create(result))) => vec![action.from, result.address],
+        (Action::Create(action), _) => vec![action.from],
+        (Action::Suicide(action), _) => vec![action.address, action.refund_address],
+        (Action::Reward(action), _) => vec![action.author],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{ActionType, Call, H256};
+
+    fn call(from: H160, to: H160) -> Trace {
+        Trace {
+            action: Action::Call(Call { from, to, ..Default::default() }),
+            result: None,
+            trace_address: vec![],
+            subtraces: 0,
+            transaction_position: Some(0),
+            transaction_hash: Some(H256::zero()),
+            block_number: 1,
+            block_hash: H256::zero(),
+            action_type: ActionType::Call,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn filters_traces_by_touched_addresses() {
+        let [a, b, c] = [1, 2, 3].map(H160::repeat_byte);
+        let traces = vec![call(a, b), call(b, c), call(c, c)];
+
+        let include =
+            TraceAddressFilter { include: Some(HashSet::from([b])), ..Default::default() };
+        assert_eq!(include.filter(traces.clone()), traces[..2]);
+
+        let exclude = TraceAddressFilter { include: None, exclude: HashSet::from([a]) };
+        assert_eq!(exclude.filter(traces.clone()), traces[1..]);
+
+        let both =
+            TraceAddressFilter { include: Some(HashSet::from([b])), exclude: HashSet::from([c]) };
+        assert_eq!(both.filter(traces.clone()), traces[..1]);
+    }
+
+    #[test]
+    fn filters_traces_by_transaction_status() {
+        let [a, b] = [1, 2].map(H160::repeat_byte);
+        let reverted = Trace { error: Some("Reverted".to_string()), ..call(a, b) };
+        let reverted_child = Trace { trace_address: vec![0], ..call(b, a) };
+        let success = Trace { transaction_position: Some(1), ..call(a, b) };
+        let failed_child = Trace {
+            trace_address: vec![0],
+            error: Some("Reverted".to_string()),
+            ..success.clone()
+        };
+        let traces = vec![reverted, reverted_child, success, failed_child];
+
+        assert_eq!(TraceStatusFilter::All.filter(traces.clone()), traces);
+        assert_eq!(TraceStatusFilter::Success.filter(traces.clone()), traces[2..]);
+        assert_eq!(TraceStatusFilter::Reverted.filter(traces.clone()), traces[..2]);
+    }
+
+    #[test]
+    fn rejects_native_transfers_of_reverted_transactions() {
+        let query = |trace_status: TraceStatusFilter| Query {
+            datatypes: vec![MetaDatatype::Scalar(Datatype::NativeTransfers)],
+            schemas: HashMap::new(),
+            time_dimension: TimeDimension::Blocks,
+            partitions: vec![],
+            partitioned_by: vec![],
+            exclude_failed: false,
+            js_tracer: None,
+            use_trace_filter: false,
+            use_bloom_filter: false,
+            function: None,
+            trace_addresses: TraceAddressFilter::default(),
+            trace_status,
+            labels: QueryLabels { align: false, reorg_buffer: 0 },
+        };
+        assert!(query(TraceStatusFilter::Success).is_valid().is_ok());
+        assert!(query(TraceStatusFilter::Reverted).is_valid().is_err());
+    }
+}
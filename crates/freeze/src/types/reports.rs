@@ -1,4 +1,6 @@
-use crate::{err, CollectError, ExecutionEnv, FileOutput, FreezeSummary, Query};
+use crate::{
+    err, CollectError, ExecutionEnv, FileFormat, FileOutput, FreezeSummary, Query, Source,
+};
 use chrono::{DateTime, Local};
 use std::{
     fs::File,
@@ -13,13 +15,26 @@ struct FreezeReport {
     cli_command: Option<Vec<String>>,
     results: Option<SerializedFreezeSummary>,
     args: Option<String>,
+    datatypes: Vec<String>,
+    min_block: Option<u64>,
+    max_block: Option<u64>,
+    n_chunks: u64,
+    output_format: String,
+    parquet_compression: Option<String>,
 }
 
 #[derive(serde::Serialize, Debug)]
 struct SerializedFreezeSummary {
     completed_paths: Vec<PathBuf>,
     errored_paths: Vec<PathBuf>,
+    errors: Vec<String>,
+    n_completed: u64,
+    n_errored: u64,
     n_skipped: u64,
+    n_cancelled: u64,
+    n_rows: u64,
+    n_rpc_requests: u64,
+    duration_seconds: Option<f64>,
 }
 
 pub(crate) fn get_report_path(
@@ -51,20 +66,37 @@ pub(crate) fn get_report_path(
 pub(crate) fn write_report(
     env: &ExecutionEnv,
     query: &Query,
+    source: &Source,
     sink: &FileOutput,
     freeze_summary: Option<&FreezeSummary>,
 ) -> Result<PathBuf, CollectError> {
     // determine version
     let cryo_version = CRYO_VERSION.to_string();
     let serialized_summary = match freeze_summary {
-        Some(x) => Some(serialize_summary(x, query, sink)?),
+        Some(x) => Some(serialize_summary(x, env, query, source, sink)?),
         None => None,
     };
+    let datatypes = query
+        .datatypes
+        .iter()
+        .flat_map(|datatype| datatype.datatypes())
+        .map(|datatype| datatype.name())
+        .collect();
+    let block_stats = crate::types::partitions::meta_chunks_stats(&query.partitions).block_numbers;
     let report = FreezeReport {
         cryo_version,
         cli_command: env.cli_command.clone(),
         args: env.args.clone(),
         results: serialized_summary,
+        datatypes,
+        min_block: block_stats.as_ref().and_then(|stats| stats.min_value),
+        max_block: block_stats.as_ref().and_then(|stats| stats.max_value),
+        n_chunks: (query.partitions.len() * query.datatypes.len()) as u64,
+        output_format: sink.format.as_str().to_string(),
+        parquet_compression: match sink.format {
+            FileFormat::Parquet => Some(format!("{:?}", sink.parquet_compression)),
+            _ => None,
+        },
     };
     let serialized = serde_json::to_string(&report)
         .map_err(|_| CollectError::CollectError("could not serialize report".to_string()))?;
@@ -90,7 +122,9 @@ pub(crate) fn write_report(
 
 fn serialize_summary(
     summary: &FreezeSummary,
+    env: &ExecutionEnv,
     query: &Query,
+    source: &Source,
     sink: &FileOutput,
 ) -> Result<SerializedFreezeSummary, CollectError> {
     let completed_paths: Vec<PathBuf> = summary
@@ -119,10 +153,20 @@ fn serialize_summary(
         .flatten()
         .collect();
 
+    let errors = summary.errored.iter().map(|(_partition, error)| error.to_string()).collect();
+    let duration_seconds = env.t_start.elapsed().ok().map(|duration| duration.as_secs_f64());
+
     Ok(SerializedFreezeSummary {
         completed_paths,
         errored_paths,
+        errors,
+        n_completed: summary.completed.len() as u64,
+        n_errored: summary.errored.len() as u64,
         n_skipped: summary.skipped.len() as u64,
+        n_cancelled: summary.cancelled.len() as u64,
+        n_rows: summary.n_rows,
+        n_rpc_requests: source.fetcher.requests_made(),
+        duration_seconds,
     })
 }
 
@@ -1,5 +1,6 @@
-use crate::{err, CollectError};
+use crate::{err, CollectError, ParseError};
 use ethers::prelude::*;
+use ethers_core::abi::{Function, HumanReadableParser};
 // import event_hash.rs's calculate_topic_0 function
 // use crate::event_hash::calculate_topic_0;
 /// represents parameters for a single rpc call
@@ -25,6 +26,8 @@ pub struct Params {
     pub slot: Option<Vec<u8>>,
     /// topic0
     pub topic0: Option<Vec<u8>>,
+    /// topic0 values matched as alternatives, takes precedence over topic0
+    pub topic0s: Option<Vec<Vec<u8>>>,
     /// topic1
     pub topic1: Option<Vec<u8>>,
     /// topic2
@@ -93,19 +96,27 @@ impl Params {
         Ok(H160::from_slice(&self.contract()?))
     }
 
-    /// log filter
+    /// log filter, matching logs emitted by `address` (or `contract` if no address is given)
     pub fn ethers_log_filter(&self) -> Result<Filter, CollectError> {
         let (start, end) = self.block_range()?;
         let block_option =
             FilterBlockOption::Range { from_block: Some(start.into()), to_block: Some(end.into()) };
-        // let calculated_topic0 = calculate_topic_0();
-        // print the topic0
-        // println!("====>Topic 0: {:?}", calculated_topic0);
+        let topic0 = match (&self.topic0s, &self.topic0) {
+            (Some(topic0s), _) => Some(ValueOrArray::Array(
+                topic0s.iter().map(|x| Some(H256::from_slice(x))).collect(),
+            )),
+            (None, Some(topic0)) => Some(ValueOrArray::Value(Some(H256::from_slice(topic0)))),
+            (None, None) => None,
+        };
         let filter = Filter {
             block_option,
-            address: self.address.clone().map(|x| ValueOrArray::Value(H160::from_slice(&x))),
+            address: self
+                .address
+                .as_ref()
+                .or(self.contract.as_ref())
+                .map(|x| ValueOrArray::Value(H160::from_slice(x))),
             topics: [
-                self.topic0.clone().map(|x| ValueOrArray::Value(Some(H256::from_slice(&x)))),
+                topic0,
                 self.topic1.clone().map(|x| ValueOrArray::Value(Some(H256::from_slice(&x)))),
                 self.topic2.clone().map(|x| ValueOrArray::Value(Some(H256::from_slice(&x)))),
                 self.topic3.clone().map(|x| ValueOrArray::Value(Some(H256::from_slice(&x)))),
@@ -114,3 +125,109 @@ impl Params {
         Ok(filter)
     }
 }
+
+/// compute topic0 of an event from its human readable signature
+/// ex: "Transfer(address,address,uint256)" or "event Transfer(address indexed from, ...)"
+pub fn event_signature_topic0(signature: &str) -> Result<H256, ParseError> {
+    let event = HumanReadableParser::parse_event(signature).map_err(|e| {
+        ParseError::ParseError(format!("invalid event signature {}: {}", signature, e))
+    })?;
+    Ok(event.signature())
+}
+
+/// parse a function from its human readable signature, including return types if given
+/// ex: "balanceOf(address)" or "function balanceOf(address) view returns (uint256)"
+pub fn parse_function_signature(signature: &str) -> Result<Function, ParseError> {
+    let trimmed = signature.trim();
+    let full = if trimmed.starts_with("function ") {
+        trimmed.to_string()
+    } else {
+        format!("function {}", trimmed)
+    };
+    HumanReadableParser::parse_function(&full).map_err(|e| {
+        ParseError::ParseError(format!("invalid function signature {}: {}", signature, e))
+    })
+}
+
+/// construct log filters from human readable event signatures
+pub trait EventSignatureFilter: Sized {
+    /// create a filter that matches logs of the given event
+    /// ex: Filter::from_event_signature("Transfer(address,address,uint256)")
+    fn from_event_signature(signature: &str) -> Result<Self, ParseError>;
+}
+
+impl EventSignatureFilter for Filter {
+    fn from_event_signature(signature: &str) -> Result<Self, ParseError> {
+        Ok(Filter::new().topic0(event_signature_topic0(signature)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSFER_TOPIC0: &str =
+        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+    #[test]
+    fn topic0_from_canonical_signature() {
+        let topic0 = event_signature_topic0("Transfer(address,address,uint256)").unwrap();
+        assert_eq!(topic0, TRANSFER_TOPIC0.parse().unwrap());
+    }
+
+    #[test]
+    fn topic0_from_full_signature() {
+        let signature = "event Transfer(address indexed from, address indexed to, uint256 amount)";
+        let topic0 = event_signature_topic0(signature).unwrap();
+        assert_eq!(topic0, TRANSFER_TOPIC0.parse().unwrap());
+    }
+
+    #[test]
+    fn function_from_signature() {
+        let function = parse_function_signature("balanceOf(address)").unwrap();
+        assert_eq!(function.short_signature(), [0x70, 0xa0, 0x82, 0x31]);
+        assert!(function.outputs.is_empty());
+
+        let signature = "function balanceOf(address owner) view returns (uint256)";
+        let function = parse_function_signature(signature).unwrap();
+        assert_eq!(function.short_signature(), [0x70, 0xa0, 0x82, 0x31]);
+        assert_eq!(function.outputs.len(), 1);
+        assert!(parse_function_signature("balanceOf(address").is_err());
+    }
+
+    #[test]
+    fn filter_from_event_signature() {
+        let filter = Filter::from_event_signature("Transfer(address,address,uint256)").unwrap();
+        let topic0 = ValueOrArray::Value(Some(TRANSFER_TOPIC0.parse().unwrap()));
+        assert_eq!(filter.topics[0], Some(topic0));
+        assert!(Filter::from_event_signature("Transfer(address").is_err());
+    }
+
+    #[test]
+    fn log_filter_uses_contract_as_address() {
+        let contract = H160::repeat_byte(1);
+        let params = Params {
+            block_range: Some((0, 10)),
+            contract: Some(contract.as_bytes().to_vec()),
+            ..Default::default()
+        };
+        let filter = params.ethers_log_filter().unwrap();
+        assert_eq!(filter.address, Some(ValueOrArray::Value(contract)));
+    }
+
+    #[test]
+    fn log_filter_ors_multiple_topic0s() {
+        let approval = "0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925";
+        let params = Params {
+            block_range: Some((0, 9)),
+            topic0s: Some(vec![
+                TRANSFER_TOPIC0.parse::<H256>().unwrap().as_bytes().to_vec(),
+                approval.parse::<H256>().unwrap().as_bytes().to_vec(),
+            ]),
+            ..Default::default()
+        };
+        let filter = params.ethers_log_filter().unwrap();
+        let topics = serde_json::to_value(&filter).unwrap()["topics"].clone();
+        assert_eq!(topics, serde_json::json!([[TRANSFER_TOPIC0, approval]]));
+    }
+}
@@ -132,6 +132,10 @@ pub enum ColumnType {
     Binary,
     /// Hex column type
     Hex,
+    /// List of binary column type
+    BinaryList,
+    /// List of hex column type
+    HexList,
 }
 
 impl ColumnType {
@@ -150,19 +154,101 @@ impl ColumnType {
             ColumnType::String => "string",
             ColumnType::Binary => "binary",
             ColumnType::Hex => "hex",
+            ColumnType::BinaryList => "list[binary]",
+            ColumnType::HexList => "list[hex]",
         }
     }
 }
 
+/// description of the columns that a datatype can produce
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct TableSchema {
+    /// name of datatype
+    pub datatype: String,
+    /// columns of datatype, in output order
+    pub columns: Vec<ColumnSchema>,
+    /// columns that rows are sorted by default
+    pub default_sort: Vec<String>,
+}
+
+/// description of a column of a datatype
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct ColumnSchema {
+    /// name of column
+    pub name: String,
+    /// type of column
+    pub column_type: String,
+    /// description of column contents
+    pub description: Option<String>,
+    /// whether column is collected by default
+    pub default: bool,
+}
+
+/// description of columns shared by many datatypes
+fn common_column_description(column: &str) -> Option<&'static str> {
+    match column {
+        "block_number" => Some("number of the block"),
+        "block_hash" => Some("hash of the block"),
+        "transaction_index" => Some("index of the transaction within its block"),
+        "transaction_hash" => Some("hash of the transaction"),
+        "log_index" => Some("index of the log within its block"),
+        "chain_id" => Some("chain id of the network"),
+        _ => None,
+    }
+}
+
 /// Error related to Schemas
 #[derive(Error, Debug)]
 pub enum SchemaError {
-    /// Invalid column being operated on
-    #[error("Invalid column")]
-    InvalidColumn,
+    /// Invalid columns requested for a datatype
+    #[error("invalid columns for {datatype}: {}", columns.join(", "))]
+    InvalidColumns {
+        /// name of datatype
+        datatype: String,
+        /// names of invalid columns
+        columns: Vec<String>,
+    },
 }
 
 impl Datatype {
+    /// check that every requested column is a known column of datatype
+    pub fn validate_columns(&self, columns: &[String]) -> Result<(), SchemaError> {
+        let column_types = self.column_types();
+        let invalid: Vec<String> = columns
+            .iter()
+            .filter(|column| {
+                column.as_str() != "all" && !column_types.contains_key(column.as_str())
+            })
+            .cloned()
+            .collect();
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(SchemaError::InvalidColumns { datatype: self.name(), columns: invalid })
+        }
+    }
+
+    /// describe every column of datatype with its type and description
+    pub fn describe(&self) -> TableSchema {
+        let descriptions = self.column_descriptions();
+        let default_columns = self.default_columns();
+        let columns = self
+            .column_types()
+            .into_iter()
+            .map(|(name, column_type)| ColumnSchema {
+                name: name.to_string(),
+                column_type: column_type.as_str().to_string(),
+                description: descriptions
+                    .get(name)
+                    .copied()
+                    .or_else(|| common_column_description(name))
+                    .map(|x| x.to_string()),
+                default: default_columns.contains(&name),
+            })
+            .collect();
+        TableSchema { datatype: self.name(), columns, default_sort: self.default_sort() }
+    }
+
     /// get schema for a particular datatype
     #[allow(clippy::too_many_arguments)]
     pub fn table_schema(
@@ -175,6 +261,9 @@ impl Datatype {
         sort: Option<Vec<String>>,
         log_decoder: Option<LogDecoder>,
     ) -> Result<Table, SchemaError> {
+        if let Some(columns) = columns {
+            self.validate_columns(columns)?;
+        }
         let column_types = self.column_types();
         let all_columns = column_types.keys().map(|k| k.to_string()).collect();
         let default_columns = self.default_columns();
@@ -187,9 +276,15 @@ impl Datatype {
         );
         let mut columns = IndexMap::new();
         for column in used_columns {
-            let mut ctype = column_types.get(column.as_str()).ok_or(SchemaError::InvalidColumn)?;
-            if (*binary_column_format == ColumnEncoding::Hex) & (ctype == &ColumnType::Binary) {
-                ctype = &ColumnType::Hex;
+            let mut ctype = column_types.get(column.as_str()).ok_or_else(|| {
+                SchemaError::InvalidColumns { datatype: self.name(), columns: vec![column.clone()] }
+            })?;
+            if binary_column_format.is_hex() {
+                match ctype {
+                    ColumnType::Binary => ctype = &ColumnType::Hex,
+                    ColumnType::BinaryList => ctype = &ColumnType::HexList,
+                    _ => {}
+                }
             }
             columns.insert((*column.clone()).to_string(), *ctype);
         }
@@ -244,6 +339,30 @@ mod tests {
         vec![U256Type::Binary, U256Type::String, U256Type::F64]
     }
 
+    #[test]
+    fn test_every_datatype_describes_columns() {
+        for datatype in Datatype::all() {
+            let schema = datatype.describe();
+            assert!(!schema.columns.is_empty(), "{} has no columns", schema.datatype);
+            assert!(schema.columns.iter().any(|column| column.default));
+            for column in schema.columns.iter() {
+                let name = &column.name;
+                assert!(
+                    column.description.is_some(),
+                    "{}.{} has no description",
+                    schema.datatype,
+                    name
+                );
+            }
+        }
+        let schema = Datatype::TransactionReceipts.describe();
+        let status = schema.columns.iter().find(|column| column.name == "status").unwrap();
+        assert_eq!(status.column_type, "uint64");
+        assert!(status.description.as_ref().unwrap().contains("byzantium"));
+        let json = serde_json::to_value(&schema).unwrap();
+        assert_eq!(json["columns"][0]["description"], "number of the block");
+    }
+
     #[test]
     fn test_table_schema_explicit_cols() {
         let cols = Some(vec!["block_number".to_string(), "block_hash".to_string()]);
@@ -257,9 +376,25 @@ mod tests {
         let table = Datatype::Blocks
             .table_schema(&get_u256_types(), &ColumnEncoding::Hex, &None, &None, &cols, None, None)
             .unwrap();
-        assert_eq!(15, table.columns().len());
+        assert_eq!(20, table.columns().len());
         assert!(table.columns().contains(&"block_hash"));
         assert!(table.columns().contains(&"transactions_root"));
+
+        // unknown columns are reported by name
+        let cols = Some(vec!["block_hash".to_string(), "foo".to_string(), "bar".to_string()]);
+        let result = Datatype::Blocks.table_schema(
+            &get_u256_types(),
+            &ColumnEncoding::Hex,
+            &None,
+            &None,
+            &cols,
+            None,
+            None,
+        );
+        match result {
+            Err(SchemaError::InvalidColumns { columns, .. }) => assert_eq!(columns, ["foo", "bar"]),
+            _ => panic!("expected invalid columns error"),
+        }
     }
 
     #[test]
@@ -308,7 +443,7 @@ mod tests {
                 None,
             )
             .unwrap();
-        assert_eq!(15, table.columns().len());
+        assert_eq!(20, table.columns().len());
         assert!(table.columns().contains(&"block_hash"));
         assert!(table.columns().contains(&"transactions_root"));
     }
@@ -17,6 +17,14 @@ lazy_static::lazy_static! {
     /// function signature of FUNCTION_ERC20_TOTAL_SUPPLY
     pub static ref FUNCTION_ERC20_TOTAL_SUPPLY: Vec<u8> = prefix_hex::decode("0x18160ddd").expect("Decoding failed");
 
+    /// function signature of FUNCTION_MULTICALL3_AGGREGATE3
+    pub static ref FUNCTION_MULTICALL3_AGGREGATE3: Vec<u8> = prefix_hex::decode("0x82ad56cb").expect("Decoding failed");
+
+    /// canonical address of the Multicall3 contract, identical on every chain it is deployed to
+    pub static ref MULTICALL3_ADDRESS: H160 = H160(
+        prefix_hex::decode("0xcA11bde05977b3631167028862bE2a173976CA11").expect("Decoding failed"),
+    );
+
     /// event hash of EVENT_ERC20_TRANSFER
     pub static ref EVENT_ERC20_TRANSFER: H256 = H256(
         prefix_hex::decode("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef")
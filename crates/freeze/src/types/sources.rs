@@ -1,17 +1,33 @@
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    num::NonZeroU32,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
-use ethers::prelude::*;
+use dashmap::DashMap;
+use ethers::{
+    abi::{ethereum_types::BloomInput, ParamType, Token},
+    prelude::*,
+    types::transaction::{eip2718::TypedTransaction, eip2930::AccessListWithGasUsed},
+};
 use governor::{
     clock::DefaultClock,
     middleware::NoOpMiddleware,
     state::{direct::NotKeyed, InMemoryState},
+    Quota,
 };
-use tokio::{
-    sync::{AcquireError, Semaphore, SemaphorePermit},
-    task,
-};
+use reqwest::header::HeaderMap;
+use tokio::sync::{AcquireError, OnceCell, Semaphore, SemaphorePermit};
 
-use crate::CollectError;
+use crate::{
+    from_to_or_default, BlockChunk, ChunkData, CollectError, FallbackClient, Transport,
+    FUNCTION_MULTICALL3_AGGREGATE3, MULTICALL3_ADDRESS,
+};
 
 /// RateLimiter based on governor crate
 pub type RateLimiter = governor::RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>;
@@ -20,7 +36,7 @@ pub type RateLimiter = governor::RateLimiter<NotKeyed, InMemoryState, DefaultClo
 #[derive(Clone)]
 pub struct Source {
     /// Shared provider for rpc data
-    pub fetcher: Arc<Fetcher<RetryClient<Http>>>,
+    pub fetcher: Arc<Fetcher<FallbackClient<Transport>>>,
     /// chain_id of network
     pub chain_id: u64,
     /// number of blocks per log request
@@ -34,49 +50,158 @@ pub struct Source {
 }
 
 impl Source {
-    /// Returns all receipts for a block.
+    /// Connect to a local node over an ipc socket
+    ///
+    /// Concurrency and rate limiting behave the same as for http sources.
+    pub async fn from_ipc(
+        path: &str,
+        max_concurrent_requests: Option<u64>,
+        max_requests_per_second: Option<u32>,
+    ) -> Result<Source> {
+        let ipc = Ipc::connect(path).await.map_err(|e| CollectError::ProviderError(e.into()))?;
+        let client = FallbackClient::new(vec![(path.to_string(), Transport::Ipc(ipc))])?;
+        Source::from_client(
+            client,
+            path,
+            RetryConfig::default(),
+            max_concurrent_requests,
+            max_requests_per_second,
+        )
+        .await
+    }
+
+    /// Connect to http rpc endpoints, sending `headers` with every request
+    ///
+    /// Use this for gateways that authenticate with headers such as `Authorization`. The first
+    /// url is the primary endpoint and the rest are fallbacks, all of which receive the headers.
+    pub async fn from_http_with_headers(
+        urls: &[String],
+        headers: HeaderMap,
+        retry: RetryConfig,
+        max_concurrent_requests: Option<u64>,
+        max_requests_per_second: Option<u32>,
+    ) -> Result<Source> {
+        let mut clients = Vec::new();
+        for url in urls.iter() {
+            let transport = Transport::connect_with_headers(url, headers.clone())
+                .await
+                .map_err(CollectError::ProviderError)?;
+            clients.push((url.clone(), transport));
+        }
+        let rpc_url = urls.first().cloned().unwrap_or_default();
+        let client = FallbackClient::new(clients)?;
+        Source::from_client(
+            client,
+            &rpc_url,
+            retry,
+            max_concurrent_requests,
+            max_requests_per_second,
+        )
+        .await
+    }
+
+    async fn from_client(
+        client: FallbackClient<Transport>,
+        rpc_url: &str,
+        retry: RetryConfig,
+        max_concurrent_requests: Option<u64>,
+        max_requests_per_second: Option<u32>,
+    ) -> Result<Source> {
+        let fetcher = Fetcher::new(
+            Provider::new(client),
+            Some(max_concurrent_requests.unwrap_or(100)),
+            max_requests_per_second,
+            retry.clone(),
+        );
+        let chain_id = fetcher.get_chain_id().await?;
+        Ok(Source {
+            fetcher: Arc::new(fetcher),
+            chain_id,
+            inner_request_size: 1,
+            max_concurrent_chunks: Some(4),
+            rpc_url: rpc_url.to_string(),
+            labels: SourceLabels {
+                max_concurrent_requests,
+                max_requests_per_second: max_requests_per_second.map(|x| x as u64),
+                max_retries: Some(retry.max_retries),
+                initial_backoff: Some(retry.base_delay.as_millis() as u64),
+            },
+        })
+    }
+
+    /// Check that the node serves the chain that `chain_id` labels data with
+    pub async fn verify_chain_id(&self) -> Result<()> {
+        self.fetcher.check_chain_id(self.chain_id).await.map(|_| ())
+    }
+
+    /// Resolve a `start:end` block range such as `latest-1000:latest`, both ends inclusive
+    pub async fn resolve_range(&self, spec: &str) -> Result<(u64, u64)> {
+        self.fetcher.resolve_range(spec).await
+    }
+
+    /// Returns all receipts for a block, aligned with the block's transactions.
     /// Tries to use `eth_getBlockReceipts` first, and falls back to `eth_getTransactionReceipt`
     pub async fn get_tx_receipts_in_block(
         &self,
         block: &Block<Transaction>,
+    ) -> Result<Vec<Option<TransactionReceipt>>> {
+        let block_number = block.number.ok_or(CollectError::MissingBlockNumber)?.as_u64();
+        self.get_receipts_with_fallback(block_number, || async {
+            Ok(block.transactions.iter().map(|tx| tx.hash).collect())
+        })
+        .await
+    }
+
+    /// Returns all receipts for a block number, in transaction order.
+    /// Tries to use `eth_getBlockReceipts` first, and falls back to fetching the block's
+    /// transaction hashes and calling `eth_getTransactionReceipt` for each of them. Missing
+    /// receipts are returned as `MissingReceipt` errors
+    pub async fn get_receipts_in_block(
+        &self,
+        block_number: u64,
     ) -> Result<Vec<TransactionReceipt>> {
-        let block_number =
-            block.number.ok_or(CollectError::CollectError("no block number".to_string()))?.as_u64();
         if let Ok(receipts) = self.fetcher.get_block_receipts(block_number).await {
             return Ok(receipts)
         }
-
-        self.get_tx_receipts(&block.transactions).await
+        let tx_hashes = self
+            .fetcher
+            .get_block(block_number)
+            .await?
+            .ok_or(CollectError::BlockNotFound { number: block_number })?
+            .transactions;
+        let receipts = self.fetcher.get_transaction_receipts(&tx_hashes).await?;
+        tx_hashes
+            .into_iter()
+            .zip(receipts)
+            .map(|(tx_hash, receipt)| receipt.ok_or(CollectError::MissingReceipt { tx_hash }))
+            .collect()
     }
 
-    /// Returns all receipts for vector of transactions using `eth_getTransactionReceipt`
-    pub async fn get_tx_receipts(
+    /// fetch receipts with `eth_getBlockReceipts`, only resolving the block's transaction hashes
+    /// if the node does not support it
+    async fn get_receipts_with_fallback<F, Fut>(
         &self,
-        transactions: &Vec<Transaction>,
-    ) -> Result<Vec<TransactionReceipt>> {
-        let mut tasks = Vec::new();
-        for tx in transactions {
-            let tx_hash = tx.hash;
-            let fetcher = self.fetcher.clone();
-            let task = task::spawn(async move {
-                match fetcher.get_transaction_receipt(tx_hash).await? {
-                    Some(receipt) => Ok(receipt),
-                    None => {
-                        Err(CollectError::CollectError("could not find tx receipt".to_string()))
-                    }
-                }
-            });
-            tasks.push(task);
-        }
-        let mut receipts = Vec::new();
-        for task in tasks {
-            match task.await {
-                Ok(receipt) => receipts.push(receipt?),
-                Err(e) => return Err(CollectError::TaskFailed(e)),
-            }
+        block_number: u64,
+        tx_hashes: F,
+    ) -> Result<Vec<Option<TransactionReceipt>>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<TxHash>>>,
+    {
+        if let Ok(receipts) = self.fetcher.get_block_receipts(block_number).await {
+            return Ok(receipts.into_iter().map(Some).collect())
         }
+        self.fetcher.get_transaction_receipts(&tx_hashes().await?).await
+    }
 
-        Ok(receipts)
+    /// Returns receipts for vector of transactions using `eth_getTransactionReceipt`, aligned
+    /// with the transactions. Missing receipts are returned as None.
+    pub async fn get_tx_receipts(
+        &self,
+        transactions: &[Transaction],
+    ) -> Result<Vec<Option<TransactionReceipt>>> {
+        let tx_hashes: Vec<TxHash> = transactions.iter().map(|tx| tx.hash).collect();
+        self.fetcher.get_transaction_receipts(&tx_hashes).await
     }
 }
 
@@ -101,16 +226,672 @@ pub struct Fetcher<P> {
     pub semaphore: Option<Semaphore>,
     /// rate limiter for controlling request rate
     pub rate_limiter: Option<RateLimiter>,
+    /// rate limiters used instead of `rate_limiter` for specific categories of methods
+    pub method_rate_limiters: HashMap<MethodCategory, RateLimiter>,
+    /// time after which a request of each category of methods is abandoned and retried
+    pub request_timeouts: HashMap<MethodCategory, Duration>,
+    /// retry policy for recoverable request errors
+    pub retry: RetryConfig,
+    /// optional cache of contract code at historical blocks
+    pub code_cache: Option<CodeCache>,
+    /// optional sink for request latencies
+    pub latency: Option<Arc<Mutex<LatencyStats>>>,
+    /// number of requests made, including retries
+    pub request_count: AtomicU64,
+    /// optional callback reporting request progress
+    pub progress: Option<RequestProgress>,
+    /// chain head used for timestamp lookups and relative block refs, fetched on first use
+    pub head_block: OnceCell<u64>,
+    /// whether Multicall3 is deployed at its canonical address, checked on first use
+    pub multicall3_deployed: OnceCell<bool>,
+    /// trace methods supported by the node, probed on first use
+    pub capabilities: OnceCell<Capabilities>,
+    /// optional circuit breaker that fails fast while the endpoint is down
+    pub circuit_breaker: Option<CircuitBreaker>,
+}
+
+/// Category of rpc method, used to rate limit expensive methods separately
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum MethodCategory {
+    /// state and block reads
+    Read,
+    /// trace and debug methods
+    Trace,
+    /// log queries
+    Log,
+}
+
+impl MethodCategory {
+    /// default request timeout, traces take much longer to compute than reads
+    pub fn default_timeout(&self) -> Duration {
+        match self {
+            MethodCategory::Read => Duration::from_secs(30),
+            MethodCategory::Log => Duration::from_secs(60),
+            MethodCategory::Trace => Duration::from_secs(300),
+        }
+    }
+
+    /// category of a Fetcher method name
+    pub fn of(method: &str) -> MethodCategory {
+        if method.starts_with("trace_") || method.starts_with("debug_trace") {
+            MethodCategory::Trace
+        } else if method == "get_logs" {
+            MethodCategory::Log
+        } else {
+            MethodCategory::Read
+        }
+    }
+}
+
+/// Family of trace methods that only some nodes serve
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Capability {
+    /// parity style `trace_block`, `trace_transaction` and `trace_filter`
+    Trace,
+    /// parity style `trace_replayBlockTransactions` and `trace_replayTransaction`
+    TraceReplay,
+    /// geth style `debug_traceBlockByNumber` and `debug_traceTransaction`
+    DebugTrace,
+}
+
+impl Capability {
+    /// rpc method probed to detect the capability
+    pub fn probe_method(&self) -> &'static str {
+        match self {
+            Capability::Trace => "trace_block",
+            Capability::TraceReplay => "trace_replayBlockTransactions",
+            Capability::DebugTrace => "debug_traceBlockByNumber",
+        }
+    }
+}
+
+/// Trace methods supported by a node, found by `Fetcher::probe_capabilities`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Capabilities {
+    /// whether `trace_block` is supported
+    pub trace: bool,
+    /// whether `trace_replayBlockTransactions` is supported
+    pub trace_replay: bool,
+    /// whether `debug_traceBlockByNumber` is supported
+    pub debug_trace: bool,
+}
+
+impl Capabilities {
+    /// whether the node supports a capability
+    pub fn supports(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Trace => self.trace,
+            Capability::TraceReplay => self.trace_replay,
+            Capability::DebugTrace => self.debug_trace,
+        }
+    }
+}
+
+/// Callback invoked with the total number of requests made, every `interval` requests
+pub struct RequestProgress {
+    /// number of requests between invocations
+    pub interval: u64,
+    /// callback receiving the total number of requests made
+    pub callback: Box<dyn Fn(u64) + Send + Sync>,
+}
+
+/// Cache of contract code keyed by (address, block number)
+///
+/// Code can change through SELFDESTRUCT and redeployment, so entries are keyed by block. Only
+/// blocks at least `reorg_depth` blocks behind the chain head are cached.
+pub struct CodeCache {
+    /// cached code
+    pub codes: Arc<DashMap<(H160, u64), Bytes>>,
+    /// minimum distance from the chain head for a block to be cached
+    pub reorg_depth: u64,
+    /// chain head, fetched on first use
+    latest_block: OnceCell<u64>,
+}
+
+/// Durations of provider requests, grouped by method
+#[derive(Debug, Default)]
+pub struct LatencyStats {
+    /// request durations by method
+    pub durations: HashMap<&'static str, Vec<Duration>>,
+}
+
+/// Latency summary of requests to a single method
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LatencySummary {
+    /// number of requests
+    pub count: usize,
+    /// median latency
+    pub p50: Duration,
+    /// 95th percentile latency
+    pub p95: Duration,
+    /// maximum latency
+    pub max: Duration,
+}
+
+impl LatencyStats {
+    /// record the duration of a request
+    pub fn record(&mut self, method: &'static str, duration: Duration) {
+        self.durations.entry(method).or_default().push(duration);
+    }
+
+    /// count, p50, p95, and max latency of each method
+    pub fn summary(&self) -> BTreeMap<&'static str, LatencySummary> {
+        let mut summary = BTreeMap::new();
+        for (method, durations) in self.durations.iter() {
+            let mut durations = durations.clone();
+            durations.sort();
+            let percentile = |p: f64| {
+                let rank = (p * durations.len() as f64).ceil() as usize;
+                durations[rank.clamp(1, durations.len()) - 1]
+            };
+            if let Some(max) = durations.last() {
+                let entry = LatencySummary {
+                    count: durations.len(),
+                    p50: percentile(0.50),
+                    p95: percentile(0.95),
+                    max: *max,
+                };
+                summary.insert(*method, entry);
+            }
+        }
+        summary
+    }
+}
+
+/// State of a `CircuitBreaker`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CircuitState {
+    /// requests are sent normally
+    Closed,
+    /// requests fail fast until the cooldown has elapsed
+    Open,
+    /// cooldown has elapsed, a single probe request tests recovery
+    HalfOpen,
+}
+
+/// Circuit breaker that pauses requests after consecutive recoverable failures
+pub struct CircuitBreaker {
+    /// consecutive failures that open the circuit
+    pub failure_threshold: u32,
+    /// time the circuit stays open before a probe request is allowed
+    pub cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    probe_started_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// create a closed circuit breaker
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> CircuitBreaker {
+        CircuitBreaker {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// current state of the circuit
+    pub fn state(&self) -> CircuitState {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match state.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.cooldown => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// number of consecutive failures recorded
+    pub fn consecutive_failures(&self) -> u32 {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).consecutive_failures
+    }
+
+    /// whether a request may be sent, fails fast while open or while a probe is in flight
+    fn allow_request(&self) -> ::core::result::Result<(), CollectError> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let opened_at = match state.opened_at {
+            None => return Ok(()),
+            Some(opened_at) => opened_at,
+        };
+        let probe_in_flight = state
+            .probe_started_at
+            .map(|started| started.elapsed() < self.cooldown)
+            .unwrap_or(false);
+        if opened_at.elapsed() < self.cooldown || probe_in_flight {
+            return Err(CollectError::CircuitOpen(state.consecutive_failures))
+        }
+        state.probe_started_at = Some(Instant::now());
+        Ok(())
+    }
+
+    /// record the outcome of a request, `failed` is true for recoverable endpoint failures
+    fn record(&self, failed: bool) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if !failed {
+            *state = BreakerState::default();
+            return
+        }
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        // a failed probe restarts the cooldown
+        if state.probe_started_at.take().is_some() ||
+            state.consecutive_failures >= self.failure_threshold
+        {
+            state.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+/// Retry policy used by `Fetcher` for recoverable request errors
+#[derive(Clone, Debug)]
+pub struct RetryConfig {
+    /// maximum number of retries after the initial attempt
+    pub max_retries: u32,
+    /// delay before the first retry, doubled for each subsequent retry
+    pub base_delay: Duration,
+    /// upper bound on the delay between retries
+    pub max_delay: Duration,
+    /// whether to randomize delays to avoid synchronized retries
+    pub jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> RetryConfig {
+        RetryConfig {
+            max_retries: 5,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// delay to wait before the given retry attempt (starting at 0)
+    fn delay(&self, attempt: u32) -> Duration {
+        let delay =
+            self.base_delay.saturating_mul(2u32.saturating_pow(attempt)).min(self.max_delay);
+        if self.jitter {
+            let nanos = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|t| t.subsec_nanos())
+                .unwrap_or_default();
+            delay.mul_f64(0.5 + 0.5 * (nanos as f64 / 1e9))
+        } else {
+            delay
+        }
+    }
+}
+
+/// whether a provider error is transient and worth retrying
+pub(crate) fn is_recoverable_error(error: &ProviderError) -> bool {
+    match error {
+        ProviderError::HTTPError(e) => {
+            e.is_timeout() ||
+                e.is_connect() ||
+                e.status().map(|status| status.as_u16() == 429).unwrap_or(false)
+        }
+        ProviderError::JsonRpcClientError(e) => {
+            let message = e.to_string().to_lowercase();
+            // oversized log queries fail the same way on every attempt
+            if is_too_many_logs_message(&message) {
+                return false
+            }
+            if let Some(response) = e.as_error_response() {
+                if response.code == 429 || response.code == -32005 {
+                    return true
+                }
+            }
+            ["timeout", "timed out", "rate limit", "too many requests", "connection reset", "429"]
+                .iter()
+                .any(|pattern| message.contains(pattern))
+        }
+        _ => false,
+    }
 }
 
 type Result<T> = ::core::result::Result<T, CollectError>;
 
+fn new_rate_limiter(max_requests_per_second: u32) -> Option<RateLimiter> {
+    NonZeroU32::new(max_requests_per_second).map(|value| {
+        let quota = Quota::per_second(value).allow_burst(NonZeroU32::MIN);
+        RateLimiter::direct(quota)
+    })
+}
+
 impl<P: JsonRpcClient> Fetcher<P> {
+    /// create a Fetcher with optional concurrency and requests-per-second limits
+    pub fn new(
+        provider: Provider<P>,
+        max_concurrent_requests: Option<u64>,
+        max_requests_per_second: Option<u32>,
+        retry: RetryConfig,
+    ) -> Self {
+        let semaphore = max_concurrent_requests.map(|n| Semaphore::new(n as usize));
+        let rate_limiter = max_requests_per_second.and_then(new_rate_limiter);
+        Fetcher {
+            provider,
+            semaphore,
+            rate_limiter,
+            method_rate_limiters: HashMap::new(),
+            request_timeouts: [MethodCategory::Read, MethodCategory::Log, MethodCategory::Trace]
+                .into_iter()
+                .map(|category| (category, category.default_timeout()))
+                .collect(),
+            retry,
+            code_cache: None,
+            latency: None,
+            request_count: AtomicU64::new(0),
+            progress: None,
+            head_block: OnceCell::new(),
+            multicall3_deployed: OnceCell::new(),
+            capabilities: OnceCell::new(),
+            circuit_breaker: None,
+        }
+    }
+
+    /// Fail fast for `cooldown` after `failure_threshold` consecutive recoverable failures
+    pub fn with_circuit_breaker(mut self, failure_threshold: u32, cooldown: Duration) -> Self {
+        self.circuit_breaker = Some(CircuitBreaker::new(failure_threshold, cooldown));
+        self
+    }
+
+    /// state of the circuit breaker, `None` if no circuit breaker is configured
+    pub fn circuit_state(&self) -> Option<CircuitState> {
+        self.circuit_breaker.as_ref().map(|breaker| breaker.state())
+    }
+
+    /// Cache `get_code` results for blocks at least `reorg_depth` blocks behind the chain head
+    pub fn with_code_cache(
+        mut self,
+        codes: Arc<DashMap<(H160, u64), Bytes>>,
+        reorg_depth: u64,
+    ) -> Self {
+        self.code_cache = Some(CodeCache { codes, reorg_depth, latest_block: OnceCell::new() });
+        self
+    }
+
+    /// Limit methods of `category` to `max_requests_per_second`, instead of the global limit
+    pub fn with_method_rate_limit(
+        mut self,
+        category: MethodCategory,
+        max_requests_per_second: u32,
+    ) -> Self {
+        match new_rate_limiter(max_requests_per_second) {
+            Some(limiter) => self.method_rate_limiters.insert(category, limiter),
+            None => self.method_rate_limiters.remove(&category),
+        };
+        self
+    }
+
+    /// Abandon and retry requests of `category` that take longer than `timeout`, zero disables
+    pub fn with_request_timeout(mut self, category: MethodCategory, timeout: Duration) -> Self {
+        if timeout.is_zero() {
+            self.request_timeouts.remove(&category);
+        } else {
+            self.request_timeouts.insert(category, timeout);
+        }
+        self
+    }
+
+    /// rate limiter that applies to `method`
+    fn rate_limiter_for(&self, method: &str) -> Option<&RateLimiter> {
+        self.method_rate_limiters.get(&MethodCategory::of(method)).or(self.rate_limiter.as_ref())
+    }
+
+    /// Record the latency of each request into `latency`
+    pub fn with_latency_stats(mut self, latency: Arc<Mutex<LatencyStats>>) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Call `callback` with the total number of requests made every `interval` requests
+    pub fn with_progress_callback(
+        mut self,
+        interval: u64,
+        callback: impl Fn(u64) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress =
+            Some(RequestProgress { interval: interval.max(1), callback: Box::new(callback) });
+        self
+    }
+
+    /// number of requests made so far, including retries
+    pub fn requests_made(&self) -> u64 {
+        self.request_count.load(Ordering::Relaxed)
+    }
+
+    /// count, p50, p95, and max latency of each method, empty if latencies are not recorded
+    pub fn latency_summary(&self) -> BTreeMap<&'static str, LatencySummary> {
+        match self.latency.as_ref().and_then(|latency| latency.lock().ok()) {
+            Some(latency) => latency.summary(),
+            None => BTreeMap::new(),
+        }
+    }
+
+    /// Split block chunks into chunks that are at least `reorg_buffer` blocks behind the chain
+    /// head and chunks that are skipped because they reach into the reorg buffer
+    pub async fn reorg_safe_chunks(
+        &self,
+        block_chunks: Vec<BlockChunk>,
+        reorg_buffer: u64,
+    ) -> Result<(Vec<BlockChunk>, Vec<BlockChunk>)> {
+        if reorg_buffer == 0 {
+            return Ok((block_chunks, Vec::new()))
+        }
+        let latest_block = self.get_block_number().await?.as_u64();
+        let max_allowed = latest_block.saturating_sub(reorg_buffer);
+        Ok(block_chunks.into_iter().partition(|chunk| match chunk.max_value() {
+            Some(max_block) => latest_block >= reorg_buffer && max_block <= max_allowed,
+            None => false,
+        }))
+    }
+
+    /// Returns the chain head, fetched once and reused so that relative block refs resolved by
+    /// this fetcher agree with each other
+    pub async fn head_block_number(&self) -> Result<u64> {
+        self.head_block
+            .get_or_try_init(|| async { self.get_block_number().await.map(|n| n.as_u64()) })
+            .await
+            .copied()
+    }
+
+    /// Resolves a `start:end` block range to block numbers, both ends inclusive
+    ///
+    /// Each end is a block number, `latest`, or `latest-N` for N blocks behind the chain head.
+    /// Offsets larger than the chain head clamp to block 0.
+    pub async fn resolve_range(&self, spec: &str) -> Result<(u64, u64)> {
+        let (start, end) =
+            spec.split_once(':').ok_or_else(|| err("block range must be in format start:end"))?;
+        let start = self.resolve_block_ref(start).await?;
+        let end = self.resolve_block_ref(end).await?;
+        if end < start {
+            return Err(err(&format!("block range {} ends before it starts", spec)))
+        }
+        Ok((start, end))
+    }
+
+    async fn resolve_block_ref(&self, block_ref: &str) -> Result<u64> {
+        let block_ref = block_ref.trim().replace('_', "");
+        let offset = match block_ref.strip_prefix("latest") {
+            Some("") => 0,
+            Some(offset) => offset
+                .strip_prefix('-')
+                .and_then(|offset| offset.parse::<u64>().ok())
+                .ok_or_else(|| err(&format!("invalid block ref: {}", block_ref)))?,
+            None => {
+                return block_ref
+                    .parse::<u64>()
+                    .map_err(|_| err(&format!("invalid block ref: {}", block_ref)))
+            }
+        };
+        Ok(self.head_block_number().await?.saturating_sub(offset))
+    }
+
+    /// Returns the first block with a timestamp at or after `timestamp`
+    ///
+    /// Binary searches block timestamps between block 0 and the chain head.
+    pub async fn block_number_at_timestamp(&self, timestamp: u64) -> Result<u64> {
+        let head = self.head_block_number().await?;
+        let block_timestamp = |block_number: u64| async move {
+            self.get_block(block_number)
+                .await?
+                .map(|block| block.timestamp.as_u64())
+                .ok_or(CollectError::BlockNotFound { number: block_number })
+        };
+        if block_timestamp(head).await? < timestamp {
+            return Err(err(&format!("no block at or after timestamp {}", timestamp)))
+        }
+        let (mut low, mut high) = (0, head);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if block_timestamp(mid).await? < timestamp {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(low)
+    }
+
     /// Returns an array (possibly empty) of logs that match the filter
     pub async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
         // println!("Filter: {:?}", filter);
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.get_logs(filter).await)
+        self.with_retry("get_logs", || self.provider.get_logs(filter)).await
+    }
+
+    /// Returns logs of the block with the given hash that match the topics
+    ///
+    /// unlike a block number range, a block hash cannot resolve to a reorged-out block
+    pub async fn get_logs_at_block_hash(
+        &self,
+        block_hash: H256,
+        topics: [Option<Topic>; 4],
+    ) -> Result<Vec<Log>> {
+        let mut filter = Filter::new().at_block_hash(block_hash);
+        filter.topics = topics;
+        self.get_logs(&filter).await
+    }
+
+    /// Returns logs that match the filter, splitting the filter's block range into sub-ranges of
+    /// at most `max_range` blocks. Sub-ranges that exceed the provider's result limit are
+    /// recursively halved. Logs returned more than once are deduplicated.
+    pub async fn get_logs_chunked(&self, filter: &Filter, max_range: u64) -> Result<Vec<Log>> {
+        let (start, end) = match filter.block_option {
+            FilterBlockOption::Range {
+                from_block: Some(BlockNumber::Number(from_block)),
+                to_block: Some(BlockNumber::Number(to_block)),
+            } => (from_block.as_u64(), to_block.as_u64()),
+            _ => return self.get_logs(filter).await,
+        };
+
+        // ranges are half-open so that adjacent ranges never share a boundary block
+        let max_range = max_range.max(1);
+        let end = end.saturating_add(1);
+        let mut ranges: std::collections::VecDeque<(u64, u64)> = (start..end)
+            .step_by(max_range as usize)
+            .map(|range_start| (range_start, range_start.saturating_add(max_range).min(end)))
+            .collect();
+
+        let mut logs = Vec::new();
+        while let Some((range_start, range_end)) = ranges.pop_front() {
+            let range_filter = filter.clone().from_block(range_start).to_block(range_end - 1);
+            match self.get_logs(&range_filter).await {
+                Ok(range_logs) => logs.extend(range_logs),
+                Err(e) if is_too_many_logs_error(&e) => {
+                    if range_end - range_start == 1 {
+                        return Err(err(&format!(
+                            "logs of block {} exceed the provider's result limit",
+                            range_start
+                        )))
+                    }
+                    let mid = range_start + (range_end - range_start) / 2;
+                    ranges.push_front((mid, range_end));
+                    ranges.push_front((range_start, mid));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(dedup_logs(logs))
+    }
+
+    /// Returns logs matching the filter like `get_logs_chunked`, but first fetches the headers of
+    /// the filter's blocks and skips blocks whose logs bloom cannot contain its topic0 or address
+    ///
+    /// each header costs a request, so this trades wide `eth_getLogs` calls for header requests.
+    /// single blocks are never filtered, and filtering stops once a batch of headers shows that
+    /// most blocks may contain matching logs
+    pub async fn get_logs_bloom_filtered(
+        &self,
+        filter: &Filter,
+        max_range: u64,
+    ) -> Result<Vec<Log>> {
+        let (start, end) = match filter.block_option {
+            FilterBlockOption::Range {
+                from_block: Some(BlockNumber::Number(from_block)),
+                to_block: Some(BlockNumber::Number(to_block)),
+            } => (from_block.as_u64(), to_block.as_u64()),
+            _ => return self.get_logs_chunked(filter, max_range).await,
+        };
+        let topic0s: Vec<H256> = match &filter.topics[0] {
+            Some(ValueOrArray::Value(Some(topic0))) => vec![*topic0],
+            Some(ValueOrArray::Array(topic0s)) if topic0s.iter().all(Option::is_some) => {
+                topic0s.iter().flatten().copied().collect()
+            }
+            _ => Vec::new(),
+        };
+        let addresses: Vec<H160> = match &filter.address {
+            Some(ValueOrArray::Value(address)) => vec![*address],
+            Some(ValueOrArray::Array(addresses)) => addresses.clone(),
+            None => Vec::new(),
+        };
+        if topic0s.is_empty() && addresses.is_empty() {
+            return self.get_logs_chunked(filter, max_range).await
+        }
+
+        // a header costs as much as the eth_getLogs call it could skip
+        if start >= end {
+            return self.get_logs_chunked(filter, max_range).await
+        }
+
+        // merge blocks that may contain matching logs into contiguous ranges
+        let mut ranges: Vec<(u64, u64)> = Vec::new();
+        let mut next_batch = Some(start);
+        while let Some(batch_start) = next_batch.filter(|batch_start| *batch_start <= end) {
+            let batch_end = batch_start.saturating_add(BLOOM_FILTER_BATCH_SIZE - 1).min(end);
+            let block_numbers: Vec<u64> = (batch_start..=batch_end).collect();
+            let blocks = self.get_blocks(&block_numbers).await?;
+            let mut n_matching = 0;
+            for (block_number, block) in block_numbers.iter().zip(blocks) {
+                let may_match = match block.and_then(|block| block.logs_bloom) {
+                    Some(bloom) => bloom_contains(&bloom, &topic0s, &addresses),
+                    None => true,
+                };
+                if may_match {
+                    n_matching += 1;
+                    push_block_range(&mut ranges, *block_number, *block_number);
+                }
+            }
+            next_batch = batch_end.checked_add(1);
+
+            // dense ranges are cheaper to fetch without their headers
+            if 2 * n_matching > block_numbers.len() {
+                if let Some(rest_start) = next_batch.filter(|rest_start| *rest_start <= end) {
+                    push_block_range(&mut ranges, rest_start, end);
+                }
+                break
+            }
+        }
+
+        let mut logs = Vec::new();
+        for (range_start, range_end) in ranges {
+            let range_filter = filter.clone().from_block(range_start).to_block(range_end);
+            logs.extend(self.get_logs_chunked(&range_filter, max_range).await?);
+        }
+        Ok(logs)
     }
 
     /// Replays all transactions in a block returning the requested traces for each transaction
@@ -119,8 +900,10 @@ impl<P: JsonRpcClient> Fetcher<P> {
         block: BlockNumber,
         trace_types: Vec<TraceType>,
     ) -> Result<Vec<BlockTrace>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.trace_replay_block_transactions(block, trace_types).await)
+        self.with_retry("trace_replay_block_transactions", || {
+            self.provider.trace_replay_block_transactions(block, trace_types.clone())
+        })
+        .await
     }
 
     /// Get state diff traces of block
@@ -141,7 +924,7 @@ impl<P: JsonRpcClient> Fetcher<P> {
         let txs = if include_transaction_hashes {
             self.get_block(block as u64)
                 .await?
-                .ok_or(CollectError::CollectError("could not find block".to_string()))?
+                .ok_or(CollectError::BlockNotFound { number: block as u64 })?
                 .transactions
                 .iter()
                 .map(|tx| Some(tx.0.to_vec()))
@@ -153,15 +936,39 @@ impl<P: JsonRpcClient> Fetcher<P> {
         Ok((Some(block), txs, result))
     }
 
-    /// Get VM traces of block
+    /// Get VM traces of block, each paired with the index of its transaction
+    ///
+    /// nodes may omit transactions without a vm trace, so indices are looked up by
+    /// transaction hash in the block rather than taken from the order of the traces
     pub async fn trace_block_vm_traces(
         &self,
         block: u32,
-    ) -> Result<(Option<u32>, Option<Vec<u8>>, Vec<BlockTrace>)> {
-        let result = self
+    ) -> Result<(Option<u32>, Option<Vec<u8>>, Vec<(u32, BlockTrace)>)> {
+        let traces = self
             .trace_replay_block_transactions(block.into(), vec![ethers::types::TraceType::VmTrace])
-            .await;
-        Ok((Some(block), None, result?))
+            .await?;
+        let positions: HashMap<TxHash, usize> = self
+            .get_block(block as u64)
+            .await?
+            .ok_or(CollectError::BlockNotFound { number: block as u64 })?
+            .transactions
+            .into_iter()
+            .enumerate()
+            .map(|(position, tx_hash)| (tx_hash, position))
+            .collect();
+        let traces = traces
+            .into_iter()
+            .map(|trace| {
+                let position = trace
+                    .transaction_hash
+                    .and_then(|tx_hash| positions.get(&tx_hash).copied())
+                    .ok_or_else(|| {
+                        err(&format!("vm trace of a transaction not in block {}", block))
+                    })?;
+                Ok((position as u32, trace))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((Some(block), None, traces))
     }
 
     /// Replays a transaction, returning the traces
@@ -170,8 +977,10 @@ impl<P: JsonRpcClient> Fetcher<P> {
         tx_hash: TxHash,
         trace_types: Vec<TraceType>,
     ) -> Result<BlockTrace> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.trace_replay_transaction(tx_hash, trace_types).await)
+        self.with_retry("trace_replay_transaction", || {
+            self.provider.trace_replay_transaction(tx_hash, trace_types.clone())
+        })
+        .await
     }
 
     /// Get state diff traces of transaction
@@ -188,24 +997,78 @@ impl<P: JsonRpcClient> Fetcher<P> {
         Ok((None, vec![Some(transaction_hash)], vec![result?]))
     }
 
+    /// Get state diffs of block, using geth's prestateTracer on nodes without trace_replay
+    pub async fn block_state_diffs(
+        &self,
+        block: u32,
+        include_transaction_hashes: bool,
+    ) -> Result<(Option<u32>, Vec<Option<Vec<u8>>>, Vec<BlockTrace>)> {
+        if self.capabilities().await?.trace_replay {
+            return self.trace_block_state_diffs(block, include_transaction_hashes).await
+        }
+        let (block, txs, diffs) =
+            self.geth_debug_trace_block_diffs(block, include_transaction_hashes).await?;
+        let traces = diffs.into_iter().map(geth_diff_to_block_trace).collect::<Result<_>>()?;
+        Ok((block, txs, traces))
+    }
+
+    /// Get state diffs of transaction, using geth's prestateTracer on nodes without trace_replay
+    pub async fn transaction_state_diffs(
+        &self,
+        transaction_hash: Vec<u8>,
+    ) -> Result<(Option<u32>, Vec<Option<Vec<u8>>>, Vec<BlockTrace>)> {
+        if self.capabilities().await?.trace_replay {
+            return self.trace_transaction_state_diffs(transaction_hash).await
+        }
+        let (_, _, diffs) =
+            self.geth_debug_trace_transaction_diffs(transaction_hash.clone(), false).await?;
+        let traces = diffs.into_iter().map(geth_diff_to_block_trace).collect::<Result<_>>()?;
+        Ok((None, vec![Some(transaction_hash)], traces))
+    }
+
     /// Get VM traces of transaction
     pub async fn trace_transaction_vm_traces(
         &self,
         transaction_hash: Vec<u8>,
-    ) -> Result<(Option<u32>, Option<Vec<u8>>, Vec<BlockTrace>)> {
+    ) -> Result<(Option<u32>, Option<Vec<u8>>, Vec<(u32, BlockTrace)>)> {
         let result = self
             .trace_replay_transaction(
                 H256::from_slice(&transaction_hash),
                 vec![ethers::types::TraceType::VmTrace],
             )
             .await;
-        Ok((None, Some(transaction_hash), vec![result?]))
+        Ok((None, Some(transaction_hash), vec![(0, result?)]))
     }
 
     /// Gets the transaction with transaction_hash
     pub async fn get_transaction(&self, tx_hash: TxHash) -> Result<Option<Transaction>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.get_transaction(tx_hash).await)
+        self.with_retry("get_transaction", || self.provider.get_transaction(tx_hash)).await
+    }
+
+    /// Builds the access list of a transaction executed at `block_number` using
+    /// `eth_createAccessList`
+    pub async fn create_access_list(
+        &self,
+        tx: TransactionRequest,
+        block_number: BlockNumber,
+    ) -> Result<AccessListWithGasUsed> {
+        let tx: TypedTransaction = tx.into();
+        self.with_retry("create_access_list", || {
+            self.provider.create_access_list(&tx, Some(block_number.into()))
+        })
+        .await
+    }
+
+    /// Gets the transaction at `index` of the block at `block_num`, without fetching the block
+    pub async fn get_transaction_by_block_and_index(
+        &self,
+        block_num: u64,
+        index: u64,
+    ) -> Result<Option<Transaction>> {
+        self.with_retry("get_transaction_by_block_and_index", || {
+            self.provider.get_transaction_by_block_and_index(block_num, index.into())
+        })
+        .await
     }
 
     /// Gets the transaction receipt with transaction_hash
@@ -213,26 +1076,67 @@ impl<P: JsonRpcClient> Fetcher<P> {
         &self,
         tx_hash: TxHash,
     ) -> Result<Option<TransactionReceipt>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.get_transaction_receipt(tx_hash).await)
+        self.with_retry("get_transaction_receipt", || {
+            self.provider.get_transaction_receipt(tx_hash)
+        })
+        .await
     }
 
     /// Gets the block at `block_num` (transaction hashes only)
     pub async fn get_block(&self, block_num: u64) -> Result<Option<Block<TxHash>>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.get_block(block_num).await)
+        self.with_retry("get_block", || self.provider.get_block(block_num)).await
     }
 
     /// Gets the block at `block_num` (transaction hashes only)
     pub async fn get_block_by_hash(&self, block_hash: H256) -> Result<Option<Block<TxHash>>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.get_block(BlockId::Hash(block_hash)).await)
+        self.with_retry("get_block", || self.provider.get_block(BlockId::Hash(block_hash))).await
+    }
+
+    /// Gets the blocks at `block_nums` (transaction hashes only), in the same order as input
+    ///
+    /// ethers transports do not expose json-rpc batching, so blocks are requested concurrently,
+    /// with concurrency bounded by the semaphore
+    pub async fn get_blocks(&self, block_nums: &[u64]) -> Result<Vec<Option<Block<TxHash>>>> {
+        futures::future::try_join_all(block_nums.iter().map(|block_num| self.get_block(*block_num)))
+            .await
+    }
+
+    /// Returns the number of uncles of the block at `block_num`
+    pub async fn get_uncle_count(&self, block_num: u64) -> Result<u64> {
+        let count =
+            self.with_retry("get_uncle_count", || self.provider.get_uncle_count(block_num)).await?;
+        Ok(count.as_u64())
+    }
+
+    /// Returns the uncle at `index` of the block at `block_num`
+    pub async fn get_uncle(&self, block_num: u64, index: u64) -> Result<Option<Block<TxHash>>> {
+        self.with_retry("get_uncle", || self.provider.get_uncle(block_num, index.into())).await
     }
 
     /// Gets the block at `block_num` (full transactions included)
     pub async fn get_block_with_txs(&self, block_num: u64) -> Result<Option<Block<Transaction>>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.get_block_with_txs(block_num).await)
+        self.with_retry("get_block_with_txs", || self.provider.get_block_with_txs(block_num)).await
+    }
+
+    /// Gets the pending block of the node (full transactions included)
+    ///
+    /// the contents depend on the node's mempool at the time of the request
+    pub async fn get_pending_block(&self) -> Result<Option<Block<Transaction>>> {
+        self.with_retry("get_pending_block", || {
+            self.provider.get_block_with_txs(BlockNumber::Pending)
+        })
+        .await
+    }
+
+    /// Returns receipts of multiple transactions, in the same order as `tx_hashes`
+    pub async fn get_transaction_receipts(
+        &self,
+        tx_hashes: &[TxHash],
+    ) -> Result<Vec<Option<TransactionReceipt>>> {
+        futures::future::try_join_all(
+            tx_hashes.iter().map(|tx_hash| self.get_transaction_receipt(*tx_hash)),
+        )
+        .await
     }
 
     /// Returns all receipts for a block.
@@ -240,20 +1144,72 @@ impl<P: JsonRpcClient> Fetcher<P> {
     /// Consider using `FetcherExt::get_tx_receipts_in_block` which takes a block, and falls back to
     /// `eth_getTransactionReceipt` if `eth_getBlockReceipts` is not supported.
     pub async fn get_block_receipts(&self, block_num: u64) -> Result<Vec<TransactionReceipt>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.get_block_receipts(block_num).await)
+        self.with_retry("get_block_receipts", || self.provider.get_block_receipts(block_num)).await
     }
 
     /// Returns traces created at given block
     pub async fn trace_block(&self, block_num: BlockNumber) -> Result<Vec<Trace>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.trace_block(block_num).await)
+        self.with_retry("trace_block", || self.provider.trace_block(block_num)).await
+    }
+
+    /// Returns all traces of a block, each paired with the hash of its transaction
+    ///
+    /// hashes are looked up by `transaction_position` in the block, rewards have no hash
+    pub async fn trace_block_joined(&self, block_num: u64) -> Result<Vec<(Trace, Option<TxHash>)>> {
+        let traces = self.trace_block(block_num.into()).await?;
+        let block = self
+            .get_block(block_num)
+            .await?
+            .ok_or(CollectError::BlockNotFound { number: block_num })?;
+        let joined = traces
+            .into_iter()
+            .map(|trace| {
+                let tx_hash = trace
+                    .transaction_position
+                    .and_then(|position| block.transactions.get(position).copied());
+                (trace, tx_hash)
+            })
+            .collect();
+        Ok(joined)
+    }
+
+    /// Returns traces matching the filter's block range and addresses
+    pub async fn trace_filter(&self, filter: TraceFilter) -> Result<Vec<Trace>> {
+        self.with_retry("trace_filter", || self.provider.trace_filter(filter.clone())).await
+    }
+
+    /// Returns traces matching the filter like `trace_filter`, requesting them in pages of
+    /// `page_size` traces so that large ranges stay under the node's result limit
+    pub async fn trace_filter_paged(
+        &self,
+        filter: TraceFilter,
+        page_size: usize,
+    ) -> Result<Vec<Trace>> {
+        let page_size = page_size.max(1);
+        let mut traces = Vec::new();
+        loop {
+            let page_filter = filter.clone().after(traces.len()).count(page_size);
+            let page = self.trace_filter(page_filter).await?;
+            let n_page = page.len();
+            traces.extend(page);
+            if n_page < page_size {
+                return Ok(traces)
+            }
+        }
     }
 
     /// Returns all traces of a given transaction
     pub async fn trace_transaction(&self, tx_hash: TxHash) -> Result<Vec<Trace>> {
-        let _permit = self.permit_request().await;
-        self.provider.trace_transaction(tx_hash).await.map_err(CollectError::ProviderError)
+        self.with_retry("trace_transaction", || self.provider.trace_transaction(tx_hash)).await
+    }
+
+    /// Returns the trace at trace address `index` of a transaction, `None` if there is none
+    pub async fn trace_get(&self, tx_hash: TxHash, index: Vec<usize>) -> Result<Option<Trace>> {
+        let index: Vec<U64> = index.into_iter().map(U64::from).collect();
+        self.with_retry("trace_get", || {
+            self.provider.request::<_, Option<Trace>>("trace_get", (tx_hash, index.clone()))
+        })
+        .await
     }
 
     /// Deprecated
@@ -262,11 +1218,11 @@ impl<P: JsonRpcClient> Fetcher<P> {
         transaction: TransactionRequest,
         block_number: BlockNumber,
     ) -> Result<Bytes> {
-        let _permit = self.permit_request().await;
-        self.provider
-            .call(&transaction.into(), Some(block_number.into()))
-            .await
-            .map_err(CollectError::ProviderError)
+        let transaction = transaction.into();
+        self.with_retry_at_block("call", block_number, || {
+            self.provider.call(&transaction, Some(block_number.into()))
+        })
+        .await
     }
 
     /// Returns traces for given call data
@@ -276,81 +1232,213 @@ impl<P: JsonRpcClient> Fetcher<P> {
         trace_type: Vec<TraceType>,
         block_number: Option<BlockNumber>,
     ) -> Result<BlockTrace> {
-        let _permit = self.permit_request().await;
-        self.provider
-            .trace_call(transaction, trace_type, block_number)
-            .await
-            .map_err(CollectError::ProviderError)
+        self.with_retry("trace_call", || {
+            self.provider.trace_call(transaction.clone(), trace_type.clone(), block_number)
+        })
+        .await
+    }
+
+    /// Returns traces for a sequence of calls simulated one after another on top of a block
+    pub async fn trace_call_many(
+        &self,
+        calls: Vec<(TransactionRequest, Vec<TraceType>)>,
+        block_number: Option<BlockNumber>,
+    ) -> Result<Vec<BlockTrace>> {
+        self.with_retry("trace_call_many", || {
+            self.provider.trace_call_many(calls.clone(), block_number)
+        })
+        .await
+    }
+
+    /// Get nonce of address
+    pub async fn get_transaction_count(
+        &self,
+        address: H160,
+        block_number: BlockNumber,
+    ) -> Result<U256> {
+        self.with_retry_at_block("get_transaction_count", block_number, || {
+            self.provider.get_transaction_count(address, Some(block_number.into()))
+        })
+        .await
+    }
+
+    /// Get code at address
+    pub async fn get_balance(&self, address: H160, block_number: BlockNumber) -> Result<U256> {
+        self.with_retry_at_block("get_balance", block_number, || {
+            self.provider.get_balance(address, Some(block_number.into()))
+        })
+        .await
+    }
+
+    /// Probe which trace methods the node supports by tracing the genesis block
+    ///
+    /// A method counts as unsupported only if the node reports that it does not exist, errors
+    /// such as missing state show that the method is served
+    pub async fn probe_capabilities(&self) -> Result<Capabilities> {
+        let genesis = serde_json::json!("0x0");
+        let trace = self.probe_method("trace_block", serde_json::json!([genesis])).await?;
+        let trace_replay = self
+            .probe_method("trace_replayBlockTransactions", serde_json::json!([genesis, ["trace"]]))
+            .await?;
+        let debug_trace = self
+            .probe_method(
+                "debug_traceBlockByNumber",
+                serde_json::json!([genesis, {"tracer": "callTracer"}]),
+            )
+            .await?;
+        Ok(Capabilities { trace, trace_replay, debug_trace })
+    }
+
+    /// Returns the trace methods supported by the node, probed once and reused
+    pub async fn capabilities(&self) -> Result<Capabilities> {
+        self.capabilities.get_or_try_init(|| self.probe_capabilities()).await.copied()
+    }
+
+    async fn probe_method(&self, method: &'static str, params: serde_json::Value) -> Result<bool> {
+        let result = self
+            .with_retry(method, || {
+                self.provider.request::<_, serde_json::Value>(method, params.clone())
+            })
+            .await;
+        match result {
+            Ok(_) => Ok(true),
+            Err(CollectError::ProviderError(e)) if is_unsupported_method_error(&e) => Ok(false),
+            Err(CollectError::ProviderError(_)) |
+            Err(CollectError::ArchiveDataUnavailable { .. }) => Ok(true),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Whether the node serves historical state, probed by reading a balance at block 1
+    pub async fn is_archive_node(&self) -> Result<bool> {
+        match self.get_balance(H160::zero(), BlockNumber::Number(1.into())).await {
+            Ok(_) => Ok(true),
+            Err(CollectError::ArchiveDataUnavailable { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get code at address, using the code cache for blocks that are safe from reorgs
+    pub async fn get_code(&self, address: H160, block_number: BlockNumber) -> Result<Bytes> {
+        let cache_key = match (&self.code_cache, block_number) {
+            (Some(cache), BlockNumber::Number(number)) => {
+                let latest_block = cache
+                    .latest_block
+                    .get_or_try_init(|| async { self.get_block_number().await.map(|n| n.as_u64()) })
+                    .await?;
+                let number = number.as_u64();
+                if number.saturating_add(cache.reorg_depth) <= *latest_block {
+                    if let Some(code) = cache.codes.get(&(address, number)) {
+                        return Ok(code.clone())
+                    }
+                    Some((cache, (address, number)))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+
+        let code = self
+            .with_retry_at_block("get_code", block_number, || {
+                self.provider.get_code(address, Some(block_number.into()))
+            })
+            .await?;
+        if let Some((cache, key)) = cache_key {
+            cache.codes.insert(key, code.clone());
+        }
+        Ok(code)
+    }
+
+    /// Get stored data at given location
+    pub async fn get_storage_at(
+        &self,
+        address: H160,
+        slot: H256,
+        block_number: BlockNumber,
+    ) -> Result<H256> {
+        self.with_retry_at_block("get_storage_at", block_number, || {
+            self.provider.get_storage_at(address, slot, Some(block_number.into()))
+        })
+        .await
     }
 
-    /// Get nonce of address
-    pub async fn get_transaction_count(
+    /// Get stored data at multiple slots of an address, in the same order as `slots`
+    ///
+    /// slots are requested concurrently, with concurrency bounded by the semaphore
+    pub async fn get_storage_at_many(
         &self,
         address: H160,
+        slots: &[H256],
         block_number: BlockNumber,
-    ) -> Result<U256> {
-        let _permit = self.permit_request().await;
-        self.provider
-            .get_transaction_count(address, Some(block_number.into()))
-            .await
-            .map_err(CollectError::ProviderError)
+    ) -> Result<Vec<H256>> {
+        futures::future::try_join_all(
+            slots.iter().map(|slot| self.get_storage_at(address, *slot, block_number)),
+        )
+        .await
     }
 
-    /// Get code at address
-    pub async fn get_balance(&self, address: H160, block_number: BlockNumber) -> Result<U256> {
-        let _permit = self.permit_request().await;
-        self.provider
-            .get_balance(address, Some(block_number.into()))
-            .await
-            .map_err(CollectError::ProviderError)
+    /// Resolve a block tag such as finalized or safe to a block number
+    ///
+    /// chains that do not support a tag fall back to the latest block, other errors are returned
+    pub async fn resolve_block_tag(&self, tag: BlockNumber) -> Result<u64> {
+        match tag {
+            BlockNumber::Number(number) => Ok(number.as_u64()),
+            BlockNumber::Earliest => Ok(0),
+            BlockNumber::Latest => Ok(self.get_block_number().await?.as_u64()),
+            tag => match self.with_retry("get_block", || self.provider.get_block(tag)).await {
+                Ok(Some(block)) => {
+                    block.number.map(|n| n.as_u64()).ok_or(CollectError::MissingBlockNumber)
+                }
+                Ok(None) => Ok(self.get_block_number().await?.as_u64()),
+                Err(e) if is_unsupported_block_tag_error(&e) => {
+                    Ok(self.get_block_number().await?.as_u64())
+                }
+                Err(e) => Err(e),
+            },
+        }
     }
 
-    /// Get code at address
-    pub async fn get_code(&self, address: H160, block_number: BlockNumber) -> Result<Bytes> {
-        let _permit = self.permit_request().await;
-        self.provider
-            .get_code(address, Some(block_number.into()))
-            .await
-            .map_err(CollectError::ProviderError)
+    /// Get the chain id of the node
+    pub async fn get_chain_id(&self) -> Result<u64> {
+        let chain_id = self.with_retry("get_chain_id", || self.provider.get_chainid()).await?;
+        Ok(chain_id.as_u64())
     }
 
-    /// Get stored data at given location
-    pub async fn get_storage_at(
-        &self,
-        address: H160,
-        slot: H256,
-        block_number: BlockNumber,
-    ) -> Result<H256> {
-        let _permit = self.permit_request().await;
-        self.provider
-            .get_storage_at(address, slot, Some(block_number.into()))
-            .await
-            .map_err(CollectError::ProviderError)
+    /// Get the chain id of the node, erroring if it differs from `expected`
+    pub async fn check_chain_id(&self, expected: u64) -> Result<u64> {
+        let actual = self.get_chain_id().await?;
+        if actual == expected {
+            Ok(actual)
+        } else {
+            Err(CollectError::ChainIdMismatch { expected, actual })
+        }
     }
 
     /// Get the block number
     pub async fn get_block_number(&self) -> Result<U64> {
-        Self::map_err(self.provider.get_block_number().await)
+        self.with_retry("get_block_number", || self.provider.get_block_number()).await
     }
 
     // extra helpers below
 
     /// block number of transaction
     pub async fn get_transaction_block_number(&self, transaction_hash: Vec<u8>) -> Result<u32> {
-        let block = self.get_transaction(H256::from_slice(&transaction_hash)).await?;
-        let block = block.ok_or(CollectError::CollectError("could not get block".to_string()))?;
-        Ok(block
-            .block_number
-            .ok_or(CollectError::CollectError("could not get block number".to_string()))?
-            .as_u32())
+        let tx_hash = H256::from_slice(&transaction_hash);
+        let transaction = self
+            .get_transaction(tx_hash)
+            .await?
+            .ok_or(CollectError::TransactionNotFound { tx_hash })?;
+        Ok(transaction.block_number.ok_or(CollectError::MissingBlockNumber)?.as_u32())
     }
 
     /// block number of transaction
     pub async fn get_transaction_logs(&self, transaction_hash: Vec<u8>) -> Result<Vec<Log>> {
+        let tx_hash = H256::from_slice(&transaction_hash);
         Ok(self
-            .get_transaction_receipt(H256::from_slice(&transaction_hash))
+            .get_transaction_receipt(tx_hash)
             .await?
-            .ok_or(CollectError::CollectError("transaction receipt not found".to_string()))?
+            .ok_or(CollectError::MissingReceipt { tx_hash })?
             .logs)
     }
 
@@ -366,11 +1454,40 @@ impl<P: JsonRpcClient> Fetcher<P> {
             data: Some(call_data.into()),
             ..Default::default()
         };
-        let _permit = self.permit_request().await;
-        self.provider
-            .call(&transaction.into(), Some(block_number.into()))
-            .await
-            .map_err(CollectError::ProviderError)
+        let transaction = transaction.into();
+        self.with_retry_at_block("call", block_number, || {
+            self.provider.call(&transaction, Some(block_number.into()))
+        })
+        .await
+    }
+
+    /// Whether Multicall3 is deployed at its canonical address on the current chain
+    pub async fn has_multicall3(&self) -> Result<bool> {
+        let deployed = self
+            .multicall3_deployed
+            .get_or_try_init(|| async {
+                let code = self.get_code(*MULTICALL3_ADDRESS, BlockNumber::Latest).await?;
+                Ok::<bool, CollectError>(!code.is_empty())
+            })
+            .await?;
+        Ok(*deployed)
+    }
+
+    /// Batch contract calls into a single Multicall3 `aggregate3` call
+    ///
+    /// outputs are in the same order as `calls`, `None` for calls that reverted
+    pub async fn multicall3(
+        &self,
+        calls: &[(H160, Vec<u8>)],
+        block_number: BlockNumber,
+    ) -> Result<Vec<Option<Bytes>>> {
+        let call_data = encode_aggregate3(calls);
+        let output = self.call2(*MULTICALL3_ADDRESS, call_data, block_number).await?;
+        let outputs = decode_aggregate3(&output)?;
+        if outputs.len() != calls.len() {
+            return Err(err("multicall3 returned wrong number of results"))
+        }
+        Ok(outputs)
     }
 
     /// Return output data of a contract call
@@ -386,11 +1503,10 @@ impl<P: JsonRpcClient> Fetcher<P> {
             data: Some(call_data.into()),
             ..Default::default()
         };
-        let _permit = self.permit_request().await;
-        self.provider
-            .trace_call(transaction, trace_type, block_number)
-            .await
-            .map_err(CollectError::ProviderError)
+        self.with_retry("trace_call", || {
+            self.provider.trace_call(transaction.clone(), trace_type.clone(), block_number)
+        })
+        .await
     }
 
     /// get geth debug block traces
@@ -400,24 +1516,19 @@ impl<P: JsonRpcClient> Fetcher<P> {
         options: GethDebugTracingOptions,
         include_transaction_hashes: bool,
     ) -> Result<(Option<u32>, Vec<Option<Vec<u8>>>, Vec<GethTrace>)> {
-        let traces = {
-            let _permit = self.permit_request().await;
-            self.provider
-                .debug_trace_block_by_number(Some(block_number.into()), options)
-                .await
-                .map_err(CollectError::ProviderError)?
-        };
+        let traces = self
+            .with_retry("debug_trace_block_by_number", || {
+                self.provider
+                    .debug_trace_block_by_number(Some(block_number.into()), options.clone())
+            })
+            .await?;
 
         let txs = if include_transaction_hashes {
             match self.get_block(block_number as u64).await? {
                 Some(block) => {
                     block.transactions.iter().map(|x| Some(x.as_bytes().to_vec())).collect()
                 }
-                None => {
-                    return Err(CollectError::CollectError(
-                        "could not get block for txs".to_string(),
-                    ))
-                }
+                None => return Err(CollectError::BlockNotFound { number: block_number as u64 }),
             }
         } else {
             vec![None; traces.len()]
@@ -426,6 +1537,22 @@ impl<P: JsonRpcClient> Fetcher<P> {
         Ok((Some(block_number), txs, traces))
     }
 
+    /// Traces every transaction of a block with `debug_traceBlockByNumber`
+    ///
+    /// `tracer` is the name of a geth built-in tracer such as `callTracer`, any other value is
+    /// used as a javascript tracer
+    pub async fn debug_trace_block(&self, block_num: u64, tracer: &str) -> Result<Vec<GethTrace>> {
+        let tracer = match serde_json::from_value(serde_json::json!(tracer)) {
+            Ok(built_in) => GethDebugTracerType::BuiltInTracer(built_in),
+            Err(_) => GethDebugTracerType::JsTracer(tracer.to_string()),
+        };
+        let options = GethDebugTracingOptions { tracer: Some(tracer), ..Default::default() };
+        self.with_retry("debug_trace_block_by_number", || {
+            self.provider.debug_trace_block_by_number(Some(block_num.into()), options.clone())
+        })
+        .await
+    }
+
     /// get geth debug block call traces
     pub async fn geth_debug_trace_block_javascript_traces(
         &self,
@@ -442,7 +1569,7 @@ impl<P: JsonRpcClient> Fetcher<P> {
         for trace in traces.into_iter() {
             match trace {
                 GethTrace::Unknown(value) => calls.push(value),
-                _ => return Err(CollectError::CollectError("invalid trace result".to_string())),
+                _ => return Err(CollectError::InvalidTraceResult),
             }
         }
         Ok((block, txs, calls))
@@ -462,7 +1589,7 @@ impl<P: JsonRpcClient> Fetcher<P> {
         for trace in traces.into_iter() {
             match trace {
                 GethTrace::Known(GethTraceFrame::Default(frame)) => calls.push(frame),
-                _ => return Err(CollectError::CollectError("invalid trace result".to_string())),
+                _ => return Err(CollectError::InvalidTraceResult),
             }
         }
         Ok((block, txs, calls))
@@ -486,7 +1613,7 @@ impl<P: JsonRpcClient> Fetcher<P> {
                     calls.push(frame)
                 }
                 GethTrace::Known(GethTraceFrame::NoopTracer(_)) => {}
-                _ => return Err(CollectError::CollectError("invalid trace result".to_string())),
+                _ => return Err(CollectError::InvalidTraceResult),
             }
         }
         Ok((block, txs, calls))
@@ -509,7 +1636,7 @@ impl<P: JsonRpcClient> Fetcher<P> {
                 GethTrace::Known(GethTraceFrame::PreStateTracer(PreStateFrame::Default(
                     PreStateMode(frame),
                 ))) => calls.push(frame),
-                _ => return Err(CollectError::CollectError("invalid trace result".to_string())),
+                _ => return Err(CollectError::InvalidTraceResult),
             }
         }
         Ok((block, txs, calls))
@@ -537,7 +1664,7 @@ impl<P: JsonRpcClient> Fetcher<P> {
         for trace in traces.into_iter() {
             match trace {
                 GethTrace::Known(GethTraceFrame::CallTracer(call_frame)) => calls.push(call_frame),
-                _ => return Err(CollectError::CollectError("invalid trace result".to_string())),
+                _ => return Err(CollectError::InvalidTraceResult),
             }
         }
         Ok((block, txs, calls))
@@ -571,10 +1698,7 @@ impl<P: JsonRpcClient> Fetcher<P> {
                     let diff = parse_geth_diff_object(map)?;
                     diffs.push(diff)
                 }
-                _ => {
-                    println!("{:?}", trace);
-                    return Err(CollectError::CollectError("invalid trace result".to_string()))
-                }
+                _ => return Err(CollectError::InvalidTraceResult),
             }
         }
         Ok((block, txs, diffs))
@@ -589,23 +1713,17 @@ impl<P: JsonRpcClient> Fetcher<P> {
     ) -> Result<(Option<u32>, Vec<Option<Vec<u8>>>, Vec<GethTrace>)> {
         let ethers_tx = H256::from_slice(&transaction_hash);
 
-        let trace = {
-            let _permit = self.permit_request().await;
-            self.provider
-                .debug_trace_transaction(ethers_tx, options)
-                .await
-                .map_err(CollectError::ProviderError)?
-        };
+        let trace = self
+            .with_retry("debug_trace_transaction", || {
+                self.provider.debug_trace_transaction(ethers_tx, options.clone())
+            })
+            .await?;
         let traces = vec![trace];
 
         let block_number = if include_block_number {
             match self.get_transaction(ethers_tx).await? {
                 Some(tx) => tx.block_number.map(|x| x.as_u32()),
-                None => {
-                    return Err(CollectError::CollectError(
-                        "could not get block for txs".to_string(),
-                    ))
-                }
+                None => return Err(CollectError::TransactionNotFound { tx_hash: ethers_tx }),
             }
         } else {
             None
@@ -631,7 +1749,7 @@ impl<P: JsonRpcClient> Fetcher<P> {
         for trace in traces.into_iter() {
             match trace {
                 GethTrace::Unknown(value) => calls.push(value),
-                _ => return Err(CollectError::CollectError("invalid trace result".to_string())),
+                _ => return Err(CollectError::InvalidTraceResult),
             }
         }
         Ok((block, txs, calls))
@@ -652,7 +1770,7 @@ impl<P: JsonRpcClient> Fetcher<P> {
         for trace in traces.into_iter() {
             match trace {
                 GethTrace::Known(GethTraceFrame::Default(frame)) => calls.push(frame),
-                _ => return Err(CollectError::CollectError("invalid trace result".to_string())),
+                _ => return Err(CollectError::InvalidTraceResult),
             }
         }
         Ok((block, txs, calls))
@@ -676,7 +1794,7 @@ impl<P: JsonRpcClient> Fetcher<P> {
                 GethTrace::Known(GethTraceFrame::FourByteTracer(FourByteFrame(frame))) => {
                     calls.push(frame)
                 }
-                _ => return Err(CollectError::CollectError("invalid trace result".to_string())),
+                _ => return Err(CollectError::InvalidTraceResult),
             }
         }
         Ok((block, txs, calls))
@@ -700,7 +1818,7 @@ impl<P: JsonRpcClient> Fetcher<P> {
                 GethTrace::Known(GethTraceFrame::PreStateTracer(PreStateFrame::Default(
                     PreStateMode(frame),
                 ))) => calls.push(frame),
-                _ => return Err(CollectError::CollectError("invalid trace result".to_string())),
+                _ => return Err(CollectError::InvalidTraceResult),
             }
         }
         Ok((block, txs, calls))
@@ -729,7 +1847,7 @@ impl<P: JsonRpcClient> Fetcher<P> {
         for trace in traces.into_iter() {
             match trace {
                 GethTrace::Known(GethTraceFrame::CallTracer(call_frame)) => calls.push(call_frame),
-                _ => return Err(CollectError::CollectError("invalid trace result".to_string())),
+                _ => return Err(CollectError::InvalidTraceResult),
             }
         }
         Ok((block, txs, calls))
@@ -760,7 +1878,7 @@ impl<P: JsonRpcClient> Fetcher<P> {
                 GethTrace::Known(GethTraceFrame::PreStateTracer(PreStateFrame::Diff(diff))) => {
                     diffs.push(diff)
                 }
-                _ => return Err(CollectError::CollectError("invalid trace result".to_string())),
+                _ => return Err(CollectError::InvalidTraceResult),
             }
         }
         Ok((block, txs, diffs))
@@ -768,29 +1886,215 @@ impl<P: JsonRpcClient> Fetcher<P> {
 
     async fn permit_request(
         &self,
+        method: &str,
     ) -> Option<::core::result::Result<SemaphorePermit<'_>, AcquireError>> {
         let permit = match &self.semaphore {
             Some(semaphore) => Some(semaphore.acquire().await),
             _ => None,
         };
-        if let Some(limiter) = &self.rate_limiter {
+        if let Some(limiter) = self.rate_limiter_for(method) {
             limiter.until_ready().await;
         }
+        let count = self.request_count.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(progress) = &self.progress {
+            // u64::is_multiple_of needs rust 1.87
+            #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+            if count % progress.interval == 0 {
+                (progress.callback)(count);
+            }
+        }
         permit
     }
 
-    fn map_err<T>(res: ::core::result::Result<T, ProviderError>) -> Result<T> {
-        res.map_err(CollectError::ProviderError)
+    /// `with_retry` for requests that read state at `block_number`, reporting pruned state as
+    /// `CollectError::ArchiveDataUnavailable`
+    async fn with_retry_at_block<T, F, Fut>(
+        &self,
+        method: &'static str,
+        block_number: BlockNumber,
+        request: F,
+    ) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = ::core::result::Result<T, ProviderError>>,
+    {
+        match (self.with_retry(method, request).await, block_number.as_number()) {
+            (Err(CollectError::ProviderError(e)), Some(block)) if is_missing_state_error(&e) => {
+                Err(CollectError::ArchiveDataUnavailable { block: block.as_u64() })
+            }
+            (result, _) => result,
+        }
+    }
+
+    /// send a request, retrying recoverable errors with exponential backoff
+    async fn with_retry<T, F, Fut>(&self, method: &'static str, request: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = ::core::result::Result<T, ProviderError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            if let Some(breaker) = &self.circuit_breaker {
+                breaker.allow_request()?;
+            }
+            // None if the request timed out
+            let result = {
+                // bound to a name so the permit is held until the request completes
+                let _permit = self.permit_request(method).await;
+                let start = Instant::now();
+                let result = match self.request_timeouts.get(&MethodCategory::of(method)) {
+                    Some(timeout) => tokio::time::timeout(*timeout, request()).await.ok(),
+                    None => Some(request().await),
+                };
+                if let Some(latency) = &self.latency {
+                    if let Ok(mut latency) = latency.lock() {
+                        latency.record(method, start.elapsed());
+                    }
+                }
+                result
+            };
+            let recoverable = match &result {
+                Some(Ok(_)) => false,
+                Some(Err(e)) => is_recoverable_error(e),
+                None => true,
+            };
+            if let Some(breaker) = &self.circuit_breaker {
+                breaker.record(recoverable);
+            }
+            if recoverable && attempt < self.retry.max_retries {
+                tokio::time::sleep(self.retry.delay(attempt)).await;
+                attempt += 1;
+                continue
+            }
+            match result {
+                Some(Ok(value)) => return Ok(value),
+                Some(Err(e)) => return Err(CollectError::ProviderError(e)),
+                None => return Err(CollectError::Timeout { method }),
+            }
+        }
     }
 }
 
 use crate::err;
-use std::collections::BTreeMap;
+
+/// calldata of a Multicall3 `aggregate3` call that allows each call to fail
+fn encode_aggregate3(calls: &[(H160, Vec<u8>)]) -> Vec<u8> {
+    let calls = calls
+        .iter()
+        .map(|(address, call_data)| {
+            Token::Tuple(vec![
+                Token::Address(*address),
+                Token::Bool(true),
+                Token::Bytes(call_data.clone()),
+            ])
+        })
+        .collect();
+    let mut data = FUNCTION_MULTICALL3_AGGREGATE3.clone();
+    data.extend(ethers::abi::encode(&[Token::Array(calls)]));
+    data
+}
+
+/// outputs of a Multicall3 `aggregate3` call, `None` for failed calls
+fn decode_aggregate3(output: &[u8]) -> Result<Vec<Option<Bytes>>> {
+    let result_type = ParamType::Tuple(vec![ParamType::Bool, ParamType::Bytes]);
+    let tokens = ethers::abi::decode(&[ParamType::Array(Box::new(result_type))], output)
+        .map_err(|_| err("could not decode multicall3 output"))?;
+    let results = match tokens.into_iter().next() {
+        Some(Token::Array(results)) => results,
+        _ => return Err(err("could not decode multicall3 output")),
+    };
+    results
+        .into_iter()
+        .map(|result| match result.into_tuple().as_deref() {
+            Some([Token::Bool(true), Token::Bytes(data)]) => Ok(Some(data.clone().into())),
+            Some([Token::Bool(false), Token::Bytes(_)]) => Ok(None),
+            _ => Err(err("could not decode multicall3 output")),
+        })
+        .collect()
+}
+
+/// whether an error reports that the node does not serve the requested method
+fn is_unsupported_method_error(error: &ProviderError) -> bool {
+    if let ProviderError::JsonRpcClientError(e) = error {
+        if e.as_error_response().map(|response| response.code == -32601).unwrap_or(false) {
+            return true
+        }
+    }
+    let message = error.to_string().to_lowercase();
+    message.contains("method") &&
+        ["not found", "does not exist", "not available", "not supported", "unsupported"]
+            .iter()
+            .any(|pattern| message.contains(pattern))
+}
+
+/// whether an error reports that a non-archive node has pruned the requested state
+fn is_missing_state_error(error: &ProviderError) -> bool {
+    let message = error.to_string().to_lowercase();
+    [
+        "missing trie node",
+        "historical state",
+        "state is not available",
+        "state not available",
+        "state histories haven't been fully indexed",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+/// number of headers fetched at a time by `Fetcher::get_logs_bloom_filtered`
+const BLOOM_FILTER_BATCH_SIZE: u64 = 32;
+
+/// append the block range `start..=end` to `ranges`, merging it with a contiguous last range
+fn push_block_range(ranges: &mut Vec<(u64, u64)>, start: u64, end: u64) {
+    match ranges.last_mut() {
+        Some((_, range_end)) if *range_end + 1 == start => *range_end = end,
+        _ => ranges.push((start, end)),
+    }
+}
+
+/// whether a logs bloom may contain a log emitted by one of `addresses` with one of `topic0s` as
+/// its first topic, an empty list matches anything
+///
+/// blooms can report false positives but never false negatives, so only blocks for which this
+/// returns false can be skipped
+pub fn bloom_contains(bloom: &Bloom, topic0s: &[H256], addresses: &[H160]) -> bool {
+    let contains = |bytes: &[u8]| bloom.contains_input(BloomInput::Raw(bytes));
+    (topic0s.is_empty() || topic0s.iter().any(|topic0| contains(topic0.as_bytes()))) &&
+        (addresses.is_empty() || addresses.iter().any(|address| contains(address.as_bytes())))
+}
+
+/// drop repeated logs, keyed by block hash (or transaction hash) and log index
+fn dedup_logs(logs: Vec<Log>) -> Vec<Log> {
+    let mut seen = std::collections::HashSet::new();
+    logs.into_iter()
+        .filter(|log| match (log.block_hash.or(log.transaction_hash), log.log_index) {
+            (Some(hash), Some(log_index)) => seen.insert((hash, log_index)),
+            _ => true,
+        })
+        .collect()
+}
+
+/// whether an error was caused by a log query exceeding the provider's result limit
+fn is_too_many_logs_error(error: &CollectError) -> bool {
+    is_too_many_logs_message(&error.to_string().to_lowercase())
+}
+
+/// whether an error was caused by the node not supporting a block tag such as finalized
+fn is_unsupported_block_tag_error(error: &CollectError) -> bool {
+    let message = error.to_string().to_lowercase();
+    message.contains("block tag") ||
+        message.contains("tag not supported") ||
+        message.contains("unknown block")
+}
+
+fn is_too_many_logs_message(message: &str) -> bool {
+    (message.contains("more than") && message.contains("results")) ||
+        message.contains("log response size exceeded")
+}
 
 fn parse_geth_diff_object(
     map: ethers::utils::__serde_json::Map<String, ethers::utils::__serde_json::Value>,
 ) -> Result<DiffMode> {
-    println!("HERE {:?}", map);
     let pre: BTreeMap<H160, AccountState> = serde_json::from_value(map["pre"].clone())
         .map_err(|_| err("cannot deserialize pre diff"))?;
     let post: BTreeMap<H160, AccountState> = serde_json::from_value(map["post"].clone())
@@ -798,3 +2102,1036 @@ fn parse_geth_diff_object(
 
     Ok(DiffMode { pre, post })
 }
+
+/// convert a geth prestateTracer diff into a parity style state diff
+///
+/// in diff mode geth omits unchanged fields from post, omits accounts from pre that did not exist
+/// before the transaction, and omits accounts from post that were destroyed by the transaction
+fn geth_diff_to_block_trace(diff: DiffMode) -> Result<BlockTrace> {
+    let DiffMode { pre, post } = diff;
+    let mut state_diff = BTreeMap::new();
+    let addresses: std::collections::BTreeSet<_> = pre.keys().chain(post.keys()).copied().collect();
+    for address in addresses.into_iter() {
+        let account_diff = match (pre.get(&address), post.get(&address)) {
+            (Some(pre), Some(post)) => AccountDiff {
+                balance: diff_changed(pre.balance, post.balance, U256::zero()),
+                nonce: diff_changed(pre.nonce, post.nonce, U256::zero()),
+                code: diff_changed(
+                    parse_geth_code(&pre.code)?,
+                    parse_geth_code(&post.code)?,
+                    Bytes::new(),
+                ),
+                storage: diff_storage(&pre.storage, &post.storage),
+            },
+            (None, Some(post)) => AccountDiff {
+                balance: Diff::Born(post.balance.unwrap_or_default()),
+                nonce: Diff::Born(post.nonce.unwrap_or_default()),
+                code: Diff::Born(parse_geth_code(&post.code)?.unwrap_or_default()),
+                storage: post
+                    .storage
+                    .iter()
+                    .flatten()
+                    .map(|(slot, value)| (*slot, Diff::Born(*value)))
+                    .collect(),
+            },
+            (Some(pre), None) => AccountDiff {
+                balance: Diff::Died(pre.balance.unwrap_or_default()),
+                nonce: Diff::Died(pre.nonce.unwrap_or_default()),
+                code: Diff::Died(parse_geth_code(&pre.code)?.unwrap_or_default()),
+                storage: pre
+                    .storage
+                    .iter()
+                    .flatten()
+                    .map(|(slot, value)| (*slot, Diff::Died(*value)))
+                    .collect(),
+            },
+            (None, None) => continue,
+        };
+        state_diff.insert(address, account_diff);
+    }
+    // built through serde because the fields of BlockTrace differ between ethers versions
+    let trace = serde_json::json!({ "output": "0x", "stateDiff": StateDiff(state_diff) });
+    serde_json::from_value(trace).map_err(|_| err("could not build state diff trace"))
+}
+
+/// diff of an account field that geth only includes in post when it changed
+fn diff_changed<T: PartialEq>(pre: Option<T>, post: Option<T>, new: T) -> Diff<T> {
+    match (pre, post) {
+        (pre, Some(post)) => {
+            let from = pre.unwrap_or(new);
+            if from == post {
+                Diff::Same
+            } else {
+                Diff::Changed(ChangedType { from, to: post })
+            }
+        }
+        (_, None) => Diff::Same,
+    }
+}
+
+/// diff of account storage, geth omits slots from post that were cleared
+fn diff_storage(
+    pre: &Option<BTreeMap<H256, H256>>,
+    post: &Option<BTreeMap<H256, H256>>,
+) -> BTreeMap<H256, Diff<H256>> {
+    let blank = BTreeMap::new();
+    let (pre, post) = (pre.as_ref().unwrap_or(&blank), post.as_ref().unwrap_or(&blank));
+    let slots: std::collections::BTreeSet<_> = pre.keys().chain(post.keys()).copied().collect();
+    let mut storage = BTreeMap::new();
+    for slot in slots.into_iter() {
+        let (from, to) = from_to_or_default(pre.get(&slot).copied(), post.get(&slot).copied());
+        if from != to {
+            storage.insert(slot, Diff::Changed(ChangedType { from, to }));
+        }
+    }
+    storage
+}
+
+fn parse_geth_code(code: &Option<String>) -> Result<Option<Bytes>> {
+    code.as_ref()
+        .map(|code| code.parse::<Bytes>().map_err(|_| err("could not decode code contents")))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rpc_error(code: i64, message: &str) -> MockResponse {
+        MockResponse::Error(JsonRpcError { code, message: message.to_string(), data: None })
+    }
+
+    fn mocked_fetcher(max_retries: u32) -> (Fetcher<MockProvider>, MockProvider) {
+        let (provider, mock) = Provider::mocked();
+        let retry = RetryConfig {
+            max_retries,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            jitter: false,
+        };
+        (Fetcher::new(provider, None, None, retry), mock)
+    }
+
+    /// serve one json rpc response per connection, recording the `x-api-key` header of each
+    async fn serve_rpc<S: ToString>(
+        responses: Vec<S>,
+    ) -> (String, Arc<Mutex<Vec<Option<String>>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let responses: Vec<String> = responses.iter().map(|body| body.to_string()).collect();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorded = seen.clone();
+        tokio::spawn(async move {
+            for body in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                while !String::from_utf8_lossy(&request).contains("\r\n\r\n") {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request = String::from_utf8_lossy(&request).to_lowercase();
+                let api_key = request
+                    .lines()
+                    .find_map(|line| line.strip_prefix("x-api-key: "))
+                    .map(|value| value.trim().to_string());
+                recorded.lock().unwrap().push(api_key);
+                let body = format!(r#"{{"jsonrpc":"2.0","id":1,{}}}"#, body);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, seen)
+    }
+
+    #[tokio::test]
+    async fn sends_headers_on_retries_and_fallbacks() {
+        let rate_limited = r#""error":{"code":429,"message":"rate limited"}"#;
+        let (primary, primary_seen) = serve_rpc(vec![rate_limited, rate_limited]).await;
+        let (secondary, secondary_seen) = serve_rpc(vec![rate_limited, r#""result":"0x1""#]).await;
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "secret".parse().unwrap());
+        let retry = RetryConfig {
+            max_retries: 1,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            jitter: false,
+        };
+
+        let source =
+            Source::from_http_with_headers(&[primary, secondary], headers, retry, None, None)
+                .await
+                .unwrap();
+        assert_eq!(source.chain_id, 1);
+        let key = Some("secret".to_string());
+        assert_eq!(*primary_seen.lock().unwrap(), vec![key.clone(), key.clone()]);
+        assert_eq!(*secondary_seen.lock().unwrap(), vec![key.clone(), key]);
+    }
+
+    #[tokio::test]
+    async fn reports_missing_receipts_of_block_by_transaction_hash() {
+        let tx_hash = H256::repeat_byte(1);
+        let block = serde_json::to_string(&Block::<TxHash> {
+            number: Some(10.into()),
+            transactions: vec![tx_hash],
+            ..Default::default()
+        })
+        .unwrap();
+        let responses = vec![
+            r#""result":"0x1""#.to_string(),
+            r#""error":{"code":-32601,"message":"method not found"}"#.to_string(),
+            format!(r#""result":{}"#, block),
+            r#""result":null"#.to_string(),
+        ];
+        let (url, _) = serve_rpc(responses).await;
+        let retry = RetryConfig { max_retries: 0, ..Default::default() };
+        let source = Source::from_http_with_headers(&[url], HeaderMap::new(), retry, None, None)
+            .await
+            .unwrap();
+
+        match source.get_receipts_in_block(10).await {
+            Err(CollectError::MissingReceipt { tx_hash: missing }) => assert_eq!(missing, tx_hash),
+            _ => panic!("expected a missing receipt error"),
+        }
+    }
+
+    /// provider whose first `n_slow` requests take `delay` to answer
+    #[derive(Debug)]
+    struct SlowProvider {
+        delay: Duration,
+        n_slow: u32,
+        calls: std::sync::atomic::AtomicU32,
+    }
+
+    #[async_trait::async_trait]
+    impl JsonRpcClient for SlowProvider {
+        type Error = ProviderError;
+
+        async fn request<T, R>(
+            &self,
+            _method: &str,
+            _params: T,
+        ) -> ::core::result::Result<R, ProviderError>
+        where
+            T: std::fmt::Debug + serde::Serialize + Send + Sync,
+            R: serde::de::DeserializeOwned + Send,
+        {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.n_slow {
+                tokio::time::sleep(self.delay).await;
+            }
+            serde_json::from_value(serde_json::json!("0x1")).map_err(ProviderError::SerdeJson)
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_requests_that_time_out() {
+        let retry = |max_retries| RetryConfig {
+            max_retries,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            jitter: false,
+        };
+        let slow = |n_slow| SlowProvider {
+            delay: Duration::from_secs(10),
+            n_slow,
+            calls: Default::default(),
+        };
+        let timeout = Duration::from_millis(20);
+
+        let fetcher = Fetcher::new(Provider::new(slow(1)), None, None, retry(1))
+            .with_request_timeout(MethodCategory::Read, timeout);
+        assert_eq!(fetcher.get_block_number().await.unwrap(), U64::from(1));
+        assert_eq!(fetcher.requests_made(), 2);
+
+        let fetcher = Fetcher::new(Provider::new(slow(2)), None, None, retry(1))
+            .with_request_timeout(MethodCategory::Read, timeout);
+        let error = fetcher.get_block_number().await.unwrap_err();
+        assert!(matches!(error, CollectError::Timeout { method: "get_block_number" }));
+    }
+
+    #[tokio::test]
+    async fn retries_recoverable_errors() {
+        let (fetcher, mock) = mocked_fetcher(3);
+        // mock responses are popped from the back
+        mock.push(U64::from(17_000_000)).unwrap();
+        mock.push_response(rpc_error(429, "too many requests"));
+        mock.push_response(rpc_error(-32000, "request timed out"));
+        assert_eq!(fetcher.get_block_number().await.unwrap(), U64::from(17_000_000));
+    }
+
+    #[tokio::test]
+    async fn gets_transaction_by_block_and_index() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let tx = Transaction {
+            block_number: Some(10.into()),
+            transaction_index: Some(3.into()),
+            ..Default::default()
+        };
+        mock.push(tx.clone()).unwrap();
+        assert_eq!(fetcher.get_transaction_by_block_and_index(10, 3).await.unwrap(), Some(tx));
+        assert_eq!(fetcher.requests_made(), 1);
+    }
+
+    #[tokio::test]
+    async fn joins_block_traces_to_transaction_hashes() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let trace = |transaction_position: Option<usize>| Trace {
+            action: Action::Call(ethers::types::Call::default()),
+            result: None,
+            trace_address: vec![],
+            subtraces: 0,
+            transaction_position,
+            transaction_hash: None,
+            block_number: 10,
+            block_hash: H256::zero(),
+            action_type: ActionType::Call,
+            error: None,
+        };
+        let hashes = vec![H256::repeat_byte(1), H256::repeat_byte(2)];
+        let block = Block::<TxHash> { transactions: hashes.clone(), ..Default::default() };
+        mock.push::<Block<TxHash>, _>(block).unwrap();
+        mock.push::<Vec<Trace>, _>(vec![trace(Some(1)), trace(Some(0)), trace(None)]).unwrap();
+
+        let joined = fetcher.trace_block_joined(10).await.unwrap();
+        let tx_hashes: Vec<_> = joined.into_iter().map(|(_, tx_hash)| tx_hash).collect();
+        assert_eq!(tx_hashes, vec![Some(hashes[1]), Some(hashes[0]), None]);
+        assert_eq!(fetcher.requests_made(), 2);
+    }
+
+    #[tokio::test]
+    async fn positions_vm_traces_by_transaction_hash() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let hashes = vec![H256::repeat_byte(1), H256::repeat_byte(2), H256::repeat_byte(3)];
+        let block = Block::<TxHash> { transactions: hashes.clone(), ..Default::default() };
+        let trace = |transaction_hash: H256| BlockTrace {
+            output: Bytes::default(),
+            trace: None,
+            vm_trace: Some(VMTrace::default()),
+            state_diff: None,
+            transaction_hash: Some(transaction_hash),
+        };
+        // the second transaction produced no vm trace and is missing from the response
+        let traces = vec![trace(hashes[0]), trace(hashes[2])];
+        mock.push::<Block<TxHash>, _>(block).unwrap();
+        mock.push::<Vec<BlockTrace>, _>(traces).unwrap();
+
+        let (_, _, traces) = fetcher.trace_block_vm_traces(10).await.unwrap();
+        let positions: Vec<_> = traces.into_iter().map(|(position, _)| position).collect();
+        assert_eq!(positions, vec![0, 2]);
+
+        // traces that cannot be matched to a transaction of the block are not guessed
+        let block = Block::<TxHash> { transactions: hashes.clone(), ..Default::default() };
+        mock.push::<Block<TxHash>, _>(block).unwrap();
+        mock.push::<Vec<BlockTrace>, _>(vec![trace(H256::repeat_byte(9))]).unwrap();
+        assert!(fetcher.trace_block_vm_traces(10).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn batches_calls_with_multicall3() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let result = |success: bool, data: &[u8]| {
+            Token::Tuple(vec![Token::Bool(success), Token::Bytes(data.to_vec())])
+        };
+        let output =
+            ethers::abi::encode(&[Token::Array(vec![result(true, b"name"), result(false, b"")])]);
+        mock.push::<Bytes, _>(Bytes::from(output)).unwrap();
+        mock.push::<Bytes, _>(Bytes::from(vec![0xfe])).unwrap();
+
+        assert!(fetcher.has_multicall3().await.unwrap());
+        assert!(fetcher.has_multicall3().await.unwrap());
+        let calls = vec![
+            (H160::repeat_byte(1), crate::FUNCTION_ERC20_NAME.clone()),
+            (H160::repeat_byte(1), crate::FUNCTION_ERC20_SYMBOL.clone()),
+        ];
+        let outputs = fetcher.multicall3(&calls, BlockNumber::Latest).await.unwrap();
+        assert_eq!(outputs, vec![Some(Bytes::from(b"name".to_vec())), None]);
+        assert_eq!(fetcher.requests_made(), 2);
+
+        let call_data = encode_aggregate3(&calls);
+        assert_eq!(call_data[..4], FUNCTION_MULTICALL3_AGGREGATE3[..]);
+    }
+
+    #[tokio::test]
+    async fn reports_missing_state_as_archive_data_unavailable() {
+        let (fetcher, mock) = mocked_fetcher(3);
+        mock.push_response(rpc_error(-32000, "missing trie node 1a2b (path ) <nil>"));
+        let result = fetcher.get_balance(H160::zero(), BlockNumber::Number(5.into())).await;
+        assert!(matches!(result, Err(CollectError::ArchiveDataUnavailable { block: 5 })));
+        assert_eq!(fetcher.requests_made(), 1);
+
+        mock.push(U256::from(7)).unwrap();
+        assert!(fetcher.is_archive_node().await.unwrap());
+        mock.push_response(rpc_error(-32000, "historical state 0xabc is not available"));
+        assert!(!fetcher.is_archive_node().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn trace_filter_sends_addresses() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        mock.push::<Vec<Trace>, _>(vec![]).unwrap();
+        let filter =
+            TraceFilter::default().from_block(5).to_block(5).to_address(vec![H160::repeat_byte(1)]);
+        assert!(fetcher.trace_filter(filter.clone()).await.unwrap().is_empty());
+        mock.assert_request("trace_filter", [filter]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn trace_filter_paged_requests_pages_until_a_short_page() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let trace = |block_number: u64| Trace {
+            action: Action::Call(ethers::types::Call::default()),
+            result: None,
+            trace_address: vec![],
+            subtraces: 0,
+            transaction_position: None,
+            transaction_hash: None,
+            block_number,
+            block_hash: H256::zero(),
+            action_type: ActionType::Call,
+            error: None,
+        };
+        mock.push::<Vec<Trace>, _>(vec![trace(3)]).unwrap();
+        mock.push::<Vec<Trace>, _>(vec![trace(1), trace(2)]).unwrap();
+        let filter = TraceFilter::default().from_block(1).to_block(3);
+        let traces = fetcher.trace_filter_paged(filter.clone(), 2).await.unwrap();
+
+        let blocks: Vec<_> = traces.iter().map(|trace| trace.block_number).collect();
+        assert_eq!(blocks, vec![1, 2, 3]);
+        mock.assert_request("trace_filter", [filter.clone().after(0).count(2)]).unwrap();
+        mock.assert_request("trace_filter", [filter.after(2).count(2)]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn resolves_block_tags() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let block = Block::<TxHash> { number: Some(90.into()), ..Default::default() };
+        mock.push::<Block<TxHash>, _>(block).unwrap();
+        assert_eq!(fetcher.resolve_block_tag(BlockNumber::Finalized).await.unwrap(), 90);
+
+        // chains without the tag fall back to the latest block
+        mock.push(U64::from(100)).unwrap();
+        mock.push_response(rpc_error(-32602, "invalid block tag"));
+        assert_eq!(fetcher.resolve_block_tag(BlockNumber::Safe).await.unwrap(), 100);
+        mock.push(U64::from(100)).unwrap();
+        mock.push_response(rpc_error(-39001, "'finalized' tag not supported on pre-merge network"));
+        assert_eq!(fetcher.resolve_block_tag(BlockNumber::Finalized).await.unwrap(), 100);
+
+        // other errors are not mistaken for a missing tag
+        mock.push_response(rpc_error(429, "rate limited"));
+        assert!(fetcher.resolve_block_tag(BlockNumber::Finalized).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn circuit_breaker_fails_fast_after_consecutive_failures() {
+        let (fetcher, mock) = mocked_fetcher(5);
+        let fetcher = fetcher.with_circuit_breaker(2, Duration::from_secs(3600));
+        mock.push(U64::from(1)).unwrap();
+        mock.push_response(rpc_error(429, "rate limited"));
+        mock.push_response(rpc_error(429, "rate limited"));
+
+        let result = fetcher.get_block_number().await;
+        assert!(matches!(result, Err(CollectError::CircuitOpen(2))));
+        assert_eq!(fetcher.circuit_state(), Some(CircuitState::Open));
+        assert_eq!(fetcher.requests_made(), 2);
+    }
+
+    #[tokio::test]
+    async fn circuit_breaker_closes_after_successful_probe() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let fetcher = fetcher.with_circuit_breaker(1, Duration::ZERO);
+        mock.push(U64::from(17_000_000)).unwrap();
+        mock.push_response(rpc_error(429, "rate limited"));
+
+        assert!(fetcher.get_block_number().await.is_err());
+        assert_eq!(fetcher.circuit_state(), Some(CircuitState::HalfOpen));
+        assert_eq!(fetcher.get_block_number().await.unwrap(), U64::from(17_000_000));
+        assert_eq!(fetcher.circuit_state(), Some(CircuitState::Closed));
+    }
+
+    #[test]
+    fn selects_rate_limiter_by_method_category() {
+        let (provider, _mock) = Provider::mocked();
+        let fetcher = Fetcher::new(provider, None, Some(100), RetryConfig::default())
+            .with_method_rate_limit(MethodCategory::Trace, 2);
+        assert_eq!(MethodCategory::of("debug_trace_transaction"), MethodCategory::Trace);
+        assert_eq!(MethodCategory::of("get_logs"), MethodCategory::Log);
+        assert_eq!(MethodCategory::of("get_block"), MethodCategory::Read);
+
+        let trace_limiter = fetcher.method_rate_limiters.get(&MethodCategory::Trace);
+        assert!(std::ptr::eq(
+            fetcher.rate_limiter_for("trace_block").unwrap(),
+            trace_limiter.unwrap()
+        ));
+        let global_limiter = fetcher.rate_limiter.as_ref().unwrap();
+        assert!(std::ptr::eq(fetcher.rate_limiter_for("get_logs").unwrap(), global_limiter));
+    }
+
+    #[tokio::test]
+    async fn check_chain_id_rejects_other_chains() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        mock.push(U256::from(137)).unwrap();
+        mock.push(U256::from(1)).unwrap();
+        assert_eq!(fetcher.check_chain_id(1).await.unwrap(), 1);
+        assert!(matches!(
+            fetcher.check_chain_id(1).await,
+            Err(CollectError::ChainIdMismatch { expected: 1, actual: 137 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn fails_fast_on_unrecoverable_errors() {
+        let (fetcher, mock) = mocked_fetcher(3);
+        mock.push(U64::from(17_000_000)).unwrap();
+        mock.push_response(rpc_error(-32602, "invalid params"));
+        assert!(fetcher.get_block_number().await.is_err());
+    }
+
+    /// transport that answers block requests with a delay that decreases with block number
+    #[derive(Debug)]
+    struct DelayedBlocks;
+
+    #[async_trait::async_trait]
+    impl JsonRpcClient for DelayedBlocks {
+        type Error = ProviderError;
+
+        async fn request<T, R>(
+            &self,
+            _method: &str,
+            params: T,
+        ) -> ::core::result::Result<R, Self::Error>
+        where
+            T: std::fmt::Debug + serde::Serialize + Send + Sync,
+            R: serde::de::DeserializeOwned + Send,
+        {
+            let params = serde_json::to_value(params)?;
+            let block_number: U64 = serde_json::from_value(params[0].clone())?;
+            tokio::time::sleep(Duration::from_millis(50 - 10 * block_number.as_u64())).await;
+            let block = Block::<TxHash> { number: Some(block_number), ..Default::default() };
+            Ok(serde_json::from_value(serde_json::to_value(block)?)?)
+        }
+    }
+
+    /// transport that echoes storage slots back, answering lower slots more slowly
+    #[derive(Debug)]
+    struct DelayedSlots;
+
+    #[async_trait::async_trait]
+    impl JsonRpcClient for DelayedSlots {
+        type Error = ProviderError;
+
+        async fn request<T, R>(
+            &self,
+            _method: &str,
+            params: T,
+        ) -> ::core::result::Result<R, Self::Error>
+        where
+            T: std::fmt::Debug + serde::Serialize + Send + Sync,
+            R: serde::de::DeserializeOwned + Send,
+        {
+            let params = serde_json::to_value(params)?;
+            // slots are sent as quantities
+            let slot: U256 = serde_json::from_value(params[1].clone())?;
+            tokio::time::sleep(Duration::from_millis(50 - 10 * slot.as_u64())).await;
+            let value = H256::from_low_u64_be(slot.as_u64());
+            Ok(serde_json::from_value(serde_json::to_value(value)?)?)
+        }
+    }
+
+    /// transport serving a chain of 1000 blocks, two per 12 second slot
+    #[derive(Debug)]
+    struct TimestampedBlocks;
+
+    #[async_trait::async_trait]
+    impl JsonRpcClient for TimestampedBlocks {
+        type Error = ProviderError;
+
+        async fn request<T, R>(
+            &self,
+            method: &str,
+            params: T,
+        ) -> ::core::result::Result<R, Self::Error>
+        where
+            T: std::fmt::Debug + serde::Serialize + Send + Sync,
+            R: serde::de::DeserializeOwned + Send,
+        {
+            if method == "eth_blockNumber" {
+                return Ok(serde_json::from_value(serde_json::to_value(U64::from(999))?)?)
+            }
+            let params = serde_json::to_value(params)?;
+            let block_number: U64 = serde_json::from_value(params[0].clone())?;
+            let block = Block::<TxHash> {
+                number: Some(block_number),
+                timestamp: (1000 + 12 * (block_number.as_u64() / 2)).into(),
+                ..Default::default()
+            };
+            Ok(serde_json::from_value(serde_json::to_value(block)?)?)
+        }
+    }
+
+    #[tokio::test]
+    async fn block_number_at_timestamp_finds_first_block() {
+        let fetcher =
+            Fetcher::new(Provider::new(TimestampedBlocks), None, None, RetryConfig::default());
+        assert_eq!(fetcher.block_number_at_timestamp(0).await.unwrap(), 0);
+        assert_eq!(fetcher.block_number_at_timestamp(1000).await.unwrap(), 0);
+        assert_eq!(fetcher.block_number_at_timestamp(1001).await.unwrap(), 2);
+        assert_eq!(fetcher.block_number_at_timestamp(1000 + 12 * 250).await.unwrap(), 500);
+        assert_eq!(fetcher.block_number_at_timestamp(1000 + 12 * 499).await.unwrap(), 998);
+        assert!(fetcher.block_number_at_timestamp(1000 + 12 * 500).await.is_err());
+    }
+
+    /// transport that tracks the maximum number of requests in flight
+    #[derive(Debug, Default)]
+    struct CountingTransport {
+        in_flight: AtomicU64,
+        max_in_flight: AtomicU64,
+    }
+
+    #[async_trait::async_trait]
+    impl JsonRpcClient for CountingTransport {
+        type Error = ProviderError;
+
+        async fn request<T, R>(
+            &self,
+            _method: &str,
+            _params: T,
+        ) -> ::core::result::Result<R, Self::Error>
+        where
+            T: std::fmt::Debug + serde::Serialize + Send + Sync,
+            R: serde::de::DeserializeOwned + Send,
+        {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(serde_json::from_value(serde_json::to_value(U64::from(1))?)?)
+        }
+    }
+
+    #[tokio::test]
+    async fn semaphore_bounds_concurrent_requests() {
+        let provider = Provider::new(CountingTransport::default());
+        let fetcher = Arc::new(Fetcher::new(provider, Some(2), None, RetryConfig::default()));
+        let tasks: Vec<_> = (0..10)
+            .map(|_| {
+                let fetcher = fetcher.clone();
+                tokio::spawn(async move { fetcher.get_block_number().await })
+            })
+            .collect();
+        for task in futures::future::join_all(tasks).await {
+            task.unwrap().unwrap();
+        }
+        assert_eq!(fetcher.provider.as_ref().max_in_flight.load(Ordering::SeqCst), 2);
+        assert_eq!(fetcher.requests_made(), 10);
+    }
+
+    #[tokio::test]
+    async fn get_blocks_preserves_order() {
+        let retry = RetryConfig::default();
+        let provider = Provider::new(DelayedBlocks);
+        let fetcher = Fetcher::new(provider, None, None, retry);
+        let block_nums = vec![1, 2, 3, 4];
+        let blocks = fetcher.get_blocks(&block_nums).await.unwrap();
+        let numbers: Vec<u64> =
+            blocks.iter().map(|b| b.as_ref().unwrap().number.unwrap().as_u64()).collect();
+        assert_eq!(numbers, block_nums);
+    }
+
+    #[tokio::test]
+    async fn get_storage_at_many_preserves_order() {
+        let provider = Provider::new(DelayedSlots);
+        let fetcher = Fetcher::new(provider, None, None, RetryConfig::default());
+        let slots: Vec<H256> = (1..=4).map(H256::from_low_u64_be).collect();
+        let values = fetcher
+            .get_storage_at_many(H160::zero(), &slots, BlockNumber::Number(1.into()))
+            .await
+            .unwrap();
+        assert_eq!(values, slots);
+    }
+
+    #[tokio::test]
+    async fn get_logs_chunked_halves_oversized_ranges() {
+        let (fetcher, mock) = mocked_fetcher(3);
+        let log = |block_number: u64| Log {
+            block_number: Some(block_number.into()),
+            ..Default::default()
+        };
+        mock.push::<Vec<Log>, _>(vec![log(3)]).unwrap();
+        mock.push::<Vec<Log>, _>(vec![log(1)]).unwrap();
+        mock.push_response(rpc_error(-32005, "query returned more than 10000 results"));
+        let filter = Filter::new().from_block(0).to_block(3);
+        let logs = fetcher.get_logs_chunked(&filter, 4).await.unwrap();
+        let blocks: Vec<_> = logs.iter().map(|log| log.block_number.unwrap().as_u64()).collect();
+        assert_eq!(blocks, vec![1, 3]);
+    }
+
+    #[tokio::test]
+    async fn get_logs_chunked_splits_without_gaps_or_duplicates() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let log = |block_number: u64, log_index: u64| Log {
+            block_number: Some(block_number.into()),
+            block_hash: Some(H256::from_low_u64_be(block_number)),
+            log_index: Some(log_index.into()),
+            ..Default::default()
+        };
+        // the provider repeats the boundary log of block 2 in both halves
+        mock.push::<Vec<Log>, _>(vec![log(2, 0), log(2, 1), log(3, 0)]).unwrap();
+        mock.push::<Vec<Log>, _>(vec![log(0, 0), log(1, 0), log(2, 0)]).unwrap();
+        mock.push_response(rpc_error(-32005, "query returned more than 10000 results"));
+        let filter = Filter::new().from_block(0).to_block(3);
+        let logs = fetcher.get_logs_chunked(&filter, 4).await.unwrap();
+
+        let keys: Vec<_> = logs
+            .iter()
+            .map(|log| (log.block_number.unwrap().as_u64(), log.log_index.unwrap().as_u64()))
+            .collect();
+        assert_eq!(keys, vec![(0, 0), (1, 0), (2, 0), (2, 1), (3, 0)]);
+        for (from_block, to_block) in [(0, 3), (0, 1), (2, 3)] {
+            let range_filter = filter.clone().from_block(from_block).to_block(to_block);
+            mock.assert_request("eth_getLogs", [range_filter]).unwrap();
+        }
+    }
+
+    #[test]
+    fn bloom_contains_requires_topic0_and_address() {
+        let (topic0, address) = (H256::repeat_byte(1), H160::repeat_byte(2));
+        let mut bloom = Bloom::zero();
+        bloom.accrue(BloomInput::Raw(topic0.as_bytes()));
+        bloom.accrue(BloomInput::Raw(address.as_bytes()));
+
+        assert!(bloom_contains(&bloom, &[topic0], &[address]));
+        assert!(bloom_contains(&bloom, &[H256::repeat_byte(3), topic0], &[]));
+        assert!(!bloom_contains(&bloom, &[topic0], &[H160::repeat_byte(4)]));
+        assert!(!bloom_contains(&Bloom::zero(), &[topic0], &[]));
+        assert!(bloom_contains(&Bloom::zero(), &[], &[]));
+    }
+
+    #[tokio::test]
+    async fn get_logs_bloom_filtered_skips_blocks_without_topic0() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let topic0 = H256::repeat_byte(1);
+        let mut matching = Bloom::zero();
+        matching.accrue(BloomInput::Raw(topic0.as_bytes()));
+        let block =
+            |logs_bloom: Option<Bloom>| Block::<TxHash> { logs_bloom, ..Default::default() };
+        // mock responses are popped from the back, headers of blocks 10 to 14 are fetched first
+        mock.push::<Vec<Log>, _>(Vec::<Log>::new()).unwrap();
+        mock.push::<Vec<Log>, _>(Vec::<Log>::new()).unwrap();
+        for logs_bloom in
+            [None, Some(matching), Some(Bloom::zero()), Some(matching), Some(matching)]
+        {
+            mock.push::<Block<TxHash>, _>(block(logs_bloom)).unwrap();
+        }
+
+        let filter = Filter::new().from_block(10).to_block(14).topic0(topic0);
+        fetcher.get_logs_bloom_filtered(&filter, 100).await.unwrap();
+        for block_number in 10..=14 {
+            mock.assert_request("eth_getBlockByNumber", (U64::from(block_number), false)).unwrap();
+        }
+        // block 12 is skipped, block 14 has no bloom and is never skipped
+        for (from_block, to_block) in [(10, 11), (13, 14)] {
+            let range_filter = filter.clone().from_block(from_block).to_block(to_block);
+            mock.assert_request("eth_getLogs", [range_filter]).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn get_logs_bloom_filtered_stops_filtering_dense_ranges() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let topic0 = H256::repeat_byte(1);
+        let mut matching = Bloom::zero();
+        matching.accrue(BloomInput::Raw(topic0.as_bytes()));
+        mock.push::<Vec<Log>, _>(Vec::<Log>::new()).unwrap();
+        for _ in 0..BLOOM_FILTER_BATCH_SIZE {
+            let block = Block::<TxHash> { logs_bloom: Some(matching), ..Default::default() };
+            mock.push::<Block<TxHash>, _>(block).unwrap();
+        }
+
+        // only the first batch of headers is fetched before falling back to a single call
+        let filter = Filter::new().from_block(10).to_block(99).topic0(topic0);
+        fetcher.get_logs_bloom_filtered(&filter, 100).await.unwrap();
+        assert_eq!(fetcher.requests_made(), BLOOM_FILTER_BATCH_SIZE + 1);
+        for block_number in 10..10 + BLOOM_FILTER_BATCH_SIZE {
+            mock.assert_request("eth_getBlockByNumber", (U64::from(block_number), false)).unwrap();
+        }
+        mock.assert_request("eth_getLogs", [filter]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn get_logs_bloom_filtered_skips_headers_of_single_blocks() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        mock.push::<Vec<Log>, _>(Vec::<Log>::new()).unwrap();
+        let filter = Filter::new().from_block(10).to_block(10).topic0(H256::repeat_byte(1));
+        fetcher.get_logs_bloom_filtered(&filter, 1).await.unwrap();
+        assert_eq!(fetcher.requests_made(), 1);
+        mock.assert_request("eth_getLogs", [filter]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn get_logs_at_block_hash_queries_by_hash() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let block_hash = H256::repeat_byte(7);
+        let log = Log { block_hash: Some(block_hash), ..Default::default() };
+        mock.push::<Vec<Log>, _>(vec![log.clone()]).unwrap();
+        let topic0 = H256::repeat_byte(1);
+        let topics = [Some(topic0.into()), None, None, None];
+        let logs = fetcher.get_logs_at_block_hash(block_hash, topics).await.unwrap();
+
+        assert_eq!(logs, vec![log]);
+        let filter = Filter::new().at_block_hash(block_hash).topic0(topic0);
+        mock.assert_request("eth_getLogs", [filter]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn trace_get_requests_trace_address() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let tx_hash = H256::repeat_byte(9);
+        let trace = Trace {
+            trace_address: vec![1, 0],
+            transaction_hash: Some(tx_hash),
+            subtraces: 0,
+            action: Action::Call(ethers::types::Call::default()),
+            action_type: ActionType::Call,
+            result: None,
+            error: None,
+            block_hash: H256::zero(),
+            block_number: 10,
+            transaction_position: Some(2),
+        };
+        mock.push::<Option<Trace>, _>(None).unwrap();
+        mock.push::<Option<Trace>, _>(Some(trace.clone())).unwrap();
+
+        let found = fetcher.trace_get(tx_hash, vec![1, 0]).await.unwrap();
+        assert_eq!(found.map(|trace| trace.trace_address), Some(vec![1, 0]));
+        mock.assert_request("trace_get", (tx_hash, vec![U64::from(1), U64::from(0)])).unwrap();
+        assert!(fetcher.trace_get(tx_hash, vec![7]).await.unwrap().is_none());
+        assert_eq!(MethodCategory::of("trace_get"), MethodCategory::Trace);
+    }
+
+    #[tokio::test]
+    async fn resolves_relative_ranges_against_one_head() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        // only one head response, later ranges must reuse it
+        mock.push(U64::from(500)).unwrap();
+
+        assert_eq!(fetcher.resolve_range("latest-100:latest").await.unwrap(), (400, 500));
+        assert_eq!(fetcher.resolve_range("latest-1000:latest-1").await.unwrap(), (0, 499));
+        assert_eq!(fetcher.resolve_range("1_000:2000").await.unwrap(), (1000, 2000));
+        assert_eq!(fetcher.resolve_range("300:latest").await.unwrap(), (300, 500));
+        assert!(fetcher.resolve_range("latest:latest-1").await.is_err());
+        assert!(fetcher.resolve_range("latest+1:latest").await.is_err());
+        assert!(fetcher.resolve_range("latest").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn probes_trace_capabilities() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        // mock responses are popped from the back
+        mock.push_response(rpc_error(-32601, "the method debug_traceBlockByNumber does not exist"));
+        mock.push_response(rpc_error(-32000, "missing trie node"));
+        mock.push::<Vec<Trace>, _>(Vec::new()).unwrap();
+
+        let capabilities = fetcher.probe_capabilities().await.unwrap();
+        assert_eq!(
+            capabilities,
+            Capabilities { trace: true, trace_replay: true, debug_trace: false }
+        );
+        assert!(!capabilities.supports(Capability::DebugTrace));
+        mock.assert_request("trace_block", ["0x0"]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn debug_trace_block_selects_built_in_tracers() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        mock.push::<Vec<GethTrace>, _>(Vec::new()).unwrap();
+        mock.push::<Vec<GethTrace>, _>(Vec::new()).unwrap();
+
+        fetcher.debug_trace_block(5, "callTracer").await.unwrap();
+        fetcher.debug_trace_block(5, "{result: function() { return 1 }}").await.unwrap();
+        let block = serde_json::json!("0x5");
+        mock.assert_request(
+            "debug_traceBlockByNumber",
+            (&block, serde_json::json!({"tracer": "callTracer"})),
+        )
+        .unwrap();
+        let js_tracer = serde_json::json!({"tracer": "{result: function() { return 1 }}"});
+        mock.assert_request("debug_traceBlockByNumber", (&block, js_tracer)).unwrap();
+    }
+
+    #[test]
+    fn converts_geth_diffs_to_state_diffs() {
+        let (changed, born, died) =
+            (H160::repeat_byte(1), H160::repeat_byte(2), H160::repeat_byte(3));
+        let (slot, cleared) = (H256::repeat_byte(4), H256::repeat_byte(5));
+        let account =
+            |balance: u64, nonce: u64, code: &str, storage: Vec<(H256, H256)>| AccountState {
+                balance: Some(balance.into()),
+                nonce: Some(nonce.into()),
+                code: Some(code.to_string()),
+                storage: Some(storage.into_iter().collect()),
+            };
+        let pre = BTreeMap::from([
+            (changed, account(10, 1, "0x60", vec![(slot, H256::repeat_byte(6)), (cleared, slot)])),
+            (died, account(7, 1, "0x61", vec![(slot, slot)])),
+        ]);
+        let post = BTreeMap::from([
+            (
+                changed,
+                AccountState {
+                    balance: Some(9.into()),
+                    storage: Some(BTreeMap::from([(slot, H256::repeat_byte(7))])),
+                    ..Default::default()
+                },
+            ),
+            (born, account(3, 0, "0x62", vec![(slot, slot)])),
+        ]);
+
+        let trace = geth_diff_to_block_trace(DiffMode { pre, post }).unwrap();
+        let StateDiff(state_diff) = trace.state_diff.unwrap();
+        let diff = &state_diff[&changed];
+        assert_eq!(diff.balance, Diff::Changed(ChangedType { from: 10.into(), to: 9.into() }));
+        assert_eq!(diff.nonce, Diff::Same);
+        assert_eq!(diff.code, Diff::Same);
+        assert_eq!(
+            diff.storage,
+            BTreeMap::from([
+                (
+                    slot,
+                    Diff::Changed(ChangedType {
+                        from: H256::repeat_byte(6),
+                        to: H256::repeat_byte(7)
+                    })
+                ),
+                (cleared, Diff::Changed(ChangedType { from: slot, to: H256::zero() })),
+            ])
+        );
+        assert_eq!(state_diff[&born].balance, Diff::Born(3.into()));
+        assert_eq!(state_diff[&born].code, Diff::Born(Bytes::from(vec![0x62])));
+        assert_eq!(state_diff[&born].storage, BTreeMap::from([(slot, Diff::Born(slot))]));
+        assert_eq!(state_diff[&died].nonce, Diff::Died(1.into()));
+        assert_eq!(state_diff[&died].storage, BTreeMap::from([(slot, Diff::Died(slot))]));
+    }
+
+    #[tokio::test]
+    async fn get_pending_block_requests_pending_tag() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let block = Block::<Transaction> {
+            transactions: vec![Transaction::default()],
+            ..Default::default()
+        };
+        mock.push::<Block<Transaction>, _>(block.clone()).unwrap();
+        assert_eq!(fetcher.get_pending_block().await.unwrap(), Some(block));
+        mock.assert_request("eth_getBlockByNumber", ("pending", true)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn get_logs_chunked_errors_on_oversized_block() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        mock.push_response(rpc_error(-32005, "query returned more than 10000 results"));
+        let filter = Filter::new().from_block(5).to_block(5);
+        let error = fetcher.get_logs_chunked(&filter, 4).await.unwrap_err();
+        assert!(error.to_string().contains("logs of block 5"));
+    }
+
+    #[tokio::test]
+    async fn get_code_caches_historical_blocks() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let codes = Arc::new(DashMap::new());
+        let fetcher = fetcher.with_code_cache(codes.clone(), 10);
+        let address = H160::repeat_byte(1);
+        mock.push::<Bytes, _>(Bytes::from(vec![2])).unwrap();
+        mock.push::<Bytes, _>(Bytes::from(vec![1])).unwrap();
+        mock.push::<U64, _>(U64::from(100)).unwrap();
+
+        // block 90 is old enough to be cached, so the second call is served from the cache
+        for _ in 0..2 {
+            let code = fetcher.get_code(address, BlockNumber::Number(90.into())).await.unwrap();
+            assert_eq!(code, Bytes::from(vec![1]));
+        }
+        // block 95 is within the reorg depth and is always fetched
+        let code = fetcher.get_code(address, BlockNumber::Number(95.into())).await.unwrap();
+        assert_eq!(code, Bytes::from(vec![2]));
+        assert_eq!(codes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn get_transaction_receipts_keeps_missing_receipts() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        let receipt = |gas_used: u64| TransactionReceipt {
+            gas_used: Some(gas_used.into()),
+            ..Default::default()
+        };
+        mock.push(receipt(30)).unwrap();
+        mock.push(serde_json::Value::Null).unwrap();
+        mock.push(receipt(10)).unwrap();
+        let tx_hashes = vec![H256::repeat_byte(1), H256::repeat_byte(2), H256::repeat_byte(3)];
+        let receipts = fetcher.get_transaction_receipts(&tx_hashes).await.unwrap();
+        let gas_used: Vec<_> =
+            receipts.iter().map(|r| r.as_ref().and_then(|r| r.gas_used)).collect();
+        assert_eq!(gas_used, vec![Some(10.into()), None, Some(30.into())]);
+    }
+
+    #[tokio::test]
+    async fn records_latency_per_method() {
+        let (fetcher, mock) = mocked_fetcher(1);
+        let fetcher = fetcher.with_latency_stats(Arc::new(Mutex::new(LatencyStats::default())));
+        mock.push(U64::from(2)).unwrap();
+        mock.push(U64::from(1)).unwrap();
+        mock.push_response(rpc_error(429, "too many requests"));
+        fetcher.get_block_number().await.unwrap();
+        fetcher.get_block_number().await.unwrap();
+
+        let summary = fetcher.latency_summary();
+        assert_eq!(summary.keys().collect::<Vec<_>>(), vec![&"get_block_number"]);
+        let stats = &summary["get_block_number"];
+        assert_eq!(stats.count, 3);
+        assert!(stats.p50 <= stats.p95 && stats.p95 <= stats.max);
+    }
+
+    #[tokio::test]
+    async fn counts_requests_and_reports_progress() {
+        let (fetcher, mock) = mocked_fetcher(1);
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = reported.clone();
+        let fetcher = fetcher.with_progress_callback(2, move |n| sink.lock().unwrap().push(n));
+        for _ in 0..2 {
+            mock.push(U64::from(1)).unwrap();
+        }
+        mock.push_response(rpc_error(429, "too many requests"));
+        fetcher.get_block_number().await.unwrap();
+        fetcher.get_block_number().await.unwrap();
+        assert_eq!(fetcher.requests_made(), 3);
+        assert_eq!(*reported.lock().unwrap(), vec![2]);
+    }
+
+    #[tokio::test]
+    async fn reorg_safe_chunks_skips_chunks_near_head() {
+        let (fetcher, mock) = mocked_fetcher(0);
+        mock.push(U64::from(100)).unwrap();
+        let chunks = vec![
+            BlockChunk::Range(80, 89),
+            BlockChunk::Range(90, 99),
+            BlockChunk::Numbers(vec![85, 95]),
+        ];
+        let (kept, skipped) = fetcher.reorg_safe_chunks(chunks, 10).await.unwrap();
+        let max_values = |chunks: Vec<BlockChunk>| -> Vec<_> {
+            chunks.iter().map(|chunk| chunk.max_value()).collect()
+        };
+        assert_eq!(max_values(kept), vec![Some(89)]);
+        assert_eq!(max_values(skipped), vec![Some(99), Some(95)]);
+    }
+
+    #[tokio::test]
+    async fn stops_after_max_retries() {
+        let (fetcher, mock) = mocked_fetcher(1);
+        mock.push(U64::from(17_000_000)).unwrap();
+        mock.push_response(rpc_error(429, "too many requests"));
+        mock.push_response(rpc_error(429, "too many requests"));
+        assert!(fetcher.get_block_number().await.is_err());
+    }
+}
@@ -27,6 +27,10 @@ pub struct FreezeSummary {
     pub skipped: Vec<Partition>,
     /// partitions errored
     pub errored: Vec<(Option<Partition>, CollectError)>,
+    /// partitions not started because collection was cancelled
+    pub cancelled: Vec<Partition>,
+    /// rows written by completed partitions
+    pub n_rows: u64,
 }
 
 /// print all datasets
@@ -158,6 +162,7 @@ pub(crate) fn print_cryo_intro(
     sink: &FileOutput,
     env: &ExecutionEnv,
     n_chunks_remaining: u64,
+    n_requests: Option<usize>,
 ) -> Result<(), CollectError> {
     print_header("cryo parameters");
     print_bullet("version", super::reports::CRYO_VERSION);
@@ -172,11 +177,17 @@ pub(crate) fn print_cryo_intro(
 
     if env.verbose > 1 {
         print_bullet_indent("exclude failed items", query.exclude_failed.to_string(), 4);
+        let trace_status = format!("{:?}", query.trace_status).to_lowercase();
+        print_bullet_indent("trace transaction status", trace_status, 4);
     }
 
     print_bullet("source", "");
     print_bullet_indent("network", &sink.prefix, 4);
     print_bullet_indent("rpc url", &source.rpc_url, 4);
+    let rpc_urls = source.fetcher.provider.as_ref().urls();
+    if rpc_urls.len() > 1 {
+        print_bullet_indent("fallback rpc urls", rpc_urls[1..].join(", "), 4);
+    }
     match source.labels.max_requests_per_second {
         Some(max_requests_per_second) => print_bullet_indent(
             "max requests per second",
@@ -241,6 +252,17 @@ pub(crate) fn print_cryo_intro(
         (n_datatypes * query.partitions.len()).separate_with_commas()
     );
     print_bullet_indent("chunks to collect", chunk_text, 4);
+    if let Some(n_requests) = n_requests {
+        print_bullet_indent("estimated requests", n_requests.separate_with_commas(), 4);
+        if let Some(max_requests_per_second) = source.labels.max_requests_per_second {
+            let seconds = n_requests as u64 / max_requests_per_second.max(1);
+            print_bullet_indent(
+                "estimated duration",
+                format!("{} seconds (at rate limit)", seconds.separate_with_commas()),
+                4,
+            );
+        }
+    }
     print_bullet_indent("output format", sink.format.as_str(), 4);
     print_bullet_indent("output dir", sink.output_dir.clone().to_string_lossy(), 4);
 
@@ -401,6 +423,7 @@ fn print_schema(name: &Datatype, schema: &Table) {
 pub(crate) fn print_cryo_conclusion(
     freeze_summary: &FreezeSummary,
     query: &Query,
+    source: &Source,
     env: &ExecutionEnv,
 ) {
     let new_env = match env.t_end {
@@ -480,6 +503,19 @@ pub(crate) fn print_cryo_conclusion(
         ),
         4,
     );
+    if !freeze_summary.cancelled.is_empty() {
+        print_bullet_indent(
+            "chunks cancelled",
+            format!(
+                "{:>width$} / {} ({}%)",
+                freeze_summary.cancelled.len().separate_with_commas(),
+                n_chunks_str,
+                format_float((100 * freeze_summary.cancelled.len() / n_chunks) as f64),
+                width = width
+            ),
+            4,
+        );
+    }
     print_bullet_indent(
         "chunks collected",
         format!(
@@ -491,6 +527,13 @@ pub(crate) fn print_cryo_conclusion(
         ),
         4,
     );
+    let request_counts = source.fetcher.provider.as_ref().request_counts();
+    if request_counts.len() > 1 {
+        print_bullet("requests per rpc", "");
+        for (url, served, failed) in request_counts {
+            print_bullet_indent(url, format!("{} served, {} failed", served, failed), 4);
+        }
+    }
 
     print_chunks_speeds(
         freeze_summary.completed.clone(),
@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use ethers::prelude::*;
+use reqwest::header::HeaderMap;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Json rpc transport used to connect to a node
+#[derive(Debug)]
+pub enum Transport {
+    /// http transport
+    ///
+    /// failed requests are retried by `Fetcher`, so the transport itself does not retry
+    Http(Http),
+    /// websocket transport
+    Ws(Ws),
+    /// ipc transport for nodes running on the same machine
+    Ipc(Ipc),
+}
+
+impl Transport {
+    /// connect to an http(s) url, ws(s) url, or ipc socket path
+    pub async fn connect(url: &str) -> Result<Transport, ProviderError> {
+        Transport::connect_with_headers(url, HeaderMap::new()).await
+    }
+
+    /// connect to an rpc endpoint, sending `headers` with every http request
+    ///
+    /// headers are attached to the http client itself, so they are also sent on retries
+    pub async fn connect_with_headers(
+        url: &str,
+        headers: HeaderMap,
+    ) -> Result<Transport, ProviderError> {
+        let is_ws = url.starts_with("ws://") || url.starts_with("wss://");
+        if !headers.is_empty() && (is_ws || is_ipc_path(url)) {
+            return Err(ProviderError::CustomError(format!(
+                "custom headers are only supported for http rpc urls, not {}",
+                url
+            )))
+        }
+        if is_ws {
+            return Ok(Transport::Ws(Ws::connect(url).await?))
+        } else if is_ipc_path(url) {
+            return Ok(Transport::Ipc(Ipc::connect(url).await?))
+        }
+        let parsed = url
+            .parse::<reqwest::Url>()
+            .map_err(|_| ProviderError::CustomError(format!("could not parse rpc url {}", url)))?;
+        let client = reqwest::Client::builder().default_headers(headers).build().map_err(|e| {
+            ProviderError::CustomError(format!("could not build http client: {}", e))
+        })?;
+        Ok(Transport::Http(Http::new_with_client(parsed, client)))
+    }
+}
+
+/// whether an rpc url refers to a local ipc socket
+pub fn is_ipc_path(url: &str) -> bool {
+    !url.contains("://") && (url.ends_with(".ipc") || Path::new(url).exists())
+}
+
+#[async_trait::async_trait]
+impl JsonRpcClient for Transport {
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, ProviderError>
+    where
+        T: std::fmt::Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        match self {
+            Transport::Http(client) => {
+                JsonRpcClient::request(client, method, params).await.map_err(Into::into)
+            }
+            Transport::Ws(client) => {
+                JsonRpcClient::request(client, method, params).await.map_err(Into::into)
+            }
+            Transport::Ipc(client) => {
+                JsonRpcClient::request(client, method, params).await.map_err(Into::into)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_ipc_paths() {
+        assert!(is_ipc_path("/tmp/geth.ipc"));
+        assert!(is_ipc_path("reth.ipc"));
+        assert!(!is_ipc_path("http://localhost:8545"));
+        assert!(!is_ipc_path("ws://localhost:8546"));
+        assert!(!is_ipc_path("localhost:8545"));
+    }
+}
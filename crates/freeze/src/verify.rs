@@ -0,0 +1,324 @@
+use crate::{err, CollectError, Datatype, Fetcher};
+use ethers::prelude::*;
+use polars::prelude::*;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// value of an output file that differs from the chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    /// block number or transaction hash of the row
+    pub key: String,
+    /// column that differs
+    pub column: String,
+    /// value stored in the file
+    pub file_value: Option<String>,
+    /// value returned by the rpc node
+    pub chain_value: Option<String>,
+}
+
+/// result of verifying a sample of rows of an output file
+#[derive(Debug, Default)]
+pub struct VerifySummary {
+    /// number of rows re-fetched from the chain
+    pub n_checked: u64,
+    /// values that differ from the chain
+    pub mismatches: Vec<Mismatch>,
+}
+
+/// re-fetch a sample of rows of a parquet output file and compare them to the chain
+///
+/// rows are sampled evenly across the file, only columns present in the file are compared
+pub async fn verify_file<P: JsonRpcClient>(
+    path: &Path,
+    datatype: Datatype,
+    fetcher: &Fetcher<P>,
+    n_samples: usize,
+) -> Result<VerifySummary, CollectError> {
+    let (key_column, columns) = verified_columns(datatype)?;
+    let file = std::fs::File::open(path).map_err(|_| err("could not open file"))?;
+    let df = ParquetReader::new(file).finish().map_err(CollectError::PolarsError)?;
+    let keys = column_strings(&df, key_column)?
+        .ok_or_else(|| err(&format!("file has no {} column", key_column)))?;
+    let mut file_columns = Vec::new();
+    for column in columns {
+        if let Some(values) = column_strings(&df, column)? {
+            file_columns.push((*column, values));
+        }
+    }
+
+    let mut summary = VerifySummary::default();
+    for row in sample_rows(df.height(), n_samples) {
+        let key = match &keys[row] {
+            Some(key) => key.clone(),
+            None => continue,
+        };
+        let chain = match datatype {
+            Datatype::Blocks => chain_block_values(fetcher, &key).await?,
+            _ => chain_transaction_values(fetcher, &key).await?,
+        };
+        summary.n_checked += 1;
+        let chain = match chain {
+            Some(chain) => chain,
+            None => {
+                summary.mismatches.push(Mismatch {
+                    key: key.clone(),
+                    column: key_column.to_string(),
+                    file_value: Some(key),
+                    chain_value: None,
+                });
+                continue
+            }
+        };
+        for (column, values) in file_columns.iter() {
+            let chain_value = chain.get(column).cloned().flatten();
+            if values[row] != chain_value {
+                summary.mismatches.push(Mismatch {
+                    key: key.clone(),
+                    column: column.to_string(),
+                    file_value: values[row].clone(),
+                    chain_value,
+                });
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// path of the sidecar file holding the checksum of an output file
+pub fn checksum_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".sha256");
+    PathBuf::from(name)
+}
+
+/// sha256 of the data stored in an output file, as hex
+///
+/// the file is read back and hashed as uncompressed arrow ipc, so the checksum depends only on
+/// the rows and schema and not on compression settings or row group layout
+pub fn file_checksum(path: &Path) -> Result<String, CollectError> {
+    let file = std::fs::File::open(path).map_err(|_| err("could not open file"))?;
+    let df = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("parquet") => ParquetReader::new(file).finish(),
+        Some("arrow") => IpcReader::new(file).finish(),
+        Some("csv") => CsvReader::new(file).finish(),
+        Some("json") => JsonReader::new(file).with_json_format(JsonFormat::Json).finish(),
+        _ => return Err(err("unsupported output file format")),
+    };
+    let mut df = df.map_err(CollectError::PolarsError)?;
+    df.as_single_chunk();
+    let mut hasher = Sha256::new();
+    IpcWriter::new(&mut hasher)
+        .with_compression(None)
+        .finish(&mut df)
+        .map_err(CollectError::PolarsError)?;
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// write the checksum of an output file to its sidecar file
+pub fn write_checksum(path: &Path) -> Result<String, CollectError> {
+    let checksum = file_checksum(path)?;
+    std::fs::write(checksum_path(path), format!("{}\n", checksum))
+        .map_err(|_| err("could not write checksum file"))?;
+    Ok(checksum)
+}
+
+/// compare an output file to the checksum recorded in its sidecar file
+///
+/// returns None if the file has no sidecar file
+pub fn verify_checksum(path: &Path) -> Result<Option<bool>, CollectError> {
+    let recorded = match std::fs::read_to_string(checksum_path(path)) {
+        Ok(recorded) => recorded,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(_) => return Err(err("could not read checksum file")),
+    };
+    Ok(Some(recorded.trim() == file_checksum(path)?))
+}
+
+/// key column and compared columns of each verifiable datatype
+fn verified_columns(
+    datatype: Datatype,
+) -> Result<(&'static str, &'static [&'static str]), CollectError> {
+    match datatype {
+        Datatype::Blocks => Ok(("block_number", &["block_hash", "timestamp", "author"])),
+        Datatype::Transactions => {
+            Ok(("transaction_hash", &["block_number", "from_address", "to_address", "nonce"]))
+        }
+        _ => Err(err("verify supports blocks and transactions files")),
+    }
+}
+
+/// indices of up to n_samples rows spread evenly across n_rows
+fn sample_rows(n_rows: usize, n_samples: usize) -> Vec<usize> {
+    if n_samples >= n_rows {
+        return (0..n_rows).collect()
+    }
+    (0..n_samples).map(|i| i * n_rows / n_samples).collect()
+}
+
+/// render a column as comparable strings, binary data as 0x prefixed hex
+///
+/// returns None if the file does not contain the column
+fn column_strings(
+    df: &DataFrame,
+    column: &str,
+) -> Result<Option<Vec<Option<String>>>, CollectError> {
+    let series = match df.column(column) {
+        Ok(series) => series,
+        Err(_) => return Ok(None),
+    };
+    let values = match series.dtype() {
+        DataType::Binary => series
+            .binary()
+            .map_err(CollectError::PolarsError)?
+            .into_iter()
+            .map(|value| value.map(|bytes| prefix_hex::encode(bytes.to_vec())))
+            .collect(),
+        DataType::Utf8 => series
+            .utf8()
+            .map_err(CollectError::PolarsError)?
+            .into_iter()
+            .map(|value| {
+                value.map(|hex| format!("0x{}", hex.trim_start_matches("0x").to_lowercase()))
+            })
+            .collect(),
+        _ => series
+            .cast(&DataType::UInt64)
+            .map_err(CollectError::PolarsError)?
+            .u64()
+            .map_err(CollectError::PolarsError)?
+            .into_iter()
+            .map(|value| value.map(|number| number.to_string()))
+            .collect(),
+    };
+    Ok(Some(values))
+}
+
+type ChainValues = HashMap<&'static str, Option<String>>;
+
+async fn chain_block_values<P: JsonRpcClient>(
+    fetcher: &Fetcher<P>,
+    block_number: &str,
+) -> Result<Option<ChainValues>, CollectError> {
+    let block_number = block_number.parse().map_err(|_| err("invalid block number in file"))?;
+    let block = match fetcher.get_block(block_number).await? {
+        Some(block) => block,
+        None => return Ok(None),
+    };
+    Ok(Some(HashMap::from([
+        ("block_hash", block.hash.map(|hash| prefix_hex::encode(hash.as_bytes()))),
+        ("timestamp", Some(block.timestamp.to_string())),
+        ("author", block.author.map(|author| prefix_hex::encode(author.as_bytes()))),
+    ])))
+}
+
+async fn chain_transaction_values<P: JsonRpcClient>(
+    fetcher: &Fetcher<P>,
+    transaction_hash: &str,
+) -> Result<Option<ChainValues>, CollectError> {
+    let transaction_hash: H256 =
+        transaction_hash.parse().map_err(|_| err("invalid transaction hash in file"))?;
+    let tx = match fetcher.get_transaction(transaction_hash).await? {
+        Some(tx) => tx,
+        None => return Ok(None),
+    };
+    Ok(Some(HashMap::from([
+        ("block_number", tx.block_number.map(|number| number.to_string())),
+        ("from_address", Some(prefix_hex::encode(tx.from.as_bytes()))),
+        ("to_address", tx.to.map(|to| prefix_hex::encode(to.as_bytes()))),
+        ("nonce", Some(tx.nonce.to_string())),
+    ])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RetryConfig;
+    use std::time::Duration;
+
+    #[test]
+    fn samples_rows_evenly() {
+        assert_eq!(sample_rows(3, 10), vec![0, 1, 2]);
+        assert_eq!(sample_rows(10, 4), vec![0, 2, 5, 7]);
+    }
+
+    #[test]
+    fn checksums_ignore_compression() {
+        let mut df = df!(
+            "block_number" => [10u32, 11, 12],
+            "block_hash" => [b"a".as_slice(), b"b".as_slice(), b"c".as_slice()],
+        )
+        .unwrap();
+        let dir = std::env::temp_dir();
+        let lz4 = dir.join(format!("cryo_checksum_lz4_{}.parquet", std::process::id()));
+        let zstd = dir.join(format!("cryo_checksum_zstd_{}.parquet", std::process::id()));
+        ParquetWriter::new(std::fs::File::create(&lz4).unwrap())
+            .with_compression(ParquetCompression::Lz4Raw)
+            .finish(&mut df)
+            .unwrap();
+        ParquetWriter::new(std::fs::File::create(&zstd).unwrap())
+            .with_compression(ParquetCompression::Zstd(None))
+            .with_row_group_size(Some(1))
+            .finish(&mut df)
+            .unwrap();
+
+        let checksum = write_checksum(&lz4).unwrap();
+        assert_eq!(checksum.len(), 64);
+        assert_eq!(file_checksum(&zstd).unwrap(), checksum);
+        assert_eq!(verify_checksum(&lz4).unwrap(), Some(true));
+        assert_eq!(verify_checksum(&zstd).unwrap(), None);
+
+        let mut changed = df!("block_number" => [10u32, 11, 13], "block_hash" => df.column("block_hash").unwrap().clone()).unwrap();
+        ParquetWriter::new(std::fs::File::create(&lz4).unwrap()).finish(&mut changed).unwrap();
+        assert_eq!(verify_checksum(&lz4).unwrap(), Some(false));
+
+        for path in [checksum_path(&lz4), lz4, zstd] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_block_mismatches() {
+        let mut df = df!(
+            "block_number" => [10u32, 11],
+            "block_hash" => [H256::repeat_byte(1).as_bytes(), H256::repeat_byte(2).as_bytes()],
+            "timestamp" => [100u32, 110],
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join("cryo_verify_blocks.parquet");
+        ParquetWriter::new(std::fs::File::create(&path).unwrap()).finish(&mut df).unwrap();
+
+        let (provider, mock) = Provider::mocked();
+        let retry = RetryConfig {
+            max_retries: 0,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            jitter: false,
+        };
+        let fetcher = Fetcher::new(provider, None, None, retry);
+        let block = |byte: u8, timestamp: u64| Block::<TxHash> {
+            hash: Some(H256::repeat_byte(byte)),
+            timestamp: timestamp.into(),
+            ..Default::default()
+        };
+        // mock responses are popped from the back
+        mock.push::<Block<TxHash>, _>(block(2, 111)).unwrap();
+        mock.push::<Block<TxHash>, _>(block(1, 100)).unwrap();
+
+        let summary = verify_file(&path, Datatype::Blocks, &fetcher, 10).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(summary.n_checked, 2);
+        assert_eq!(
+            summary.mismatches,
+            vec![Mismatch {
+                key: "11".to_string(),
+                column: "timestamp".to_string(),
+                file_value: Some("110".to_string()),
+                chain_value: Some("111".to_string()),
+            }]
+        );
+    }
+}
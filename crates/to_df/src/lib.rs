@@ -42,6 +42,9 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
                 "Vec < Option < Vec < u8 > > >" => {
                     syn::Ident::new("with_series_binary", Span::call_site())
                 }
+                "Vec < Vec < Vec < u8 > > >" => {
+                    syn::Ident::new("with_series_binary_list", Span::call_site())
+                }
                 "Vec < U256 >" => syn::Ident::new("with_series_u256", Span::call_site()),
                 "Vec < Option < U256 > >" => {
                     syn::Ident::new("with_series_option_u256", Span::call_site())
@@ -83,7 +86,7 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
                                     ColumnEncoding::Binary => {
                                         cols.push(Series::new(full_name, Vec::<Vec<u8>>::new()))
                                     },
-                                    ColumnEncoding::Hex => {
+                                    ColumnEncoding::Hex | ColumnEncoding::UnprefixedHex => {
                                         cols.push(Series::new(full_name, Vec::<String>::new()))
                                     },
                                 }
@@ -93,7 +96,7 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
                             U256Type::F64 => cols.push(Series::new(full_name, Vec::<f64>::new())),
                             U256Type::U32 => cols.push(Series::new(full_name, Vec::<u32>::new())),
                             U256Type::U64 => cols.push(Series::new(full_name, Vec::<u64>::new())),
-                            U256Type::Decimal128 => cols.push(Series::new(full_name, Vec::<Vec<u8>>::new())),
+                            U256Type::Decimal128 => cols.push(decimal128_series(full_name, vec![])),
                         }
                     }
                 }
@@ -106,17 +109,26 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
                     for param in decoder.event.inputs.iter() {
                         let name = "event__".to_string() + param.name.as_str();
                         let name = name.as_str();
+                        if LogDecoder::is_hashed(param) {
+                            let hash_name = name.to_string() + "_hash";
+                            match schema.binary_type {
+                                ColumnEncoding::Binary => cols.push(Series::new(hash_name.as_str(), Vec::<Vec<u8>>::new())),
+                                ColumnEncoding::Hex | ColumnEncoding::UnprefixedHex => cols.push(Series::new(hash_name.as_str(), Vec::<String>::new())),
+                            }
+                            cols.push(Series::new((name.to_string() + "_hashed").as_str(), Vec::<bool>::new()));
+                            continue
+                        }
                         match param.kind {
                             ParamType::Address => {
                                 match schema.binary_type {
                                     ColumnEncoding::Binary => cols.push(Series::new(name, Vec::<Vec<u8>>::new())),
-                                    ColumnEncoding::Hex => cols.push(Series::new(name, Vec::<String>::new())),
+                                    ColumnEncoding::Hex | ColumnEncoding::UnprefixedHex => cols.push(Series::new(name, Vec::<String>::new())),
                                 }
                             },
                             ParamType::Bytes => {
                                 match schema.binary_type {
                                     ColumnEncoding::Binary => cols.push(Series::new(name, Vec::<Vec<u8>>::new())),
-                                    ColumnEncoding::Hex => cols.push(Series::new(name, Vec::<String>::new())),
+                                    ColumnEncoding::Hex | ColumnEncoding::UnprefixedHex => cols.push(Series::new(name, Vec::<String>::new())),
                                 }
                             },
                             ParamType::Int(bits) => {
@@ -194,6 +206,8 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
             "Vec < Option < f64 > >" => Some(quote! { ColumnType::Float64 }),
             "Vec < Option < String > >" => Some(quote! { ColumnType::String }),
             "Vec < Option < Vec < u8 > > >" => Some(quote! { ColumnType::Binary }),
+
+            "Vec < Vec < Vec < u8 > > >" => Some(quote! { ColumnType::BinaryList }),
             _ => None,
             // _ => quote! {ColumnType::Binary},
         }
@@ -203,6 +217,27 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
         datatypes[0].segments.iter().map(|seg| seg.ident.to_string()).collect::<Vec<_>>();
     let datatype_str = datatype_str.iter().last().unwrap();
 
+    // doc comments of fields are used as column descriptions
+    let mut column_descriptions = Vec::new();
+    for field in input.fields.iter() {
+        let docs: Vec<_> = field
+            .attrs
+            .iter()
+            .filter_map(|attr| match attr.parse_meta() {
+                Ok(syn::Meta::NameValue(meta)) if meta.path.is_ident("doc") => match meta.lit {
+                    syn::Lit::Str(doc) => Some(doc.value().trim().to_string()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        if let (Some(name), false) = (&field.ident, docs.is_empty()) {
+            let field_name_str = format!("{}", quote!(#name));
+            let description = docs.join(" ");
+            column_descriptions.push(quote! { (#field_name_str, #description) });
+        }
+    }
+
     let mut column_types = Vec::new();
     for (name, ty) in field_names_and_types.iter() {
         if let Some(column_type) = map_type_to_column_type(ty) {
@@ -247,6 +282,10 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
                 output.insert(datatype, df);
                 Ok(output)
             }
+
+            fn n_rows(&self) -> u64 {
+                self.n_rows as u64
+            }
         }
 
         impl ColumnData for #name {
@@ -256,6 +295,12 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
                     #(#column_types),*
                 ])
             }
+
+            fn column_descriptions() -> std::collections::HashMap<&'static str, &'static str> {
+                std::collections::HashMap::from_iter(vec![
+                    #(#column_descriptions),*
+                ])
+            }
         }
     };
 